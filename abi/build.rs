@@ -1,32 +1,73 @@
-use std::process::Command;
+use std::{env, path::PathBuf, process::Command};
 
 use tonic_build::Builder;
 
 fn main() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
     tonic_build::configure()
         .out_dir("src/pb")
+        // for server reflection
+        .file_descriptor_set_path(out_dir.join("reservation_descriptor.bin"))
         // .emit_rerun_if_changed(true)
         .with_sql_type(&["reservation.ReservationStatus"])
+        .with_serde(&[
+            "reservation.BusinessHours",
+            "reservation.OpeningHours",
+            "reservation.AttributeCondition",
+            "reservation.AttributeValue",
+            "reservation.AttributeValue.value",
+        ])
         .with_builder(&[
             "reservation.ReservationQuery",
             "reservation.ReservationFilter",
+            "reservation.ReservationSearch",
+            "reservation.ApprovalQuery",
         ])
         .with_builder_into(
             "reservation.ReservationQuery",
-            &["resource_id", "user_id", "status", "page", "desc"],
+            &[
+                "resource_id",
+                "user_id",
+                "status",
+                "page",
+                "desc",
+                "limit",
+                "label_selector",
+                "tenant_id",
+            ],
         )
         .with_builder_into(
             "reservation.ReservationFilter",
-            &["resource_id", "user_id", "status", "desc"],
+            &[
+                "resource_id",
+                "user_id",
+                "status",
+                "desc",
+                "include_total",
+                "order_by",
+                "label_selector",
+                "tenant_id",
+            ],
+        )
+        .with_builder_into(
+            "reservation.ReservationSearch",
+            &["text", "resource_id", "user_id", "status", "tenant_id"],
         )
+        .with_builder_into("reservation.ApprovalQuery", &["approver", "tenant_id"])
         .with_builder_option("reservation.ReservationFilter", &["cursor"])
+        .with_builder_option("reservation.ApprovalQuery", &["cursor"])
+        .with_builder_option("reservation.ReservationSearch", &["cursor", "start", "end"])
         .with_builder_option("reservation.ReservationQuery", &["start", "end"])
         .with_field_attributes(
             &["page_size"],
             &["#[builder(setter(into), default = \"10\")]"],
         )
         .with_type_attributes(
-            &["reservation.ReservationFilter"],
+            &[
+                "reservation.ReservationFilter",
+                "reservation.ReservationSearch",
+                "reservation.ApprovalQuery",
+            ],
             &[r#"#[builder(build_fn(name = "private_build"))]"#],
         )
         .compile(&["protos/reservation.proto"], &["protos"])
@@ -38,6 +79,7 @@ fn main() {
 
 trait BuilderExt {
     fn with_sql_type(self, paths: &[&str]) -> Self;
+    fn with_serde(self, paths: &[&str]) -> Self;
     fn with_builder(self, paths: &[&str]) -> Self;
     fn with_builder_into(self, path: &str, fields: &[&str]) -> Self;
     fn with_builder_option(self, path: &str, fields: &[&str]) -> Self;
@@ -52,6 +94,12 @@ impl BuilderExt for Builder {
         })
     }
 
+    fn with_serde(self, paths: &[&str]) -> Self {
+        paths.iter().fold(self, |acc, path| {
+            acc.type_attribute(path, "#[derive(serde::Serialize, serde::Deserialize)]")
+        })
+    }
+
     fn with_builder(self, paths: &[&str]) -> Self {
         paths.iter().fold(self, |acc, path| {
             acc.type_attribute(path, "#[derive(derive_builder::Builder)]")
@@ -1,19 +1,44 @@
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
 
 use serde::{Deserialize, Serialize};
 
-use crate::Error;
+use crate::{Error, Validator};
 
 #[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct Config {
     pub db: DbConfig,
     pub server: ServerConfig,
+    #[serde(default)]
+    pub pager: PagerConfig,
+    #[serde(default)]
+    pub no_show: NoShowConfig,
+    /// callers have to send a bearer token if set, otherwise everyone is trusted.
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    #[serde(default)]
+    pub rbac: RbacConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
 }
 
 impl Config {
     pub fn load(filename: impl AsRef<Path>) -> Result<Self, Error> {
         let f: String = std::fs::read_to_string(filename).map_err(|_| Error::ConfigReadError)?;
-        serde_yaml::from_str(&f).map_err(|_| Error::ConfigReadError)
+        let config: Self = serde_yaml::from_str(&f).map_err(|_| Error::ConfigReadError)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl Validator for Config {
+    fn validate(&self) -> Result<(), Error> {
+        // a zero period can't be ticked, and no-shows would never be released
+        if self.no_show.interval == 0 {
+            return Err(Error::InvalidConfig(
+                "no_show.interval has to be at least 1 second".into(),
+            ));
+        }
+        Ok(())
     }
 }
 
@@ -53,6 +78,17 @@ fn default_pool_size() -> u32 {
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// serve over TLS if set, otherwise plaintext.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// how long calls in flight, streams in particular, may take to finish once the server is
+    /// shutting down, in seconds.
+    #[serde(default = "default_drain_period")]
+    pub drain_period: u64,
+}
+
+fn default_drain_period() -> u64 {
+    30
 }
 
 impl ServerConfig {
@@ -64,6 +100,115 @@ impl ServerConfig {
     }
 }
 
+/// pem files of the server's certificate chain and key.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct TlsConfig {
+    pub cert: String,
+    pub key: String,
+    /// if set, clients have to present a certificate signed by this CA (mutual TLS). The
+    /// common name of its subject is the caller's user id, the organization their tenant.
+    #[serde(default)]
+    pub client_ca: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct PagerConfig {
+    /// secret used to sign page tokens. If empty, a random one is generated on startup,
+    /// so tokens are only valid until the server restarts.
+    #[serde(default)]
+    pub secret: String,
+    /// when the planner estimates more rows than this, the estimation is returned as the
+    /// filter total instead of counting every row.
+    #[serde(default = "default_exact_count_threshold")]
+    pub exact_count_threshold: i64,
+}
+
+impl Default for PagerConfig {
+    fn default() -> Self {
+        Self {
+            secret: String::new(),
+            exact_count_threshold: default_exact_count_threshold(),
+        }
+    }
+}
+
+fn default_exact_count_threshold() -> i64 {
+    10_000
+}
+
+/// how bearer tokens (JWTs) of callers are verified. The `sub` claim is the caller's user id.
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct AuthConfig {
+    pub algorithm: JwtAlgorithm,
+    /// shared secret of HS256 tokens.
+    #[serde(default)]
+    pub secret: String,
+    /// pem file of the public key RS256 tokens are signed with.
+    #[serde(default)]
+    pub public_key: String,
+    /// local JWKS file, RS256 tokens are verified with the key matching their `kid` instead.
+    #[serde(default)]
+    pub jwks: String,
+    /// if set, tokens have to be issued by or for them.
+    #[serde(default)]
+    pub issuer: String,
+    #[serde(default)]
+    pub audience: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub enum JwtAlgorithm {
+    HS256,
+    RS256,
+}
+
+/// who may do what once callers are authenticated.
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RbacConfig {
+    /// roles of users on top of the ones in their tokens, e.g. `alice: [admin]`.
+    #[serde(default)]
+    pub grants: BTreeMap<String, Vec<String>>,
+}
+
+/// how often each caller may call the RPCs, calls over the limit fail with `ResourceExhausted`.
+#[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct RateLimitConfig {
+    /// limit of the RPCs not in `methods`, unlimited if not set.
+    #[serde(default)]
+    pub default: Option<RateLimit>,
+    /// limits by RPC name, e.g. `reserve: { rate: 5, burst: 10 }`.
+    #[serde(default)]
+    pub methods: BTreeMap<String, RateLimit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone, Copy)]
+pub struct RateLimit {
+    /// calls per second.
+    pub rate: u32,
+    /// calls that can be made at once after being idle, `rate` if 0.
+    #[serde(default)]
+    pub burst: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Clone)]
+pub struct NoShowConfig {
+    /// how often confirmed reservations not checked in in time are looked for, in seconds.
+    #[serde(default = "default_no_show_interval")]
+    pub interval: u64,
+}
+
+impl Default for NoShowConfig {
+    fn default() -> Self {
+        Self {
+            interval: default_no_show_interval(),
+        }
+    }
+}
+
+fn default_no_show_interval() -> u64 {
+    60
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,8 +230,36 @@ mod tests {
                 server: ServerConfig {
                     host: String::from("0.0.0.0"),
                     port: 50051,
+                    tls: None,
+                    drain_period: 30,
+                },
+                pager: PagerConfig {
+                    secret: String::from("luckychacha-page-secret"),
+                    exact_count_threshold: 10_000,
+                },
+                no_show: NoShowConfig { interval: 60 },
+                auth: None,
+                rbac: RbacConfig {
+                    grants: BTreeMap::from([(
+                        String::from("luckychacha-admin"),
+                        vec![String::from("admin")]
+                    )]),
                 },
+                rate_limit: RateLimitConfig::default(),
             })
         );
     }
+
+    #[test]
+    fn zero_no_show_interval_should_be_rejected() {
+        let mut config = Config::load("../service/fixtures/config.yml").unwrap();
+        assert!(config.validate().is_ok());
+        config.no_show.interval = 0;
+        assert_eq!(
+            config.validate(),
+            Err(Error::InvalidConfig(
+                "no_show.interval has to be at least 1 second".into()
+            ))
+        );
+    }
 }
@@ -79,11 +79,15 @@ impl FromStr for ParsedInfo {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new(r"\((?P<k1>[a-zA-Z0-9-_]+),\s*(?P<k2>[a-zA-Z0-9-_]+)\)=\((?P<v1>[a-zA-Z0-9-_]+),\s*\[(?P<v2>[^\]\)]+)").unwrap();
+        // the key starts with the tenant since reservations are isolated by tenant
+        let re = Regex::new(r"\((?:(?P<k0>[a-zA-Z0-9-_]+),\s*)?(?P<k1>[a-zA-Z0-9-_]+),\s*(?P<k2>[a-zA-Z0-9-_]+)\)=\((?:(?P<v0>[^,]*),\s*)?(?P<v1>[a-zA-Z0-9-_]+),\s*\[(?P<v2>[^\]\)]+)").unwrap();
 
         let mut maps = vec![];
         for caps in re.captures_iter(s) {
             let mut tmp = HashMap::new();
+            if let (Some(k0), Some(v0)) = (caps.name("k0"), caps.name("v0")) {
+                tmp.insert(k0.as_str().to_string(), v0.as_str().to_string());
+            }
             tmp.insert(caps["k1"].to_string(), caps["v1"].to_string());
             tmp.insert(caps["k2"].to_string(), caps["v2"].to_string());
 
@@ -146,4 +150,16 @@ mod tests {
             ReservationConflictInfo::Unparsed(_) => panic!("should be parsed"),
         }
     }
+
+    #[test]
+    fn conflict_error_message_with_tenant_should_parse() {
+        let msg = "Key (tenant_id, resource_id, timespan)=(, ocean-view-room-666, [\"2022-12-25 07:00:00+00\",\"2022-12-28 03:00:00+00\")) conflicts with existing key (tenant_id, resource_id, timespan)=(, ocean-view-room-666, [\"2022-12-25 07:00:00+00\",\"2022-12-28 03:00:00+00\")).";
+        let info: ParsedInfo = msg.parse().unwrap();
+        assert_eq!(info.new["tenant_id"], "");
+        assert_eq!(info.old["resource_id"], "ocean-view-room-666");
+
+        let conflict: ReservationConflict = msg.replace("(, ", "(sales, ").parse().unwrap();
+        assert_eq!(conflict.new.rid, "ocean-view-room-666");
+        assert_eq!(conflict.old.end.to_rfc3339(), "2022-12-28T03:00:00+00:00");
+    }
 }
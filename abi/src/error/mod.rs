@@ -2,7 +2,7 @@ mod conflict;
 
 use sqlx::postgres::PgDatabaseError;
 
-pub use conflict::{ReservationConflictInfo, ReservationWindow};
+pub use conflict::{ReservationConflict, ReservationConflictInfo, ReservationWindow};
 use tonic::Status;
 
 #[derive(thiserror::Error, Debug)]
@@ -16,6 +16,9 @@ pub enum Error {
     #[error("parse config.toml failed")]
     ConfigParseError,
 
+    #[error("Invalid config: {0}")]
+    InvalidConfig(String),
+
     #[error("Reservation Conflict Error.")]
     ConflictReservation(ReservationConflictInfo),
 
@@ -38,11 +41,94 @@ pub enum Error {
     InvalidPageSize(i64),
 
     #[error("Invalid cursor :{0}")]
-    InvalidCursor(i64),
+    InvalidCursor(String),
 
     #[error("Invalid status: {0}")]
     InvalidStatus(i32),
 
+    #[error("Invalid sort key: {0}")]
+    InvalidSortKey(i32),
+
+    #[error("Invalid query limit: {0}")]
+    InvalidQueryLimit(i64),
+
+    #[error("Invalid search text: {0:?}")]
+    InvalidSearchText(String),
+
+    #[error("Invalid label: {0:?}")]
+    InvalidLabel(String),
+
+    #[error("Invalid label selector: {0:?}")]
+    InvalidLabelSelector(String),
+
+    #[error("Invalid attribute: {0:?}")]
+    InvalidAttribute(String),
+
+    #[error("No resource matches the requirements.")]
+    NoResourceMatched,
+
+    #[error("Resource not found: {0}")]
+    ResourceNotFound(String),
+
+    #[error("Invalid parent resource: {0}")]
+    InvalidParentResource(String),
+
+    #[error("Invalid booking policy: {0}")]
+    InvalidBookingPolicy(String),
+
+    #[error("Invalid business hours: {0}")]
+    InvalidBusinessHours(String),
+
+    #[error("Invalid turnaround buffer: {0}")]
+    InvalidBuffer(String),
+
+    #[error("Invalid quota: {0}")]
+    InvalidQuota(String),
+
+    #[error("Quota not found: {0}")]
+    QuotaNotFound(String),
+
+    #[error("Quota {quota} exceeded: limit {limit}, current usage {usage}")]
+    QuotaExceeded {
+        quota: String,
+        limit: i64,
+        usage: i64,
+    },
+
+    #[error("Waitlist entry not found: {0}")]
+    WaitlistEntryNotFound(i64),
+
+    #[error("Invalid approver: {0:?}")]
+    InvalidApprover(String),
+
+    #[error("Reservation {0} has to be approved by an approver of its resource")]
+    ApprovalRequired(i64),
+
+    #[error("Reservation {id} can be extended by at most {}s", max_extension.num_seconds())]
+    ExtensionConflict {
+        id: i64,
+        conflict: ReservationConflictInfo,
+        max_extension: chrono::Duration,
+    },
+
+    #[error("Invalid check-in window: {0}")]
+    InvalidCheckInWindow(String),
+
+    #[error("Check-in not allowed: {0}")]
+    CheckInNotAllowed(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Missing field: {0}")]
+    MissingField(String),
+
+    #[error("Booking policy violation ({rule}): {detail}")]
+    PolicyViolation { rule: String, detail: String },
+
+    #[error("Server is shutting down")]
+    ShuttingDown,
+
     #[error("unknown error")]
     Unknown,
 }
@@ -53,15 +139,49 @@ impl From<Error> for Status {
             Error::DbError(_)
             | Error::ConfigReadError
             | Error::ConfigParseError
+            | Error::InvalidConfig(_)
             | Error::InvalidPageSize(_)
-            | Error::InvalidStatus(_)
-            | Error::InvalidCursor(_) => Status::internal(e.to_string()),
+            | Error::InvalidStatus(_) => Status::internal(e.to_string()),
+            Error::InvalidCursor(_)
+            | Error::InvalidSortKey(_)
+            | Error::InvalidQueryLimit(_)
+            | Error::InvalidSearchText(_)
+            | Error::InvalidLabel(_)
+            | Error::InvalidLabelSelector(_)
+            | Error::InvalidAttribute(_)
+            | Error::InvalidParentResource(_)
+            | Error::InvalidBookingPolicy(_)
+            | Error::InvalidBusinessHours(_)
+            | Error::InvalidBuffer(_)
+            | Error::InvalidQuota(_)
+            | Error::InvalidApprover(_)
+            | Error::InvalidCheckInWindow(_)
+            | Error::MissingField(_)
+            | Error::PolicyViolation { .. } => Status::invalid_argument(e.to_string()),
             Error::ConflictReservation(_)
             | Error::ReservationNotFound
             | Error::InvalidReservationId(_)
             | Error::InvalidTime
             | Error::InvalidUserId(_)
-            | Error::InvalidResourceId(_) => Status::failed_precondition(e.to_string()),
+            | Error::InvalidResourceId(_)
+            | Error::NoResourceMatched
+            | Error::ResourceNotFound(_)
+            | Error::QuotaNotFound(_)
+            | Error::WaitlistEntryNotFound(_)
+            | Error::ApprovalRequired(_)
+            | Error::CheckInNotAllowed(_) => Status::failed_precondition(e.to_string()),
+            // clients may retry with the longest extension possible right away
+            Error::ExtensionConflict { max_extension, .. } => {
+                let mut status = Status::failed_precondition(e.to_string());
+                status.metadata_mut().insert(
+                    "max-extension-seconds",
+                    max_extension.num_seconds().to_string().parse().unwrap(),
+                );
+                status
+            }
+            Error::QuotaExceeded { .. } => Status::resource_exhausted(e.to_string()),
+            Error::PermissionDenied(_) => Status::permission_denied(e.to_string()),
+            Error::ShuttingDown => Status::unavailable(e.to_string()),
             Error::Unknown => Status::unknown(e.to_string()),
         }
     }
@@ -77,6 +197,63 @@ impl PartialEq for Error {
             (Error::InvalidTime, Error::InvalidTime) => true,
             (Error::InvalidUserId(v1), Error::InvalidUserId(v2)) => v1 == v2,
             (Error::InvalidResourceId(v1), Error::InvalidResourceId(v2)) => v1 == v2,
+            (Error::InvalidCursor(v1), Error::InvalidCursor(v2)) => v1 == v2,
+            (Error::InvalidQueryLimit(v1), Error::InvalidQueryLimit(v2)) => v1 == v2,
+            (Error::InvalidSearchText(v1), Error::InvalidSearchText(v2)) => v1 == v2,
+            (Error::InvalidLabel(v1), Error::InvalidLabel(v2)) => v1 == v2,
+            (Error::InvalidLabelSelector(v1), Error::InvalidLabelSelector(v2)) => v1 == v2,
+            (Error::InvalidAttribute(v1), Error::InvalidAttribute(v2)) => v1 == v2,
+            (Error::NoResourceMatched, Error::NoResourceMatched) => true,
+            (Error::ResourceNotFound(v1), Error::ResourceNotFound(v2)) => v1 == v2,
+            (Error::InvalidParentResource(v1), Error::InvalidParentResource(v2)) => v1 == v2,
+            (Error::InvalidBookingPolicy(v1), Error::InvalidBookingPolicy(v2)) => v1 == v2,
+            (Error::InvalidBusinessHours(v1), Error::InvalidBusinessHours(v2)) => v1 == v2,
+            (Error::InvalidBuffer(v1), Error::InvalidBuffer(v2)) => v1 == v2,
+            (Error::InvalidQuota(v1), Error::InvalidQuota(v2)) => v1 == v2,
+            (Error::QuotaNotFound(v1), Error::QuotaNotFound(v2)) => v1 == v2,
+            (Error::WaitlistEntryNotFound(v1), Error::WaitlistEntryNotFound(v2)) => v1 == v2,
+            (Error::InvalidApprover(v1), Error::InvalidApprover(v2)) => v1 == v2,
+            (Error::ApprovalRequired(v1), Error::ApprovalRequired(v2)) => v1 == v2,
+            (Error::InvalidCheckInWindow(v1), Error::InvalidCheckInWindow(v2)) => v1 == v2,
+            (Error::CheckInNotAllowed(v1), Error::CheckInNotAllowed(v2)) => v1 == v2,
+            (Error::PermissionDenied(v1), Error::PermissionDenied(v2)) => v1 == v2,
+            (Error::MissingField(v1), Error::MissingField(v2)) => v1 == v2,
+            (Error::InvalidConfig(v1), Error::InvalidConfig(v2)) => v1 == v2,
+            (Error::ShuttingDown, Error::ShuttingDown) => true,
+            (
+                Error::QuotaExceeded {
+                    quota: q1,
+                    limit: l1,
+                    usage: u1,
+                },
+                Error::QuotaExceeded {
+                    quota: q2,
+                    limit: l2,
+                    usage: u2,
+                },
+            ) => q1 == q2 && l1 == l2 && u1 == u2,
+            (
+                Error::ExtensionConflict {
+                    id: i1,
+                    conflict: c1,
+                    max_extension: m1,
+                },
+                Error::ExtensionConflict {
+                    id: i2,
+                    conflict: c2,
+                    max_extension: m2,
+                },
+            ) => i1 == i2 && c1 == c2 && m1 == m2,
+            (
+                Error::PolicyViolation {
+                    rule: r1,
+                    detail: d1,
+                },
+                Error::PolicyViolation {
+                    rule: r2,
+                    detail: d2,
+                },
+            ) => r1 == r2 && d1 == d2,
             (Error::Unknown, Error::Unknown) => true,
             _ => false,
         }
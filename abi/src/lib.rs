@@ -7,8 +7,10 @@ mod utils;
 
 // 这样在别的地方引用 abi 深层代码的时候就可以直接 abi::xxx 了
 pub use config::*;
-pub use error::{Error, ReservationConflictInfo, ReservationWindow};
+pub use error::{Error, ReservationConflict, ReservationConflictInfo, ReservationWindow};
+pub use pager::{Cursor, Direction, Id, PageInfo, PageSigner, PageToken, Pager, Paginator};
 pub use pb::*;
+pub use types::{merge_spans, subtract_spans, LabelRequirement, LabelSelector, SearchHit, Span};
 
 pub use utils::*;
 
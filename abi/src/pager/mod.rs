@@ -0,0 +1,253 @@
+mod token;
+
+use std::collections::VecDeque;
+
+pub use token::{PageSigner, PageToken};
+
+/// which way a page is read relative to its cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// rows after the cursor, in the filter's sort order
+    Forward,
+    /// rows before the cursor, in the filter's sort order
+    Backward,
+}
+
+/// position of a page boundary in the (sort key, id) order. The row at the boundary itself
+/// is never part of the page. If rows are sorted by id, `key` is the id too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub direction: Direction,
+    pub key: i64,
+    pub id: i64,
+}
+
+#[derive(Debug)]
+pub struct PageInfo {
+    pub cursor: Option<Cursor>,
+    pub page_size: i64,
+    pub desc: bool,
+}
+
+pub struct Pager {
+    pub prev: Option<Cursor>,
+    pub next: Option<Cursor>,
+    pub total: Option<i64>,
+}
+
+pub trait Paginator: Sized {
+    /// pager for rows sorted by id.
+    fn get_pager<T: Id>(&self, data: &mut VecDeque<T>) -> Pager {
+        self.get_pager_by(data, |v| v.id())
+    }
+    /// pager for rows sorted by `key` first and by id for rows with the same key.
+    fn get_pager_by<T: Id>(&self, data: &mut VecDeque<T>, key: impl Fn(&T) -> i64) -> Pager;
+    fn prev_page(&self, pager: &Pager) -> Option<Self>;
+    fn next_page(&self, pager: &Pager) -> Option<Self>;
+}
+
+pub trait Id {
+    fn id(&self) -> i64;
+}
+
+/// rows which can be sorted by other columns than their id.
+pub trait SortKey<K>: Id {
+    fn sort_key(&self, by: K) -> i64;
+}
+
+impl Cursor {
+    pub fn forward(key: i64, id: i64) -> Self {
+        Self {
+            direction: Direction::Forward,
+            key,
+            id,
+        }
+    }
+
+    pub fn backward(key: i64, id: i64) -> Self {
+        Self {
+            direction: Direction::Backward,
+            key,
+            id,
+        }
+    }
+}
+
+impl PageInfo {
+    /// whether rows are fetched in reverse of the order they are presented.
+    pub fn is_backward(&self) -> bool {
+        matches!(
+            self.cursor,
+            Some(Cursor {
+                direction: Direction::Backward,
+                ..
+            })
+        )
+    }
+
+    fn with_cursor(&self, cursor: Option<Cursor>) -> Option<Self> {
+        cursor.map(|cursor| PageInfo {
+            cursor: Some(cursor),
+            page_size: self.page_size,
+            desc: self.desc,
+        })
+    }
+}
+
+impl Paginator for PageInfo {
+    // `data` is expected in fetch order with at most one extra row, which tells us
+    // whether there are more rows beyond this page in the fetch direction.
+    fn get_pager_by<T: Id>(&self, data: &mut VecDeque<T>, key: impl Fn(&T) -> i64) -> Pager {
+        let has_more = data.len() as i64 > self.page_size;
+        if has_more {
+            data.pop_back();
+        }
+
+        let backward = self.is_backward();
+        if backward {
+            data.make_contiguous().reverse();
+        }
+
+        // the page we came from always exists on the side of the cursor
+        let (has_prev, has_next) = if backward {
+            (has_more, true)
+        } else {
+            (self.cursor.is_some(), has_more)
+        };
+
+        let prev = if has_prev {
+            data.front().map(|v| Cursor::backward(key(v), v.id()))
+        } else {
+            None
+        };
+
+        let next = if has_next {
+            data.back().map(|v| Cursor::forward(key(v), v.id()))
+        } else {
+            None
+        };
+
+        Pager {
+            prev,
+            next,
+            total: None,
+        }
+    }
+
+    fn prev_page(&self, pager: &Pager) -> Option<Self> {
+        self.with_cursor(pager.prev)
+    }
+
+    fn next_page(&self, pager: &Pager) -> Option<Self> {
+        self.with_cursor(pager.next)
+    }
+}
+
+#[cfg(test)]
+pub mod pager_test_utils {
+    use std::collections::VecDeque;
+
+    use super::{Id, SortKey};
+
+    pub struct TestId(i64);
+
+    pub fn generate_test_ids(start: i64, end: i64) -> VecDeque<TestId> {
+        (start..=end).map(TestId).collect()
+    }
+
+    // pub fn generate_test_ids_2(start: i64, end: i64) -> VecDeque<TestId> {
+    //     (start..=end).map(|i| TestId(i)).collect()
+    // }
+
+    impl Id for TestId {
+        fn id(&self) -> i64 {
+            self.0
+        }
+    }
+
+    // every test row sorts the same by any key
+    impl<K> SortKey<K> for TestId {
+        fn sort_key(&self, _by: K) -> i64 {
+            self.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paginator_should_work() {
+        let page = PageInfo {
+            cursor: None,
+            page_size: 10,
+            desc: false,
+        };
+
+        let mut items = pager_test_utils::generate_test_ids(1, 11);
+        let pager = page.get_pager(&mut items);
+        assert!(pager.prev.is_none());
+        assert_eq!(pager.next, Some(Cursor::forward(10, 10)));
+
+        let prev = page.prev_page(&pager);
+        assert!(prev.is_none());
+
+        // second page
+        let page = page.next_page(&pager).unwrap();
+        println!("{page:?}");
+        let pager = page.get_pager(&mut pager_test_utils::generate_test_ids(11, 21));
+        assert_eq!(pager.prev, Some(Cursor::backward(11, 11)));
+        assert_eq!(pager.next, Some(Cursor::forward(20, 20)));
+
+        // third page
+        let page = page.next_page(&pager).unwrap();
+        let pager = page.get_pager(&mut pager_test_utils::generate_test_ids(21, 25));
+        assert!(pager.next.is_none());
+        assert_eq!(pager.prev, Some(Cursor::backward(21, 21)));
+
+        // back to the second page, rows are fetched in reverse order
+        let page = page.prev_page(&pager).unwrap();
+        assert_eq!(page.cursor, Some(Cursor::backward(21, 21)));
+        let mut items: VecDeque<_> = pager_test_utils::generate_test_ids(10, 20)
+            .into_iter()
+            .rev()
+            .collect();
+        let pager = page.get_pager(&mut items);
+        assert_eq!(items.front().unwrap().id(), 11);
+        assert_eq!(items.back().unwrap().id(), 20);
+        assert_eq!(pager.prev, Some(Cursor::backward(11, 11)));
+        assert_eq!(pager.next, Some(Cursor::forward(20, 20)));
+
+        // back to the first page, there is nothing before it
+        let page = page.prev_page(&pager).unwrap();
+        let mut items: VecDeque<_> = pager_test_utils::generate_test_ids(1, 10)
+            .into_iter()
+            .rev()
+            .collect();
+        let pager = page.get_pager(&mut items);
+        assert_eq!(items.front().unwrap().id(), 1);
+        assert!(pager.prev.is_none());
+        assert_eq!(pager.next, Some(Cursor::forward(10, 10)));
+    }
+
+    #[test]
+    fn paginator_should_use_sort_key_in_cursor() {
+        let page = PageInfo {
+            cursor: None,
+            page_size: 2,
+            desc: false,
+        };
+
+        let mut items = pager_test_utils::generate_test_ids(1, 3);
+        let pager = page.get_pager_by(&mut items, |v| v.id() * 100);
+        assert_eq!(pager.next, Some(Cursor::forward(200, 2)));
+
+        let page = page.next_page(&pager).unwrap();
+        let pager = page.get_pager_by(&mut pager_test_utils::generate_test_ids(3, 3), |v| {
+            v.id() * 100
+        });
+        assert_eq!(pager.prev, Some(Cursor::backward(300, 3)));
+        assert!(pager.next.is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+use super::{Cursor, Direction};
+use crate::{Error, PagerConfig};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const VERSION: u8 = 2;
+// version + direction + key + id + fingerprint
+const PAYLOAD_LEN: usize = 1 + 1 + 8 + 8 + 8;
+const MAC_LEN: usize = 16;
+
+/// A cursor bound to the filter it was issued for. Clients only ever see it signed and
+/// base64 encoded, see [`PageSigner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageToken {
+    pub cursor: Cursor,
+    pub fingerprint: u64,
+}
+
+/// Signs page tokens handed out to clients and verifies the ones they send back, so
+/// cursors can't be crafted or tampered with.
+#[derive(Clone)]
+pub struct PageSigner {
+    key: Vec<u8>,
+}
+
+impl PageToken {
+    pub fn new(cursor: Cursor, fingerprint: u64) -> Self {
+        Self {
+            cursor,
+            fingerprint,
+        }
+    }
+
+    /// decode the token payload without checking its signature.
+    pub fn parse(token: &str) -> Result<Self, Error> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|_| Error::InvalidCursor(token.to_string()))?;
+        if bytes.len() != PAYLOAD_LEN + MAC_LEN {
+            return Err(Error::InvalidCursor(token.to_string()));
+        }
+        Self::from_bytes(&bytes[..PAYLOAD_LEN])
+            .ok_or_else(|| Error::InvalidCursor(token.to_string()))
+    }
+
+    fn to_bytes(self) -> [u8; PAYLOAD_LEN] {
+        let mut buf = [0u8; PAYLOAD_LEN];
+        buf[0] = VERSION;
+        buf[1] = match self.cursor.direction {
+            Direction::Forward => 0,
+            Direction::Backward => 1,
+        };
+        buf[2..10].copy_from_slice(&self.cursor.key.to_be_bytes());
+        buf[10..18].copy_from_slice(&self.cursor.id.to_be_bytes());
+        buf[18..26].copy_from_slice(&self.fingerprint.to_be_bytes());
+        buf
+    }
+
+    fn from_bytes(buf: &[u8]) -> Option<Self> {
+        if buf[0] != VERSION {
+            return None;
+        }
+        let direction = match buf[1] {
+            0 => Direction::Forward,
+            1 => Direction::Backward,
+            _ => return None,
+        };
+        let key = i64::from_be_bytes(buf[2..10].try_into().ok()?);
+        let id = i64::from_be_bytes(buf[10..18].try_into().ok()?);
+        let fingerprint = u64::from_be_bytes(buf[18..26].try_into().ok()?);
+
+        Some(Self::new(Cursor { direction, key, id }, fingerprint))
+    }
+}
+
+impl PageSigner {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            key: secret.as_ref().to_vec(),
+        }
+    }
+
+    /// a signer with a key only known to this process. Tokens won't survive a restart.
+    pub fn random() -> Self {
+        let mut key = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self { key }
+    }
+
+    pub fn sign(&self, token: &PageToken) -> String {
+        let payload = token.to_bytes();
+        let mac = self.mac(&payload).finalize().into_bytes();
+
+        let mut buf = Vec::with_capacity(PAYLOAD_LEN + MAC_LEN);
+        buf.extend_from_slice(&payload);
+        buf.extend_from_slice(&mac[..MAC_LEN]);
+        URL_SAFE_NO_PAD.encode(buf)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<PageToken, Error> {
+        let parsed = PageToken::parse(token)?;
+        // parse already checked the length
+        let bytes = URL_SAFE_NO_PAD.decode(token).unwrap();
+        let (payload, mac) = bytes.split_at(PAYLOAD_LEN);
+
+        self.mac(payload)
+            .verify_truncated_left(mac)
+            .map_err(|_| Error::InvalidCursor(token.to_string()))?;
+
+        Ok(parsed)
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC takes key of any size");
+        mac.update(payload);
+        mac
+    }
+}
+
+impl From<&PagerConfig> for PageSigner {
+    fn from(config: &PagerConfig) -> Self {
+        if config.secret.is_empty() {
+            Self::random()
+        } else {
+            Self::new(&config.secret)
+        }
+    }
+}
+
+impl fmt::Debug for PageSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PageSigner").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_token_should_verify() {
+        let signer = PageSigner::new("secret");
+        let token = PageToken::new(Cursor::backward(42, 42), 0xdead_beef);
+
+        let encoded = signer.sign(&token);
+        assert!(!encoded.contains("42"));
+        assert_eq!(signer.verify(&encoded), Ok(token));
+        assert_eq!(PageToken::parse(&encoded), Ok(token));
+    }
+
+    #[test]
+    fn token_signed_by_other_key_should_be_rejected() {
+        let token = PageToken::new(Cursor::forward(1, 1), 1);
+        let encoded = PageSigner::new("secret").sign(&token);
+
+        assert_eq!(
+            PageSigner::new("another").verify(&encoded),
+            Err(Error::InvalidCursor(encoded))
+        );
+    }
+
+    #[test]
+    fn tampered_token_should_be_rejected() {
+        let signer = PageSigner::random();
+        let encoded = signer.sign(&PageToken::new(Cursor::forward(1, 1), 1));
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(&encoded).unwrap();
+        // point the cursor at another id
+        bytes[17] = 100;
+        let tampered = URL_SAFE_NO_PAD.encode(bytes);
+
+        assert!(PageToken::parse(&tampered).is_ok());
+        assert!(signer.verify(&tampered).is_err());
+        assert!(signer.verify("not-a-token").is_err());
+    }
+}
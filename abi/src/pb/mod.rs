@@ -2,3 +2,7 @@
 mod reservation;
 
 pub use reservation::*;
+
+/// the encoded protobuf descriptors of the reservation service, see `tonic_reflection`.
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/reservation_descriptor.bin"));
@@ -24,6 +24,145 @@ pub struct Reservation {
     /// extra note
     #[prost(string, tag = "7")]
     pub note: ::prost::alloc::string::String,
+    /// structured metadata, e.g. team=infra. Select reservations by labels with a label selector
+    #[prost(map = "string, string", tag = "8")]
+    pub labels:
+        ::std::collections::HashMap<::prost::alloc::string::String, ::prost::alloc::string::String>,
+    /// reservations made with preemption bump overlapping ones of a lower priority
+    #[prost(int32, tag = "9")]
+    pub priority: i32,
+    /// when the user showed up, confirmed reservations only
+    #[prost(message, optional, tag = "10")]
+    pub checked_in_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// business unit the reservation belongs to, set by the server from the caller
+    #[prost(string, tag = "11")]
+    pub tenant_id: ::prost::alloc::string::String,
+}
+/// typed value of a resource attribute, e.g. seats = 10 or projector = true
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AttributeValue {
+    #[prost(oneof = "attribute_value::Value", tags = "1, 2, 3, 4")]
+    pub value: ::core::option::Option<attribute_value::Value>,
+}
+/// Nested message and enum types in `AttributeValue`.
+pub mod attribute_value {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Value {
+        #[prost(string, tag = "1")]
+        StringValue(::prost::alloc::string::String),
+        #[prost(int64, tag = "2")]
+        IntValue(i64),
+        #[prost(double, tag = "3")]
+        DoubleValue(f64),
+        #[prost(bool, tag = "4")]
+        BoolValue(bool),
+    }
+}
+/// rules every reservation of a resource has to follow. Unset rules don't apply
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BookingPolicy {
+    /// shortest and longest reservation allowed
+    #[prost(message, optional, tag = "1")]
+    pub min_duration: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "2")]
+    pub max_duration: ::core::option::Option<::prost_types::Duration>,
+    /// reservations have to start on a multiple of this many minutes, e.g. 15, 30 or 60. 0 means any time
+    #[prost(int32, tag = "3")]
+    pub slot_minutes: i32,
+    /// how long in advance a reservation has to be made at least
+    #[prost(message, optional, tag = "4")]
+    pub min_lead_time: ::core::option::Option<::prost_types::Duration>,
+    /// how far in the future a reservation may start at most
+    #[prost(message, optional, tag = "5")]
+    pub max_horizon: ::core::option::Option<::prost_types::Duration>,
+}
+/// opening hours of a resource on one day of the week, in the resource's timezone
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OpeningHours {
+    /// 1 is Monday, 7 is Sunday
+    #[prost(int32, tag = "1")]
+    pub weekday: i32,
+    /// minutes since local midnight, the resource is open in [open_minute, close_minute). Use 1440 to close at midnight
+    #[prost(int32, tag = "2")]
+    pub open_minute: i32,
+    #[prost(int32, tag = "3")]
+    pub close_minute: i32,
+}
+/// when a resource can be booked. Reservations have to fit in the opening hours of a single stretch
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BusinessHours {
+    /// IANA timezone of the opening hours and holidays, e.g. "Asia/Shanghai"
+    #[prost(string, tag = "1")]
+    pub timezone: ::prost::alloc::string::String,
+    /// weekdays without any entry are closed, a weekday may have several entries, e.g. for a lunch break
+    #[prost(message, repeated, tag = "2")]
+    pub hours: ::prost::alloc::vec::Vec<OpeningHours>,
+    /// local dates the resource is closed all day, as "YYYY-MM-DD"
+    #[prost(string, repeated, tag = "3")]
+    pub holidays: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// a bookable resource described by its attributes
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Resource {
+    /// unique id for the resource, the same as resource_id in reservations
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// attribute names follow the same rules as label keys
+    #[prost(map = "string, message", tag = "2")]
+    pub attributes: ::std::collections::HashMap<::prost::alloc::string::String, AttributeValue>,
+    /// id of the resource this one is part of, e.g. the hall of a partition. Booking a resource
+    /// conflicts with bookings of its parents and children in the same window. Empty if none
+    #[prost(string, tag = "3")]
+    pub parent_id: ::prost::alloc::string::String,
+    /// booking rules for the resource, if not set any reservation is allowed
+    #[prost(message, optional, tag = "4")]
+    pub policy: ::core::option::Option<BookingPolicy>,
+    /// if not set, the resource can be booked at any time
+    #[prost(message, optional, tag = "5")]
+    pub business_hours: ::core::option::Option<BusinessHours>,
+    /// turnaround time kept free before and after every booking, e.g. cleaning after checkout.
+    /// Buffers count when checking for conflicts but aren't part of the booked window
+    #[prost(message, optional, tag = "6")]
+    pub pre_buffer: ::core::option::Option<::prost_types::Duration>,
+    #[prost(message, optional, tag = "7")]
+    pub post_buffer: ::core::option::Option<::prost_types::Duration>,
+    /// owners of the resource, who may approve or reject its pending reservations
+    #[prost(string, repeated, tag = "8")]
+    pub approvers: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// pending reservations have to be approved by an approver, users can't confirm them
+    #[prost(bool, tag = "9")]
+    pub requires_approval: bool,
+    /// confirmed reservations not checked in this long after their start are released as
+    /// no-shows, which frees the rest of their window. Never released if not set
+    #[prost(message, optional, tag = "10")]
+    pub check_in_window: ::core::option::Option<::prost_types::Duration>,
+    /// business unit the resource belongs to, set by the server from the caller. Resource ids
+    /// are only unique within a tenant
+    #[prost(string, tag = "11")]
+    pub tenant_id: ::prost::alloc::string::String,
+}
+/// one requirement on a resource attribute, e.g. seats >= 10. Values of different types never match
+#[derive(serde::Serialize, serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AttributeCondition {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(enumeration = "AttributeOp", tag = "2")]
+    pub op: i32,
+    /// ignored for ATTRIBUTE_OP_EXISTS
+    #[prost(message, optional, tag = "3")]
+    pub value: ::core::option::Option<AttributeValue>,
 }
 /// To make a reservation, send a ReservationRequest with Reservation object (id should be empty)
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -31,6 +170,15 @@ pub struct Reservation {
 pub struct ReserveRequest {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
+    /// join the waitlist of the resource if the window is taken, instead of failing
+    #[prost(bool, tag = "2")]
+    pub waitlist: bool,
+    /// cancel overlapping pending or confirmed reservations of a lower priority to make room
+    #[prost(bool, tag = "3")]
+    pub preempt: bool,
+    /// why they were cancelled, recorded for the affected users
+    #[prost(string, tag = "4")]
+    pub preempt_reason: ::prost::alloc::string::String,
 }
 /// Created reservation will be returned in ReserveResponse
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -38,6 +186,90 @@ pub struct ReserveRequest {
 pub struct ReserveResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
+    /// set instead of the reservation if the caller joined the waitlist
+    #[prost(message, optional, tag = "2")]
+    pub waitlist_entry: ::core::option::Option<WaitlistEntry>,
+    /// reservations cancelled to make room for this one
+    #[prost(message, repeated, tag = "3")]
+    pub preempted: ::prost::alloc::vec::Vec<Reservation>,
+}
+/// a reservation cancelled to make room for a more important one
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Preemption {
+    /// the cancelled reservation
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+    /// id of the reservation it made room for
+    #[prost(int64, tag = "2")]
+    pub preempted_by: i64,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// preemptions of a user's reservations, latest first
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListPreemptionsRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListPreemptionsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub preemptions: ::prost::alloc::vec::Vec<Preemption>,
+}
+/// To book any resource matching some requirements, send a ReserveAnyRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveAnyRequest {
+    /// reservation to make. resource_id is ignored, the picked resource is filled in
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+    /// all conditions must hold for a resource to be picked
+    #[prost(message, repeated, tag = "2")]
+    pub conditions: ::prost::alloc::vec::Vec<AttributeCondition>,
+    /// if set, pick the matching resource with the smallest value of this numeric attribute
+    /// (e.g. the smallest room with enough seats), otherwise the first one by id
+    #[prost(string, tag = "3")]
+    pub best_fit: ::prost::alloc::string::String,
+}
+/// Created reservation will be returned in ReserveAnyResponse, its resource_id tells which resource was picked
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReserveAnyResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// To create or replace a resource, send a PutResourceRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutResourceRequest {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+/// Stored resource will be returned in PutResourceResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+/// To get a resource, send a GetResourceRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetResourceRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Resource will be returned in GetResourceResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
 }
 /// To update a reservation, send an UpdateRequest. Only note is updatable.
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -69,6 +301,139 @@ pub struct ConfirmResponse {
     #[prost(message, optional, tag = "1")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+/// the user showed up for a confirmed reservation. Check-in opens the resource's check-in
+/// window before the start and closes at the end
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckInRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckInResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// give back the rest of a reservation, e.g. a meeting finished early. The reservation keeps
+/// its id and start, its end is moved to the time given, now if not set, which has to be
+/// within the reservation
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(message, optional, tag = "2")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// make a running reservation last longer. Its start never moves, and it's
+/// only extended as a whole if the time after it is free and the resource's rules allow it.
+/// Otherwise the call fails with FAILED_PRECONDITION and the longest extension possible in
+/// seconds in the `max-extension-seconds` metadata
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExtendRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(message, optional, tag = "2")]
+    pub by: ::core::option::Option<::prost_types::Duration>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExtendResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// number of reservations of a user released because they didn't check in
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CountNoShowsRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CountNoShowsResponse {
+    #[prost(int64, tag = "1")]
+    pub count: i64,
+}
+/// an approver signs off a pending reservation, which confirms it
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApproveRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub approver: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApproveResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// an approver turns down a pending reservation, which cancels it
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RejectRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub approver: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RejectResponse {
+    #[prost(message, optional, tag = "1")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// pending reservations waiting for an approver, oldest first
+#[derive(derive_builder::Builder)]
+#[builder(build_fn(name = "private_build"))]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApprovalQuery {
+    /// the caller if empty, only admins may list the pending approvals of others
+    #[prost(string, tag = "1")]
+    #[builder(setter(into), default)]
+    pub approver: ::prost::alloc::string::String,
+    /// opaque page token taken from FilterPager.prev or FilterPager.next of a previous query
+    #[prost(string, optional, tag = "2")]
+    #[builder(setter(into, strip_option), default)]
+    pub cursor: ::core::option::Option<::prost::alloc::string::String>,
+    /// page size for the query
+    #[prost(int64, tag = "3")]
+    #[builder(setter(into), default = "10")]
+    pub page_size: i64,
+    /// set by the server, only reservations of the caller's tenant are returned
+    #[prost(string, tag = "4")]
+    #[builder(setter(into), default)]
+    pub tenant_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListPendingApprovalsRequest {
+    #[prost(message, optional, tag = "1")]
+    pub query: ::core::option::Option<ApprovalQuery>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListPendingApprovalsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
+    #[prost(message, optional, tag = "2")]
+    pub pager: ::core::option::Option<FilterPager>,
+}
 /// To cancel a reservation, send a CancelRequest
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -126,6 +491,18 @@ pub struct ReservationQuery {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// maximum number of reservations to return, 0 means no limit
+    #[prost(int64, tag = "7")]
+    #[builder(setter(into), default)]
+    pub limit: i64,
+    /// only return reservations whose labels match, e.g. "team=infra,env in (lab,prod),!temporary"
+    #[prost(string, tag = "8")]
+    #[builder(setter(into), default)]
+    pub label_selector: ::prost::alloc::string::String,
+    /// set by the server, only reservations of the caller's tenant are returned
+    #[prost(string, tag = "9")]
+    #[builder(setter(into), default)]
+    pub tenant_id: ::prost::alloc::string::String,
 }
 /// To query reservations, send a QueryRequest
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -134,7 +511,7 @@ pub struct QueryRequest {
     #[prost(message, optional, tag = "1")]
     pub query: ::core::option::Option<ReservationQuery>,
 }
-/// query reservations, order by reservation id
+/// query reservations, order by reservation id or by the start or end of the reservation
 #[derive(derive_builder::Builder)]
 #[builder(build_fn(name = "private_build"))]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -152,9 +529,10 @@ pub struct ReservationFilter {
     #[prost(enumeration = "ReservationStatus", tag = "3")]
     #[builder(setter(into), default)]
     pub status: i32,
-    #[prost(int64, optional, tag = "4")]
+    /// opaque page token taken from FilterPager.prev or FilterPager.next. If empty, start from the first page
+    #[prost(string, optional, tag = "4")]
     #[builder(setter(into, strip_option), default)]
-    pub cursor: ::core::option::Option<i64>,
+    pub cursor: ::core::option::Option<::prost::alloc::string::String>,
     /// page size for the query
     #[prost(int64, tag = "5")]
     #[builder(setter(into), default = "10")]
@@ -163,6 +541,22 @@ pub struct ReservationFilter {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// count all matching reservations and return it in FilterPager.total
+    #[prost(bool, tag = "7")]
+    #[builder(setter(into), default)]
+    pub include_total: bool,
+    /// sort key, default is id
+    #[prost(enumeration = "ReservationSortKey", tag = "8")]
+    #[builder(setter(into), default)]
+    pub order_by: i32,
+    /// only return reservations whose labels match, see ReservationQuery.label_selector
+    #[prost(string, tag = "9")]
+    #[builder(setter(into), default)]
+    pub label_selector: ::prost::alloc::string::String,
+    /// set by the server, only reservations of the caller's tenant are returned
+    #[prost(string, tag = "10")]
+    #[builder(setter(into), default)]
+    pub tenant_id: ::prost::alloc::string::String,
 }
 /// To query reservations, send a QueryRequest
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -171,16 +565,20 @@ pub struct FilterRequest {
     #[prost(message, optional, tag = "1")]
     pub filter: ::core::option::Option<ReservationFilter>,
 }
-/// filter pager info
+/// filter pager info, prev and next are opaque page tokens which are only valid for the same filter
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterPager {
-    #[prost(int64, optional, tag = "1")]
-    pub prev: ::core::option::Option<i64>,
-    #[prost(int64, optional, tag = "2")]
-    pub next: ::core::option::Option<i64>,
+    #[prost(string, optional, tag = "1")]
+    pub prev: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "2")]
+    pub next: ::core::option::Option<::prost::alloc::string::String>,
+    /// only populated if include_total is set in the filter
     #[prost(int64, optional, tag = "3")]
     pub total: ::core::option::Option<i64>,
+    /// total is an estimation from the planner statistics rather than an exact count
+    #[prost(bool, tag = "4")]
+    pub total_estimated: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -190,59 +588,239 @@ pub struct FilterResponse {
     #[prost(message, optional, tag = "2")]
     pub pager: ::core::option::Option<FilterPager>,
 }
-/// Client can listen to reservation updates by sending a ListenRequest
+/// full-text search over reservation notes, best matches first
+#[derive(derive_builder::Builder)]
+#[builder(build_fn(name = "private_build"))]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListenRequest {}
-/// Server will send ListenResponse to client in streaming response
+pub struct ReservationSearch {
+    /// free text to look for in the note, e.g. "projector broken". Quoted phrases, "or" and "-" are supported
+    #[prost(string, tag = "1")]
+    #[builder(setter(into), default)]
+    pub text: ::prost::alloc::string::String,
+    /// resource id for the search. If empty, search all resources
+    #[prost(string, tag = "2")]
+    #[builder(setter(into), default)]
+    pub resource_id: ::prost::alloc::string::String,
+    /// user id for the search. If empty, search all users
+    #[prost(string, tag = "3")]
+    #[builder(setter(into), default)]
+    pub user_id: ::prost::alloc::string::String,
+    /// use status to filter result. If UNKNOWN, search reservations of any status
+    #[prost(enumeration = "ReservationStatus", tag = "4")]
+    #[builder(setter(into), default)]
+    pub status: i32,
+    /// only return reservations overlapping [start, end). If not set, the window is unbounded on that side
+    #[prost(message, optional, tag = "5")]
+    #[builder(setter(into, strip_option), default)]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "6")]
+    #[builder(setter(into, strip_option), default)]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// opaque page token taken from FilterPager.prev or FilterPager.next of a previous search
+    #[prost(string, optional, tag = "7")]
+    #[builder(setter(into, strip_option), default)]
+    pub cursor: ::core::option::Option<::prost::alloc::string::String>,
+    /// page size for the search
+    #[prost(int64, tag = "8")]
+    #[builder(setter(into), default = "10")]
+    pub page_size: i64,
+    /// set by the server, only reservations of the caller's tenant are returned
+    #[prost(string, tag = "9")]
+    #[builder(setter(into), default)]
+    pub tenant_id: ::prost::alloc::string::String,
+}
+/// To search reservations, send a SearchRequest
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ListenResponse {
-    /// update type
-    #[prost(enumeration = "ReservationUpdateType", tag = "1")]
-    pub op: i32,
-    /// id for updated reservation
+pub struct SearchRequest {
+    #[prost(message, optional, tag = "1")]
+    pub search: ::core::option::Option<ReservationSearch>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub reservations: ::prost::alloc::vec::Vec<Reservation>,
     #[prost(message, optional, tag = "2")]
-    pub reservation: ::core::option::Option<Reservation>,
+    pub pager: ::core::option::Option<FilterPager>,
 }
-/// reservation status for a given time period
-#[derive(
-    sqlx::Type, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
-)]
-#[repr(i32)]
-pub enum ReservationStatus {
-    Unknown = 0,
-    Pending = 1,
-    Confirmed = 2,
-    Blocked = 3,
+/// To find free time of a resource, send an AvailabilityRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityRequest {
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// window to look for free time in
+    #[prost(message, optional, tag = "2")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "3")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
 }
-impl ReservationStatus {
-    /// String value of the enum field names used in the ProtoBuf definition.
-    ///
-    /// The values are not transformed in any way and thus are considered stable
-    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
-    pub fn as_str_name(&self) -> &'static str {
-        match self {
-            ReservationStatus::Unknown => "RESERVATION_STATUS_UNKNOWN",
-            ReservationStatus::Pending => "RESERVATION_STATUS_PENDING",
-            ReservationStatus::Confirmed => "RESERVATION_STATUS_CONFIRMED",
-            ReservationStatus::Blocked => "RESERVATION_STATUS_BLOCKED",
-        }
-    }
-    /// Creates an enum from field names used in the ProtoBuf definition.
-    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
-        match value {
-            "RESERVATION_STATUS_UNKNOWN" => Some(Self::Unknown),
-            "RESERVATION_STATUS_PENDING" => Some(Self::Pending),
-            "RESERVATION_STATUS_CONFIRMED" => Some(Self::Confirmed),
-            "RESERVATION_STATUS_BLOCKED" => Some(Self::Blocked),
-            _ => None,
-        }
-    }
+/// a stretch of time, [start, end)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimeSlot {
+    #[prost(message, optional, tag = "1")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "2")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
 }
-/// when reservation is updated, record the update type
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
-#[repr(i32)]
+/// free slots of the resource in the window, in order. Slots exclude closed hours, holidays and
+/// bookings of the resource, its parents and its children
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AvailabilityResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub slots: ::prost::alloc::vec::Vec<TimeSlot>,
+}
+/// fair-use limit of a user, e.g. at most 3 active reservations of meeting rooms
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Quota {
+    /// unique name of the quota, follows the same rules as label keys
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(enumeration = "QuotaKind", tag = "2")]
+    pub kind: i32,
+    /// reservations or minutes allowed
+    #[prost(int64, tag = "3")]
+    pub limit: i64,
+    /// only for QUOTA_KIND_BOOKED_MINUTES
+    #[prost(enumeration = "QuotaPeriod", tag = "4")]
+    pub period: i32,
+    /// resources the quota applies to, usage is counted over all of them. Every reservation
+    /// counts if empty, including the ones of resources not registered
+    #[prost(message, repeated, tag = "5")]
+    pub conditions: ::prost::alloc::vec::Vec<AttributeCondition>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutQuotaRequest {
+    #[prost(message, optional, tag = "1")]
+    pub quota: ::core::option::Option<Quota>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutQuotaResponse {
+    #[prost(message, optional, tag = "1")]
+    pub quota: ::core::option::Option<Quota>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListQuotasRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListQuotasResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub quotas: ::prost::alloc::vec::Vec<Quota>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteQuotaRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteQuotaResponse {
+    #[prost(message, optional, tag = "1")]
+    pub quota: ::core::option::Option<Quota>,
+}
+/// a reservation waiting for its window to become free. Once it does, e.g. because the
+/// blocking reservation is cancelled, entries are turned into pending reservations first come
+/// first served, and announced through the change feed like any other new reservation
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaitlistEntry {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    /// the reservation to make, its id is set once it's been made
+    #[prost(message, optional, tag = "2")]
+    pub reservation: ::core::option::Option<Reservation>,
+    #[prost(message, optional, tag = "3")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// entries still waiting, oldest first. Empty fields match everything
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListWaitlistRequest {
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListWaitlistResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<WaitlistEntry>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeaveWaitlistRequest {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LeaveWaitlistResponse {
+    #[prost(message, optional, tag = "1")]
+    pub entry: ::core::option::Option<WaitlistEntry>,
+}
+/// Client can listen to reservation updates by sending a ListenRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListenRequest {}
+/// Server will send ListenResponse to client in streaming response
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListenResponse {
+    /// update type
+    #[prost(enumeration = "ReservationUpdateType", tag = "1")]
+    pub op: i32,
+    /// id for updated reservation
+    #[prost(message, optional, tag = "2")]
+    pub reservation: ::core::option::Option<Reservation>,
+}
+/// reservation status for a given time period
+#[derive(
+    sqlx::Type, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
+)]
+#[repr(i32)]
+pub enum ReservationStatus {
+    Unknown = 0,
+    Pending = 1,
+    Confirmed = 2,
+    Blocked = 3,
+}
+impl ReservationStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationStatus::Unknown => "RESERVATION_STATUS_UNKNOWN",
+            ReservationStatus::Pending => "RESERVATION_STATUS_PENDING",
+            ReservationStatus::Confirmed => "RESERVATION_STATUS_CONFIRMED",
+            ReservationStatus::Blocked => "RESERVATION_STATUS_BLOCKED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_STATUS_UNKNOWN" => Some(Self::Unknown),
+            "RESERVATION_STATUS_PENDING" => Some(Self::Pending),
+            "RESERVATION_STATUS_CONFIRMED" => Some(Self::Confirmed),
+            "RESERVATION_STATUS_BLOCKED" => Some(Self::Blocked),
+            _ => None,
+        }
+    }
+}
+/// when reservation is updated, record the update type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum ReservationUpdateType {
     Unknown = 0,
     Create = 1,
@@ -273,6 +851,144 @@ impl ReservationUpdateType {
         }
     }
 }
+/// column to sort filter results by, reservations with the same value are sorted by id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReservationSortKey {
+    Id = 0,
+    Start = 1,
+    End = 2,
+}
+impl ReservationSortKey {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationSortKey::Id => "RESERVATION_SORT_KEY_ID",
+            ReservationSortKey::Start => "RESERVATION_SORT_KEY_START",
+            ReservationSortKey::End => "RESERVATION_SORT_KEY_END",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_SORT_KEY_ID" => Some(Self::Id),
+            "RESERVATION_SORT_KEY_START" => Some(Self::Start),
+            "RESERVATION_SORT_KEY_END" => Some(Self::End),
+            _ => None,
+        }
+    }
+}
+/// how an attribute of a resource is compared in an AttributeCondition
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AttributeOp {
+    Eq = 0,
+    Ne = 1,
+    Lt = 2,
+    Le = 3,
+    Gt = 4,
+    Ge = 5,
+    /// the resource has the attribute, whatever its value
+    Exists = 6,
+}
+impl AttributeOp {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AttributeOp::Eq => "ATTRIBUTE_OP_EQ",
+            AttributeOp::Ne => "ATTRIBUTE_OP_NE",
+            AttributeOp::Lt => "ATTRIBUTE_OP_LT",
+            AttributeOp::Le => "ATTRIBUTE_OP_LE",
+            AttributeOp::Gt => "ATTRIBUTE_OP_GT",
+            AttributeOp::Ge => "ATTRIBUTE_OP_GE",
+            AttributeOp::Exists => "ATTRIBUTE_OP_EXISTS",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ATTRIBUTE_OP_EQ" => Some(Self::Eq),
+            "ATTRIBUTE_OP_NE" => Some(Self::Ne),
+            "ATTRIBUTE_OP_LT" => Some(Self::Lt),
+            "ATTRIBUTE_OP_LE" => Some(Self::Le),
+            "ATTRIBUTE_OP_GT" => Some(Self::Gt),
+            "ATTRIBUTE_OP_GE" => Some(Self::Ge),
+            "ATTRIBUTE_OP_EXISTS" => Some(Self::Exists),
+            _ => None,
+        }
+    }
+}
+/// what a quota limits for every user
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum QuotaKind {
+    Unknown = 0,
+    /// reservations of the user which haven't ended yet
+    ActiveReservations = 1,
+    /// minutes booked by the user in a period, reservations count in the period they start in
+    BookedMinutes = 2,
+}
+impl QuotaKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            QuotaKind::Unknown => "QUOTA_KIND_UNKNOWN",
+            QuotaKind::ActiveReservations => "QUOTA_KIND_ACTIVE_RESERVATIONS",
+            QuotaKind::BookedMinutes => "QUOTA_KIND_BOOKED_MINUTES",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "QUOTA_KIND_UNKNOWN" => Some(Self::Unknown),
+            "QUOTA_KIND_ACTIVE_RESERVATIONS" => Some(Self::ActiveReservations),
+            "QUOTA_KIND_BOOKED_MINUTES" => Some(Self::BookedMinutes),
+            _ => None,
+        }
+    }
+}
+/// calendar period booked minutes are counted in, in utc. Weeks start on monday
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum QuotaPeriod {
+    Unknown = 0,
+    Day = 1,
+    Week = 2,
+    Month = 3,
+}
+impl QuotaPeriod {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            QuotaPeriod::Unknown => "QUOTA_PERIOD_UNKNOWN",
+            QuotaPeriod::Day => "QUOTA_PERIOD_DAY",
+            QuotaPeriod::Week => "QUOTA_PERIOD_WEEK",
+            QuotaPeriod::Month => "QUOTA_PERIOD_MONTH",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "QUOTA_PERIOD_UNKNOWN" => Some(Self::Unknown),
+            "QUOTA_PERIOD_DAY" => Some(Self::Day),
+            "QUOTA_PERIOD_WEEK" => Some(Self::Week),
+            "QUOTA_PERIOD_MONTH" => Some(Self::Month),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod reservation_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -358,7 +1074,24 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/reserve");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// confirm a pending reservation, if reservation is not pending, do nothing
+        /// book the first or best fitting free resource matching the requirements
+        pub async fn reserve_any(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReserveAnyRequest>,
+        ) -> Result<tonic::Response<super::ReserveAnyResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/reserve_any");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// confirm a pending reservation, if reservation is not pending, do nothing. Reservations of
+        /// resources requiring approval have to be approved instead
         pub async fn confirm(
             &mut self,
             request: impl tonic::IntoRequest<super::ConfirmRequest>,
@@ -374,11 +1107,11 @@ pub mod reservation_service_client {
                 http::uri::PathAndQuery::from_static("/reservation.ReservationService/confirm");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// update the reservation note
-        pub async fn update(
+        /// check in for a confirmed reservation, so it isn't released as a no-show
+        pub async fn check_in(
             &mut self,
-            request: impl tonic::IntoRequest<super::UpdateRequest>,
-        ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::CheckInRequest>,
+        ) -> Result<tonic::Response<super::CheckInResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -387,14 +1120,14 @@ pub mod reservation_service_client {
             })?;
             let codec = tonic::codec::ProstCodec::default();
             let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/update");
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/check_in");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// cancel a reservation
-        pub async fn cancel(
+        /// how often a user didn't show up for a confirmed reservation
+        pub async fn count_no_shows(
             &mut self,
-            request: impl tonic::IntoRequest<super::CancelRequest>,
-        ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::CountNoShowsRequest>,
+        ) -> Result<tonic::Response<super::CountNoShowsResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -402,15 +1135,16 @@ pub mod reservation_service_client {
                 )
             })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/cancel");
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/count_no_shows",
+            );
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// get a reservation by id
-        pub async fn get(
+        /// confirm a pending reservation of a resource requiring approval, only for its approvers
+        pub async fn approve(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetRequest>,
-        ) -> Result<tonic::Response<super::GetResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::ApproveRequest>,
+        ) -> Result<tonic::Response<super::ApproveResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -418,15 +1152,15 @@ pub mod reservation_service_client {
                 )
             })?;
             let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/reservation.ReservationService/get");
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/approve");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// query reservations by resource id, user id, status, start time, end time
-        pub async fn query(
+        /// cancel a pending reservation of a resource requiring approval, only for its approvers
+        pub async fn reject(
             &mut self,
-            request: impl tonic::IntoRequest<super::QueryRequest>,
-        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Reservation>>, tonic::Status>
-        {
+            request: impl tonic::IntoRequest<super::RejectRequest>,
+        ) -> Result<tonic::Response<super::RejectResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -435,16 +1169,31 @@ pub mod reservation_service_client {
             })?;
             let codec = tonic::codec::ProstCodec::default();
             let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/query");
-            self.inner
-                .server_streaming(request.into_request(), path, codec)
-                .await
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/reject");
+            self.inner.unary(request.into_request(), path, codec).await
         }
-        /// filter reservations, order by reservation id
-        pub async fn filter(
+        /// pending reservations an approver has to decide on
+        pub async fn list_pending_approvals(
             &mut self,
-            request: impl tonic::IntoRequest<super::FilterRequest>,
-        ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::ListPendingApprovalsRequest>,
+        ) -> Result<tonic::Response<super::ListPendingApprovalsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/list_pending_approvals",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// update the reservation note
+        pub async fn update(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateRequest>,
+        ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -453,15 +1202,14 @@ pub mod reservation_service_client {
             })?;
             let codec = tonic::codec::ProstCodec::default();
             let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/filter");
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/update");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        /// another system could monitor newly added/confirmed/cancelled reservations
-        pub async fn listen(
+        /// cancel a reservation
+        pub async fn cancel(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListenRequest>,
-        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Reservation>>, tonic::Status>
-        {
+            request: impl tonic::IntoRequest<super::CancelRequest>,
+        ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status> {
             self.inner.ready().await.map_err(|e| {
                 tonic::Status::new(
                     tonic::Code::Unknown,
@@ -470,10 +1218,276 @@ pub mod reservation_service_client {
             })?;
             let codec = tonic::codec::ProstCodec::default();
             let path =
-                http::uri::PathAndQuery::from_static("/reservation.ReservationService/listen");
-            self.inner
-                .server_streaming(request.into_request(), path, codec)
-                .await
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/cancel");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// end a reservation early, the rest of its window becomes free
+        pub async fn release(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReleaseRequest>,
+        ) -> Result<tonic::Response<super::ReleaseResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/release");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// make a running reservation last longer
+        pub async fn extend(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExtendRequest>,
+        ) -> Result<tonic::Response<super::ExtendResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/extend");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// get a reservation by id
+        pub async fn get(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetRequest>,
+        ) -> Result<tonic::Response<super::GetResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/reservation.ReservationService/get");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// query reservations by resource id, user id, status, start time, end time
+        pub async fn query(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QueryRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Reservation>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/query");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        /// filter reservations, order by reservation id, start or end time
+        pub async fn filter(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FilterRequest>,
+        ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/filter");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// search reservation notes, best matches first
+        pub async fn search(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SearchRequest>,
+        ) -> Result<tonic::Response<super::SearchResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/search");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// create or replace a resource and its attributes
+        pub async fn put_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PutResourceRequest>,
+        ) -> Result<tonic::Response<super::PutResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/put_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// get a resource by id
+        pub async fn get_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetResourceRequest>,
+        ) -> Result<tonic::Response<super::GetResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/get_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// free slots of a resource in a window
+        pub async fn availability(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/availability",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// create or replace a quota, it's checked for reservations made from then on
+        pub async fn put_quota(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PutQuotaRequest>,
+        ) -> Result<tonic::Response<super::PutQuotaResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/put_quota");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// all quotas, ordered by id
+        pub async fn list_quotas(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListQuotasRequest>,
+        ) -> Result<tonic::Response<super::ListQuotasResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/list_quotas");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// remove a quota
+        pub async fn delete_quota(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteQuotaRequest>,
+        ) -> Result<tonic::Response<super::DeleteQuotaResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/delete_quota",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// entries waiting for a resource or of a user
+        pub async fn list_waitlist(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListWaitlistRequest>,
+        ) -> Result<tonic::Response<super::ListWaitlistResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/list_waitlist",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// stop waiting, the entry is removed
+        pub async fn leave_waitlist(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LeaveWaitlistRequest>,
+        ) -> Result<tonic::Response<super::LeaveWaitlistResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/leave_waitlist",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// reservations of a user cancelled by preemption, and why
+        pub async fn list_preemptions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListPreemptionsRequest>,
+        ) -> Result<tonic::Response<super::ListPreemptionsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/list_preemptions",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// another system could monitor newly added/confirmed/cancelled reservations
+        pub async fn listen(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListenRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::Reservation>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/listen");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
         }
     }
 }
@@ -489,11 +1503,42 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::ReserveRequest>,
         ) -> Result<tonic::Response<super::ReserveResponse>, tonic::Status>;
-        /// confirm a pending reservation, if reservation is not pending, do nothing
+        /// book the first or best fitting free resource matching the requirements
+        async fn reserve_any(
+            &self,
+            request: tonic::Request<super::ReserveAnyRequest>,
+        ) -> Result<tonic::Response<super::ReserveAnyResponse>, tonic::Status>;
+        /// confirm a pending reservation, if reservation is not pending, do nothing. Reservations of
+        /// resources requiring approval have to be approved instead
         async fn confirm(
             &self,
             request: tonic::Request<super::ConfirmRequest>,
         ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status>;
+        /// check in for a confirmed reservation, so it isn't released as a no-show
+        async fn check_in(
+            &self,
+            request: tonic::Request<super::CheckInRequest>,
+        ) -> Result<tonic::Response<super::CheckInResponse>, tonic::Status>;
+        /// how often a user didn't show up for a confirmed reservation
+        async fn count_no_shows(
+            &self,
+            request: tonic::Request<super::CountNoShowsRequest>,
+        ) -> Result<tonic::Response<super::CountNoShowsResponse>, tonic::Status>;
+        /// confirm a pending reservation of a resource requiring approval, only for its approvers
+        async fn approve(
+            &self,
+            request: tonic::Request<super::ApproveRequest>,
+        ) -> Result<tonic::Response<super::ApproveResponse>, tonic::Status>;
+        /// cancel a pending reservation of a resource requiring approval, only for its approvers
+        async fn reject(
+            &self,
+            request: tonic::Request<super::RejectRequest>,
+        ) -> Result<tonic::Response<super::RejectResponse>, tonic::Status>;
+        /// pending reservations an approver has to decide on
+        async fn list_pending_approvals(
+            &self,
+            request: tonic::Request<super::ListPendingApprovalsRequest>,
+        ) -> Result<tonic::Response<super::ListPendingApprovalsResponse>, tonic::Status>;
         /// update the reservation note
         async fn update(
             &self,
@@ -504,6 +1549,16 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::CancelRequest>,
         ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status>;
+        /// end a reservation early, the rest of its window becomes free
+        async fn release(
+            &self,
+            request: tonic::Request<super::ReleaseRequest>,
+        ) -> Result<tonic::Response<super::ReleaseResponse>, tonic::Status>;
+        /// make a running reservation last longer
+        async fn extend(
+            &self,
+            request: tonic::Request<super::ExtendRequest>,
+        ) -> Result<tonic::Response<super::ExtendResponse>, tonic::Status>;
         /// get a reservation by id
         async fn get(
             &self,
@@ -518,11 +1573,61 @@ pub mod reservation_service_server {
             &self,
             request: tonic::Request<super::QueryRequest>,
         ) -> Result<tonic::Response<Self::queryStream>, tonic::Status>;
-        /// filter reservations, order by reservation id
+        /// filter reservations, order by reservation id, start or end time
         async fn filter(
             &self,
             request: tonic::Request<super::FilterRequest>,
         ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status>;
+        /// search reservation notes, best matches first
+        async fn search(
+            &self,
+            request: tonic::Request<super::SearchRequest>,
+        ) -> Result<tonic::Response<super::SearchResponse>, tonic::Status>;
+        /// create or replace a resource and its attributes
+        async fn put_resource(
+            &self,
+            request: tonic::Request<super::PutResourceRequest>,
+        ) -> Result<tonic::Response<super::PutResourceResponse>, tonic::Status>;
+        /// get a resource by id
+        async fn get_resource(
+            &self,
+            request: tonic::Request<super::GetResourceRequest>,
+        ) -> Result<tonic::Response<super::GetResourceResponse>, tonic::Status>;
+        /// free slots of a resource in a window
+        async fn availability(
+            &self,
+            request: tonic::Request<super::AvailabilityRequest>,
+        ) -> Result<tonic::Response<super::AvailabilityResponse>, tonic::Status>;
+        /// create or replace a quota, it's checked for reservations made from then on
+        async fn put_quota(
+            &self,
+            request: tonic::Request<super::PutQuotaRequest>,
+        ) -> Result<tonic::Response<super::PutQuotaResponse>, tonic::Status>;
+        /// all quotas, ordered by id
+        async fn list_quotas(
+            &self,
+            request: tonic::Request<super::ListQuotasRequest>,
+        ) -> Result<tonic::Response<super::ListQuotasResponse>, tonic::Status>;
+        /// remove a quota
+        async fn delete_quota(
+            &self,
+            request: tonic::Request<super::DeleteQuotaRequest>,
+        ) -> Result<tonic::Response<super::DeleteQuotaResponse>, tonic::Status>;
+        /// entries waiting for a resource or of a user
+        async fn list_waitlist(
+            &self,
+            request: tonic::Request<super::ListWaitlistRequest>,
+        ) -> Result<tonic::Response<super::ListWaitlistResponse>, tonic::Status>;
+        /// stop waiting, the entry is removed
+        async fn leave_waitlist(
+            &self,
+            request: tonic::Request<super::LeaveWaitlistRequest>,
+        ) -> Result<tonic::Response<super::LeaveWaitlistResponse>, tonic::Status>;
+        /// reservations of a user cancelled by preemption, and why
+        async fn list_preemptions(
+            &self,
+            request: tonic::Request<super::ListPreemptionsRequest>,
+        ) -> Result<tonic::Response<super::ListPreemptionsResponse>, tonic::Status>;
         /// Server streaming response type for the listen method.
         type listenStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
             + Send
@@ -618,18 +1723,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/confirm" => {
+                "/reservation.ReservationService/reserve_any" => {
                     #[allow(non_camel_case_types)]
-                    struct confirmSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::ConfirmRequest> for confirmSvc<T> {
-                        type Response = super::ConfirmResponse;
+                    struct reserve_anySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ReserveAnyRequest>
+                        for reserve_anySvc<T>
+                    {
+                        type Response = super::ReserveAnyResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ConfirmRequest>,
+                            request: tonic::Request<super::ReserveAnyRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).confirm(request).await };
+                            let fut = async move { (*inner).reserve_any(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -638,7 +1746,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = confirmSvc(inner);
+                        let method = reserve_anySvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -649,18 +1757,18 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/update" => {
+                "/reservation.ReservationService/confirm" => {
                     #[allow(non_camel_case_types)]
-                    struct updateSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::UpdateRequest> for updateSvc<T> {
-                        type Response = super::UpdateResponse;
+                    struct confirmSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::ConfirmRequest> for confirmSvc<T> {
+                        type Response = super::ConfirmResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::UpdateRequest>,
+                            request: tonic::Request<super::ConfirmRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).update(request).await };
+                            let fut = async move { (*inner).confirm(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -669,7 +1777,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = updateSvc(inner);
+                        let method = confirmSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -680,18 +1788,18 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/cancel" => {
+                "/reservation.ReservationService/check_in" => {
                     #[allow(non_camel_case_types)]
-                    struct cancelSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::CancelRequest> for cancelSvc<T> {
-                        type Response = super::CancelResponse;
+                    struct check_inSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::CheckInRequest> for check_inSvc<T> {
+                        type Response = super::CheckInResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CancelRequest>,
+                            request: tonic::Request<super::CheckInRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).cancel(request).await };
+                            let fut = async move { (*inner).check_in(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -700,7 +1808,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = cancelSvc(inner);
+                        let method = check_inSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -711,18 +1819,21 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/get" => {
+                "/reservation.ReservationService/count_no_shows" => {
                     #[allow(non_camel_case_types)]
-                    struct getSvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService> tonic::server::UnaryService<super::GetRequest> for getSvc<T> {
-                        type Response = super::GetResponse;
+                    struct count_no_showsSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::CountNoShowsRequest>
+                        for count_no_showsSvc<T>
+                    {
+                        type Response = super::CountNoShowsResponse;
                         type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetRequest>,
+                            request: tonic::Request<super::CountNoShowsRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).get(request).await };
+                            let fut = async move { (*inner).count_no_shows(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -731,7 +1842,7 @@ pub mod reservation_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = getSvc(inner);
+                        let method = count_no_showsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
                             accept_compression_encodings,
@@ -742,13 +1853,264 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/reservation.ReservationService/query" => {
+                "/reservation.ReservationService/approve" => {
                     #[allow(non_camel_case_types)]
-                    struct querySvc<T: ReservationService>(pub Arc<T>);
-                    impl<T: ReservationService>
-                        tonic::server::ServerStreamingService<super::QueryRequest> for querySvc<T>
-                    {
-                        type Response = super::Reservation;
+                    struct approveSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::ApproveRequest> for approveSvc<T> {
+                        type Response = super::ApproveResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ApproveRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).approve(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = approveSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/reject" => {
+                    #[allow(non_camel_case_types)]
+                    struct rejectSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::RejectRequest> for rejectSvc<T> {
+                        type Response = super::RejectResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RejectRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reject(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = rejectSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/list_pending_approvals" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_pending_approvalsSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ListPendingApprovalsRequest>
+                        for list_pending_approvalsSvc<T>
+                    {
+                        type Response = super::ListPendingApprovalsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListPendingApprovalsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_pending_approvals(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_pending_approvalsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/update" => {
+                    #[allow(non_camel_case_types)]
+                    struct updateSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::UpdateRequest> for updateSvc<T> {
+                        type Response = super::UpdateResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = updateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/cancel" => {
+                    #[allow(non_camel_case_types)]
+                    struct cancelSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::CancelRequest> for cancelSvc<T> {
+                        type Response = super::CancelResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).cancel(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = cancelSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/release" => {
+                    #[allow(non_camel_case_types)]
+                    struct releaseSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::ReleaseRequest> for releaseSvc<T> {
+                        type Response = super::ReleaseResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReleaseRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).release(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = releaseSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/extend" => {
+                    #[allow(non_camel_case_types)]
+                    struct extendSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::ExtendRequest> for extendSvc<T> {
+                        type Response = super::ExtendResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExtendRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).extend(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = extendSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/get" => {
+                    #[allow(non_camel_case_types)]
+                    struct getSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::GetRequest> for getSvc<T> {
+                        type Response = super::GetResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = getSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/query" => {
+                    #[allow(non_camel_case_types)]
+                    struct querySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::ServerStreamingService<super::QueryRequest> for querySvc<T>
+                    {
+                        type Response = super::Reservation;
                         type ResponseStream = T::queryStream;
                         type Future =
                             BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
@@ -808,6 +2170,342 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/search" => {
+                    #[allow(non_camel_case_types)]
+                    struct searchSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::SearchRequest> for searchSvc<T> {
+                        type Response = super::SearchResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SearchRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).search(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = searchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/put_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct put_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::PutResourceRequest>
+                        for put_resourceSvc<T>
+                    {
+                        type Response = super::PutResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PutResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).put_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = put_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/get_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct get_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::GetResourceRequest>
+                        for get_resourceSvc<T>
+                    {
+                        type Response = super::GetResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = get_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/availability" => {
+                    #[allow(non_camel_case_types)]
+                    struct availabilitySvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::AvailabilityRequest>
+                        for availabilitySvc<T>
+                    {
+                        type Response = super::AvailabilityResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AvailabilityRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).availability(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = availabilitySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/put_quota" => {
+                    #[allow(non_camel_case_types)]
+                    struct put_quotaSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::PutQuotaRequest>
+                        for put_quotaSvc<T>
+                    {
+                        type Response = super::PutQuotaResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PutQuotaRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).put_quota(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = put_quotaSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/list_quotas" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_quotasSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ListQuotasRequest>
+                        for list_quotasSvc<T>
+                    {
+                        type Response = super::ListQuotasResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListQuotasRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_quotas(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_quotasSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/delete_quota" => {
+                    #[allow(non_camel_case_types)]
+                    struct delete_quotaSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::DeleteQuotaRequest>
+                        for delete_quotaSvc<T>
+                    {
+                        type Response = super::DeleteQuotaResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteQuotaRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).delete_quota(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = delete_quotaSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/list_waitlist" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_waitlistSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ListWaitlistRequest>
+                        for list_waitlistSvc<T>
+                    {
+                        type Response = super::ListWaitlistResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListWaitlistRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_waitlist(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_waitlistSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/leave_waitlist" => {
+                    #[allow(non_camel_case_types)]
+                    struct leave_waitlistSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::LeaveWaitlistRequest>
+                        for leave_waitlistSvc<T>
+                    {
+                        type Response = super::LeaveWaitlistResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LeaveWaitlistRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).leave_waitlist(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = leave_waitlistSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/list_preemptions" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_preemptionsSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ListPreemptionsRequest>
+                        for list_preemptionsSvc<T>
+                    {
+                        type Response = super::ListPreemptionsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListPreemptionsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_preemptions(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_preemptionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/reservation.ReservationService/listen" => {
                     #[allow(non_camel_case_types)]
                     struct listenSvc<T: ReservationService>(pub Arc<T>);
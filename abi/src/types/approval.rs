@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    pager::{Cursor, PageInfo, PageSigner, PageToken, Paginator},
+    quote, ApprovalQuery, ApprovalQueryBuilder, Error, FilterPager, Reservation, ToSql, Validator,
+};
+
+impl ApprovalQueryBuilder {
+    pub fn build(&self) -> Result<ApprovalQuery, Error> {
+        let query = self.private_build().expect("failed to build ApprovalQuery");
+        query.validate()?;
+        Ok(query)
+    }
+}
+
+impl ApprovalQuery {
+    // oldest reservations are the ones waiting longest, they come first
+    fn page_info(&self) -> PageInfo {
+        PageInfo {
+            cursor: self
+                .cursor
+                .as_deref()
+                .and_then(|v| PageToken::parse(v).ok())
+                .map(|token| token.cursor),
+            page_size: self.page_size,
+            desc: false,
+        }
+    }
+
+    /// see `ReservationFilter::fingerprint`.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"approval");
+        hasher.update([0]);
+        hasher.update(self.approver.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.tenant_id.as_bytes());
+        let digest = hasher.finalize();
+        u64::from_be_bytes(digest[..8].try_into().unwrap())
+    }
+
+    /// make sure the cursor was signed by us for this very query.
+    pub fn verify_cursor(&self, signer: &PageSigner) -> Result<(), Error> {
+        if let Some(cursor) = self.cursor.as_ref() {
+            let token = signer.verify(cursor)?;
+            if token.fingerprint != self.fingerprint() {
+                return Err(Error::InvalidCursor(cursor.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_pager(&self, data: &mut VecDeque<Reservation>, signer: &PageSigner) -> FilterPager {
+        let pager = self.page_info().get_pager(data);
+
+        let fingerprint = self.fingerprint();
+        let sign = |cursor: Option<Cursor>| {
+            cursor.map(|cursor| signer.sign(&PageToken::new(cursor, fingerprint)))
+        };
+
+        FilterPager {
+            prev: sign(pager.prev),
+            next: sign(pager.next),
+            total: None,
+            total_estimated: false,
+        }
+    }
+
+    pub fn prev_page(&self, pager: &FilterPager) -> Option<Self> {
+        self.with_cursor(pager.prev.as_ref())
+    }
+
+    pub fn next_page(&self, pager: &FilterPager) -> Option<Self> {
+        self.with_cursor(pager.next.as_ref())
+    }
+
+    fn with_cursor(&self, cursor: Option<&String>) -> Option<Self> {
+        cursor.map(|cursor| Self {
+            cursor: Some(cursor.clone()),
+            ..self.clone()
+        })
+    }
+}
+
+impl Validator for ApprovalQuery {
+    fn validate(&self) -> Result<(), Error> {
+        if self.approver.is_empty() {
+            return Err(Error::InvalidApprover(self.approver.clone()));
+        }
+        if self.page_size < 10 || self.page_size > 100 {
+            return Err(Error::InvalidPageSize(self.page_size));
+        }
+        if let Some(cursor) = self.cursor.as_ref() {
+            PageToken::parse(cursor)?;
+        }
+        Ok(())
+    }
+}
+
+/// pending reservations of resources requiring approval the approver is responsible for.
+impl ToSql for ApprovalQuery {
+    fn to_sql(&self) -> String {
+        let page = self.page_info();
+        let backward = page.is_backward();
+
+        let mut sql = format!(
+            "SELECT v.* FROM rsvp.reservation v JOIN rsvp.resource r ON r.tenant_id = v.tenant_id AND r.id = v.resource_id WHERE v.tenant_id = {} AND v.status = 'pending'::rsvp.reservation_status AND r.requires_approval AND {} = ANY(r.approvers)",
+            quote(&self.tenant_id),
+            quote(&self.approver)
+        );
+        if let Some(cursor) = page.cursor {
+            let op = if backward { "<" } else { ">" };
+            sql.push_str(&format!(" AND v.id {op} {}", cursor.id));
+        }
+        let order = if backward { "DESC" } else { "ASC" };
+        sql.push_str(&format!(
+            " ORDER BY v.id {order} LIMIT {}",
+            self.page_size + 1
+        ));
+        sql
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PagerConfig;
+
+    #[test]
+    fn approval_query_should_generate_correct_sql() {
+        let query = ApprovalQueryBuilder::default()
+            .approver("it's-me")
+            .build()
+            .unwrap();
+        assert_eq!(query.to_sql(), "SELECT v.* FROM rsvp.reservation v JOIN rsvp.resource r ON r.tenant_id = v.tenant_id AND r.id = v.resource_id WHERE v.tenant_id = '' AND v.status = 'pending'::rsvp.reservation_status AND r.requires_approval AND 'it''s-me' = ANY(r.approvers) ORDER BY v.id ASC LIMIT 11");
+
+        let signer = PageSigner::from(&PagerConfig::default());
+        let mut data: VecDeque<_> = (1..=11)
+            .map(|id| Reservation {
+                id,
+                ..Default::default()
+            })
+            .collect();
+        let pager = query.get_pager(&mut data, &signer);
+        assert!(pager.prev.is_none());
+        let next = query.next_page(&pager).unwrap();
+        next.verify_cursor(&signer).unwrap();
+        assert_eq!(next.to_sql(), "SELECT v.* FROM rsvp.reservation v JOIN rsvp.resource r ON r.tenant_id = v.tenant_id AND r.id = v.resource_id WHERE v.tenant_id = '' AND v.status = 'pending'::rsvp.reservation_status AND r.requires_approval AND 'it''s-me' = ANY(r.approvers) AND v.id > 10 ORDER BY v.id ASC LIMIT 11");
+
+        // cursors of one approver can't be used by another
+        let other = ApprovalQuery {
+            approver: "someone-else".into(),
+            ..next
+        };
+        assert!(other.verify_cursor(&signer).is_err());
+    }
+
+    #[test]
+    fn invalid_approval_query_should_be_rejected() {
+        assert!(ApprovalQueryBuilder::default().build().is_err());
+        assert!(ApprovalQueryBuilder::default()
+            .approver("alice")
+            .page_size(5)
+            .build()
+            .is_err());
+    }
+}
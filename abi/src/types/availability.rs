@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+
+use crate::{convert_to_timestamp, AvailabilityRequest, Error, TimeSlot, Validator};
+
+use super::validate_range;
+
+/// a stretch of time, [start, end).
+pub type Span = (DateTime<Utc>, DateTime<Utc>);
+
+impl Validator for AvailabilityRequest {
+    fn validate(&self) -> Result<(), Error> {
+        if self.resource_id.is_empty() {
+            return Err(Error::InvalidResourceId(self.resource_id.clone()));
+        }
+        validate_range(self.start.as_ref(), self.end.as_ref())
+    }
+}
+
+impl From<Span> for TimeSlot {
+    fn from((start, end): Span) -> Self {
+        Self {
+            start: Some(convert_to_timestamp(start)),
+            end: Some(convert_to_timestamp(end)),
+        }
+    }
+}
+
+/// sort spans and join the ones which overlap or touch.
+pub fn merge_spans(mut spans: Vec<Span>) -> Vec<Span> {
+    spans.sort();
+    let mut merged: Vec<Span> = Vec::with_capacity(spans.len());
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// what's left of the free spans once the busy ones are taken out.
+pub fn subtract_spans(free: Vec<Span>, busy: Vec<Span>) -> Vec<Span> {
+    let busy = merge_spans(busy);
+    let mut left = vec![];
+    for (mut start, end) in merge_spans(free) {
+        for (busy_start, busy_end) in busy.iter() {
+            if *busy_end <= start || *busy_start >= end {
+                continue;
+            }
+            if *busy_start > start {
+                left.push((start, *busy_start));
+            }
+            start = start.max(*busy_end);
+        }
+        if start < end {
+            left.push((start, end));
+        }
+    }
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: u32, end: u32) -> Span {
+        let at = |hour| format!("2022-12-25T{hour:02}:00:00Z").parse().unwrap();
+        (at(start), at(end))
+    }
+
+    #[test]
+    fn merge_spans_should_join_touching_spans() {
+        let spans = vec![span(9, 10), span(1, 3), span(2, 4), span(4, 5)];
+        assert_eq!(merge_spans(spans), vec![span(1, 5), span(9, 10)]);
+    }
+
+    #[test]
+    fn subtract_spans_should_leave_gaps() {
+        let free = vec![span(8, 12), span(13, 18)];
+        let busy = vec![span(9, 10), span(11, 14), span(17, 20), span(3, 4)];
+        assert_eq!(
+            subtract_spans(free, busy),
+            vec![span(8, 9), span(10, 11), span(14, 17)]
+        );
+
+        assert_eq!(subtract_spans(vec![span(8, 12)], vec![]), vec![span(8, 12)]);
+        assert_eq!(subtract_spans(vec![span(8, 12)], vec![span(7, 13)]), vec![]);
+    }
+}
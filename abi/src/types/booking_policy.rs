@@ -0,0 +1,313 @@
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use sqlx::{
+    postgres::{types::PgInterval, PgRow},
+    Row,
+};
+
+use crate::{
+    convert_interval_to_duration, convert_to_duration, convert_to_pb_duration, convert_to_utc_time,
+    BookingPolicy, Error, Reservation, Validator,
+};
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+impl BookingPolicy {
+    pub fn min_duration(&self) -> Option<Duration> {
+        self.min_duration.as_ref().map(convert_to_duration)
+    }
+
+    pub fn max_duration(&self) -> Option<Duration> {
+        self.max_duration.as_ref().map(convert_to_duration)
+    }
+
+    pub fn min_lead_time(&self) -> Option<Duration> {
+        self.min_lead_time.as_ref().map(convert_to_duration)
+    }
+
+    pub fn max_horizon(&self) -> Option<Duration> {
+        self.max_horizon.as_ref().map(convert_to_duration)
+    }
+
+    /// make sure a reservation made at `now` follows every rule of the policy. Slots start
+    /// at midnight in the timezone of the resource's business hours, or in UTC without any.
+    pub fn check(
+        &self,
+        rsvp: &Reservation,
+        now: DateTime<Utc>,
+        tz: Option<Tz>,
+    ) -> Result<(), Error> {
+        let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+
+        self.check_duration(rsvp)?;
+        if self.slot_minutes > 0 {
+            let slot = self.slot_minutes as i64 * 60 * 1_000_000;
+            // slots divide a day, so aligning to local midnight is aligning to the local epoch
+            let local = start.with_timezone(&tz.unwrap_or(Tz::UTC)).naive_local();
+            let micros = local.timestamp() * 1_000_000 + local.timestamp_subsec_micros() as i64;
+            if micros % slot != 0 {
+                return Err(violation(
+                    "slot",
+                    format!(
+                        "reservation has to start on a {} minute slot",
+                        self.slot_minutes
+                    ),
+                ));
+            }
+        }
+        if let Some(lead) = self.min_lead_time().filter(|lead| start - now < *lead) {
+            return Err(violation(
+                "min_lead_time",
+                format!(
+                    "reservation has to be made at least {} in advance",
+                    fmt_duration(lead)
+                ),
+            ));
+        }
+        if let Some(horizon) = self.max_horizon().filter(|horizon| start - now > *horizon) {
+            return Err(violation(
+                "max_horizon",
+                format!(
+                    "reservation can't start more than {} from now",
+                    fmt_duration(horizon)
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// only the min and max duration, e.g. for a reservation being extended.
+    pub fn check_duration(&self, rsvp: &Reservation) -> Result<(), Error> {
+        let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+        let end = convert_to_utc_time(rsvp.end.clone().unwrap());
+        let duration = end - start;
+
+        if let Some(min) = self.min_duration().filter(|min| duration < *min) {
+            return Err(violation(
+                "min_duration",
+                format!(
+                    "reservation lasts {}, at least {} is required",
+                    fmt_duration(duration),
+                    fmt_duration(min)
+                ),
+            ));
+        }
+        if let Some(max) = self.max_duration().filter(|max| duration > *max) {
+            return Err(violation(
+                "max_duration",
+                format!(
+                    "reservation lasts {}, at most {} is allowed",
+                    fmt_duration(duration),
+                    fmt_duration(max)
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// the policy stored in the resource columns, None if no rule is set.
+    pub fn from_resource_row(row: &PgRow) -> Result<Option<Self>, sqlx::Error> {
+        let duration = |column: &str| -> Result<Option<prost_types::Duration>, sqlx::Error> {
+            let interval: Option<PgInterval> = row.try_get(column)?;
+            Ok(interval.map(|v| convert_to_pb_duration(convert_interval_to_duration(v))))
+        };
+        let policy = Self {
+            min_duration: duration("min_duration")?,
+            max_duration: duration("max_duration")?,
+            slot_minutes: row.try_get("slot_minutes")?,
+            min_lead_time: duration("min_lead_time")?,
+            max_horizon: duration("max_horizon")?,
+        };
+
+        Ok((policy != Self::default()).then_some(policy))
+    }
+}
+
+impl Validator for BookingPolicy {
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |detail: &str| Err(Error::InvalidBookingPolicy(detail.to_string()));
+        let durations = [
+            self.min_duration(),
+            self.max_duration(),
+            self.min_lead_time(),
+            self.max_horizon(),
+        ];
+        if durations.iter().flatten().any(|d| *d < Duration::zero()) {
+            return invalid("durations can't be negative");
+        }
+        if let (Some(min), Some(max)) = (self.min_duration(), self.max_duration()) {
+            if min > max {
+                return invalid("min_duration is longer than max_duration");
+            }
+        }
+        // slots have to line up with the start of every day
+        if self.slot_minutes < 0
+            || (self.slot_minutes > 0 && MINUTES_PER_DAY % self.slot_minutes != 0)
+        {
+            return invalid("slot_minutes has to divide a day into whole slots");
+        }
+        Ok(())
+    }
+}
+
+fn violation(rule: &str, detail: String) -> Error {
+    Error::PolicyViolation {
+        rule: rule.to_string(),
+        detail,
+    }
+}
+
+// e.g. 1d2h, 1h30m or 45s
+fn fmt_duration(d: Duration) -> String {
+    let secs = d.num_seconds();
+    let parts = [
+        (secs / 86_400, "d"),
+        (secs % 86_400 / 3_600, "h"),
+        (secs % 3_600 / 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let s: String = parts
+        .iter()
+        .filter(|(v, _)| *v != 0)
+        .map(|(v, unit)| format!("{v}{unit}"))
+        .collect();
+    if s.is_empty() {
+        "0s".to_string()
+    } else {
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(m: i64) -> Option<prost_types::Duration> {
+        Some(convert_to_pb_duration(Duration::minutes(m)))
+    }
+
+    fn rsvp(start: &str, end: &str) -> Reservation {
+        Reservation::new_pending(
+            "alice",
+            "room-1",
+            start.parse().unwrap(),
+            end.parse().unwrap(),
+            "",
+        )
+    }
+
+    fn rule_of(ret: Result<(), Error>) -> String {
+        match ret {
+            Err(Error::PolicyViolation { rule, .. }) => rule,
+            v => panic!("expect policy violation, got {v:?}"),
+        }
+    }
+
+    #[test]
+    fn booking_policy_should_check_duration_and_slot() {
+        let policy = BookingPolicy {
+            min_duration: minutes(30),
+            max_duration: minutes(4 * 60),
+            slot_minutes: 15,
+            ..Default::default()
+        };
+        let now = "2022-12-01T00:00:00Z".parse().unwrap();
+
+        let ok = rsvp("2022-12-25T09:15:00+0800", "2022-12-25T10:00:00+0800");
+        assert!(policy.check(&ok, now, None).is_ok());
+
+        let short = rsvp("2022-12-25T09:00:00+0800", "2022-12-25T09:15:00+0800");
+        assert_eq!(
+            policy.check(&short, now, None),
+            Err(Error::PolicyViolation {
+                rule: "min_duration".into(),
+                detail: "reservation lasts 15m, at least 30m is required".into(),
+            })
+        );
+
+        let long = rsvp("2022-12-25T09:00:00+0800", "2022-12-25T13:30:00+0800");
+        assert_eq!(rule_of(policy.check(&long, now, None)), "max_duration");
+
+        let unaligned = rsvp("2022-12-25T09:10:00+0800", "2022-12-25T10:10:00+0800");
+        assert_eq!(rule_of(policy.check(&unaligned, now, None)), "slot");
+    }
+
+    #[test]
+    fn slots_should_be_aligned_to_local_midnight() {
+        let policy = BookingPolicy {
+            slot_minutes: 60,
+            ..Default::default()
+        };
+        let now = "2022-12-01T00:00:00Z".parse().unwrap();
+        let kolkata = Some(Tz::Asia__Kolkata);
+        let kathmandu = Some(Tz::Asia__Kathmandu);
+
+        // whole hours at +05:30 are half past in utc
+        let local = rsvp("2022-12-25T09:00:00+0530", "2022-12-25T10:00:00+0530");
+        assert!(policy.check(&local, now, kolkata).is_ok());
+        assert_eq!(rule_of(policy.check(&local, now, None)), "slot");
+        let utc = rsvp("2022-12-25T09:00:00Z", "2022-12-25T10:00:00Z");
+        assert!(policy.check(&utc, now, None).is_ok());
+        assert_eq!(rule_of(policy.check(&utc, now, kolkata)), "slot");
+
+        let local = rsvp("2022-12-25T09:00:00+0545", "2022-12-25T10:00:00+0545");
+        assert!(policy.check(&local, now, kathmandu).is_ok());
+        assert_eq!(rule_of(policy.check(&local, now, kolkata)), "slot");
+    }
+
+    #[test]
+    fn booking_policy_should_check_lead_time_and_horizon() {
+        let policy = BookingPolicy {
+            min_lead_time: minutes(60),
+            max_horizon: minutes(30 * 24 * 60),
+            ..Default::default()
+        };
+        let booking = rsvp("2022-12-25T09:00:00+0800", "2022-12-25T10:00:00+0800");
+
+        let now = "2022-12-25T00:30:00Z".parse().unwrap();
+        assert_eq!(rule_of(policy.check(&booking, now, None)), "min_lead_time");
+
+        let now = "2022-12-24T00:30:00Z".parse().unwrap();
+        assert!(policy.check(&booking, now, None).is_ok());
+
+        let now = "2022-11-01T00:00:00Z".parse().unwrap();
+        assert_eq!(rule_of(policy.check(&booking, now, None)), "max_horizon");
+    }
+
+    #[test]
+    fn invalid_booking_policy_should_be_rejected() {
+        let policy = BookingPolicy {
+            min_duration: minutes(60),
+            max_duration: minutes(30),
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+
+        let policy = BookingPolicy {
+            slot_minutes: 7,
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+
+        let policy = BookingPolicy {
+            min_lead_time: minutes(-5),
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+
+        let policy = BookingPolicy {
+            slot_minutes: 30,
+            ..Default::default()
+        };
+        assert!(policy.validate().is_ok());
+    }
+
+    #[test]
+    fn fmt_duration_should_work() {
+        assert_eq!(fmt_duration(Duration::minutes(90)), "1h30m");
+        assert_eq!(fmt_duration(Duration::hours(26)), "1d2h");
+        assert_eq!(fmt_duration(Duration::seconds(45)), "45s");
+        assert_eq!(fmt_duration(Duration::zero()), "0s");
+    }
+}
@@ -0,0 +1,354 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::{convert_to_utc_time, BusinessHours, Error, Reservation, Validator};
+
+use super::availability::{merge_spans, Span};
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+impl BusinessHours {
+    pub fn get_timezone(&self) -> Result<Tz, Error> {
+        Tz::from_str(&self.timezone).map_err(|_| {
+            Error::InvalidBusinessHours(format!("unknown timezone {:?}", self.timezone))
+        })
+    }
+
+    fn holiday_dates(&self) -> Vec<NaiveDate> {
+        self.holidays
+            .iter()
+            .filter_map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+            .collect()
+    }
+
+    /// stretches of time the resource is open in [start, end), clipped to it.
+    pub fn open_spans(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Span>, Error> {
+        let tz = self.get_timezone()?;
+        let holidays = self.holiday_dates();
+
+        let mut spans = vec![];
+        // opening hours of the day before may last into the window on the other side of a
+        // DST change, start one day early to be safe
+        let mut day = start.with_timezone(&tz).date_naive().pred_opt().unwrap();
+        let last = end.with_timezone(&tz).date_naive();
+        while day <= last {
+            if !holidays.contains(&day) {
+                let weekday = day.weekday().number_from_monday() as i32;
+                for hours in self.hours.iter().filter(|h| h.weekday == weekday) {
+                    let open = local_to_utc(&tz, day, hours.open_minute).max(start);
+                    let close = local_to_utc(&tz, day, hours.close_minute).min(end);
+                    if open < close {
+                        spans.push((open, close));
+                    }
+                }
+            }
+            day = day.succ_opt().unwrap();
+        }
+
+        Ok(merge_spans(spans))
+    }
+
+    /// the first holiday [start, end) falls on, if any.
+    pub fn holiday_in(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Option<NaiveDate>, Error> {
+        let tz = self.get_timezone()?;
+        let first = start.with_timezone(&tz).date_naive();
+        let last = (end - Duration::microseconds(1))
+            .with_timezone(&tz)
+            .date_naive();
+        let mut holidays: Vec<_> = self
+            .holiday_dates()
+            .into_iter()
+            .filter(|day| *day >= first && *day <= last)
+            .collect();
+        holidays.sort();
+        Ok(holidays.first().copied())
+    }
+
+    /// a reservation has to fall within one stretch of opening hours, and not on a holiday.
+    pub fn check(&self, rsvp: &Reservation) -> Result<(), Error> {
+        let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+        let end = convert_to_utc_time(rsvp.end.clone().unwrap());
+
+        if let Some(day) = self.holiday_in(start, end)? {
+            return Err(Error::PolicyViolation {
+                rule: "holiday".to_string(),
+                detail: format!("resource is closed on {day}"),
+            });
+        }
+        if self.open_spans(start, end)? != [(start, end)] {
+            return Err(Error::PolicyViolation {
+                rule: "business_hours".to_string(),
+                detail: format!(
+                    "reservation is outside the opening hours of the resource ({})",
+                    self.timezone
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Validator for BusinessHours {
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |detail: String| Err(Error::InvalidBusinessHours(detail));
+        self.get_timezone()?;
+        for hours in self.hours.iter() {
+            if !(1..=7).contains(&hours.weekday) {
+                return invalid(format!("invalid weekday {}", hours.weekday));
+            }
+            if hours.open_minute < 0
+                || hours.open_minute >= hours.close_minute
+                || hours.close_minute > MINUTES_PER_DAY
+            {
+                return invalid(format!(
+                    "invalid opening hours {}-{}",
+                    hours.open_minute, hours.close_minute
+                ));
+            }
+        }
+        if let Some(day) = self
+            .holidays
+            .iter()
+            .find(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").is_err())
+        {
+            return invalid(format!("invalid holiday {day:?}"));
+        }
+        Ok(())
+    }
+}
+
+// local wall clock time of a day in utc. Times skipped by a DST change move forward to
+// when the clock resumes, times repeated by one are taken the first time they occur.
+fn local_to_utc(tz: &Tz, day: NaiveDate, minute: i32) -> DateTime<Utc> {
+    let mut local = day.and_hms_opt(0, 0, 0).unwrap() + Duration::minutes(minute as i64);
+    loop {
+        match tz.from_local_datetime(&local) {
+            LocalResult::Single(v) | LocalResult::Ambiguous(v, _) => return v.with_timezone(&Utc),
+            LocalResult::None => local += Duration::minutes(15),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OpeningHours;
+
+    fn weekdays(open_minute: i32, close_minute: i32) -> Vec<OpeningHours> {
+        (1..=5)
+            .map(|weekday| OpeningHours {
+                weekday,
+                open_minute,
+                close_minute,
+            })
+            .collect()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn open_spans_should_follow_local_time() {
+        // 9:00 - 18:00 in Shanghai is 1:00 - 10:00 utc
+        let hours = BusinessHours {
+            timezone: "Asia/Shanghai".into(),
+            hours: weekdays(9 * 60, 18 * 60),
+            holidays: vec!["2022-12-26".into()],
+        };
+        hours.validate().unwrap();
+
+        // friday 23rd to tuesday 27th, the 26th is a holiday
+        let spans = hours
+            .open_spans(utc("2022-12-23T00:00:00Z"), utc("2022-12-28T00:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            spans,
+            vec![
+                (utc("2022-12-23T01:00:00Z"), utc("2022-12-23T10:00:00Z")),
+                (utc("2022-12-27T01:00:00Z"), utc("2022-12-27T10:00:00Z")),
+            ]
+        );
+
+        // clipped to the window
+        let spans = hours
+            .open_spans(utc("2022-12-23T05:00:00Z"), utc("2022-12-23T06:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            spans,
+            vec![(utc("2022-12-23T05:00:00Z"), utc("2022-12-23T06:00:00Z"))]
+        );
+    }
+
+    #[test]
+    fn open_spans_should_follow_dst() {
+        // new york switches to daylight saving time on 2023-03-12, 9:00 is 14:00 utc
+        // before and 13:00 utc after
+        let hours = BusinessHours {
+            timezone: "America/New_York".into(),
+            hours: (1..=7)
+                .map(|weekday| OpeningHours {
+                    weekday,
+                    open_minute: 9 * 60,
+                    close_minute: 10 * 60,
+                })
+                .collect(),
+            holidays: vec![],
+        };
+        let spans = hours
+            .open_spans(utc("2023-03-11T00:00:00Z"), utc("2023-03-13T00:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            spans,
+            vec![
+                (utc("2023-03-11T14:00:00Z"), utc("2023-03-11T15:00:00Z")),
+                (utc("2023-03-12T13:00:00Z"), utc("2023-03-12T14:00:00Z")),
+            ]
+        );
+
+        // 2:30 doesn't exist on that day, the resource opens at 3:00 instead
+        let hours = BusinessHours {
+            hours: vec![OpeningHours {
+                weekday: 7,
+                open_minute: 2 * 60 + 30,
+                close_minute: 4 * 60,
+            }],
+            ..hours
+        };
+        let spans = hours
+            .open_spans(utc("2023-03-12T00:00:00Z"), utc("2023-03-13T00:00:00Z"))
+            .unwrap();
+        assert_eq!(
+            spans,
+            vec![(utc("2023-03-12T07:00:00Z"), utc("2023-03-12T08:00:00Z"))]
+        );
+    }
+
+    #[test]
+    fn adjacent_opening_hours_should_merge() {
+        let hours = BusinessHours {
+            timezone: "UTC".into(),
+            hours: vec![
+                OpeningHours {
+                    weekday: 1,
+                    open_minute: 18 * 60,
+                    close_minute: MINUTES_PER_DAY,
+                },
+                OpeningHours {
+                    weekday: 2,
+                    open_minute: 0,
+                    close_minute: 6 * 60,
+                },
+            ],
+            holidays: vec![],
+        };
+        // overnight from monday to tuesday
+        let rsvp = Reservation::new_pending(
+            "alice",
+            "room-1",
+            "2022-12-26T20:00:00Z".parse().unwrap(),
+            "2022-12-27T02:00:00Z".parse().unwrap(),
+            "",
+        );
+        assert!(hours.check(&rsvp).is_ok());
+    }
+
+    #[test]
+    fn reservation_outside_business_hours_should_be_rejected() {
+        let hours = BusinessHours {
+            timezone: "Asia/Shanghai".into(),
+            hours: weekdays(9 * 60, 18 * 60),
+            holidays: vec!["2022-12-26".into()],
+        };
+        let rsvp = |start: &str, end: &str| {
+            Reservation::new_pending(
+                "alice",
+                "room-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+
+        assert!(hours
+            .check(&rsvp(
+                "2022-12-23T09:00:00+0800",
+                "2022-12-23T18:00:00+0800"
+            ))
+            .is_ok());
+
+        let err = hours
+            .check(&rsvp(
+                "2022-12-23T03:00:00+0800",
+                "2022-12-23T10:00:00+0800",
+            ))
+            .unwrap_err();
+        assert!(matches!(err, Error::PolicyViolation { rule, .. } if rule == "business_hours"));
+
+        // across the night
+        let err = hours
+            .check(&rsvp(
+                "2022-12-22T17:00:00+0800",
+                "2022-12-23T10:00:00+0800",
+            ))
+            .unwrap_err();
+        assert!(matches!(err, Error::PolicyViolation { rule, .. } if rule == "business_hours"));
+
+        let err = hours
+            .check(&rsvp(
+                "2022-12-26T10:00:00+0800",
+                "2022-12-26T11:00:00+0800",
+            ))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Error::PolicyViolation {
+                rule: "holiday".into(),
+                detail: "resource is closed on 2022-12-26".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_business_hours_should_be_rejected() {
+        let hours = BusinessHours {
+            timezone: "Mars/Olympus_Mons".into(),
+            ..Default::default()
+        };
+        assert!(hours.validate().is_err());
+        // stored before they were validated, checking against them fails instead of panicking
+        let rsvp = Reservation::new_pending(
+            "alice",
+            "room-1",
+            "2022-12-23T09:00:00Z".parse().unwrap(),
+            "2022-12-23T10:00:00Z".parse().unwrap(),
+            "",
+        );
+        assert_eq!(
+            hours.check(&rsvp),
+            Err(Error::InvalidBusinessHours(
+                "unknown timezone \"Mars/Olympus_Mons\"".into()
+            ))
+        );
+
+        let hours = BusinessHours {
+            timezone: "UTC".into(),
+            hours: weekdays(18 * 60, 9 * 60),
+            holidays: vec![],
+        };
+        assert!(hours.validate().is_err());
+
+        let hours = BusinessHours {
+            timezone: "UTC".into(),
+            hours: vec![],
+            holidays: vec!["26/12/2022".into()],
+        };
+        assert!(hours.validate().is_err());
+    }
+}
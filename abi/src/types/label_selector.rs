@@ -0,0 +1,247 @@
+use std::{collections::HashMap, str::FromStr};
+
+use serde_json::json;
+
+use crate::{quote, Error, ToSql};
+
+/// Selects reservations by their labels, e.g. `team=infra,env in (lab,prod),!temporary`.
+/// Requirements are separated by commas and must all match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelSelector(Vec<LabelRequirement>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelRequirement {
+    /// `key=value` or `key==value`
+    Equals(String, String),
+    /// `key!=value`, also matches reservations without the label
+    NotEquals(String, String),
+    /// `key in (v1,v2)`
+    In(String, Vec<String>),
+    /// `key notin (v1,v2)`, also matches reservations without the label
+    NotIn(String, Vec<String>),
+    /// `key`
+    Exists(String),
+    /// `!key`
+    NotExists(String),
+}
+
+impl LabelSelector {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        self.0.iter().all(|req| req.matches(labels))
+    }
+}
+
+impl LabelRequirement {
+    pub fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        match self {
+            Self::Equals(k, v) => labels.get(k) == Some(v),
+            Self::NotEquals(k, v) => labels.get(k) != Some(v),
+            Self::In(k, vs) => labels.get(k).is_some_and(|v| vs.contains(v)),
+            Self::NotIn(k, vs) => labels.get(k).is_none_or(|v| !vs.contains(v)),
+            Self::Exists(k) => labels.contains_key(k),
+            Self::NotExists(k) => !labels.contains_key(k),
+        }
+    }
+}
+
+impl FromStr for LabelSelector {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidLabelSelector(s.to_string());
+
+        let mut requirements = vec![];
+        for part in split_top_level(s).ok_or_else(invalid)? {
+            let part = part.trim();
+            if part.is_empty() {
+                if s.trim().is_empty() {
+                    continue;
+                }
+                return Err(invalid());
+            }
+            requirements.push(parse_requirement(part).ok_or_else(invalid)?);
+        }
+
+        Ok(Self(requirements))
+    }
+}
+
+// split on commas which are not inside parentheses
+fn split_top_level(s: &str) -> Option<Vec<&str>> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return None,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    parts.push(&s[start..]);
+    Some(parts)
+}
+
+fn parse_requirement(s: &str) -> Option<LabelRequirement> {
+    if let Some(key) = s.strip_prefix('!') {
+        return Some(LabelRequirement::NotExists(label(key)?));
+    }
+    if let Some((key, value)) = s.split_once("!=") {
+        return Some(LabelRequirement::NotEquals(label(key)?, label(value)?));
+    }
+    if let Some((key, value)) = s.split_once("==").or_else(|| s.split_once('=')) {
+        return Some(LabelRequirement::Equals(label(key)?, label(value)?));
+    }
+    if let Some((key, values)) = s.split_once('(') {
+        let values = values.strip_suffix(')')?;
+        let values = values.split(',').map(label).collect::<Option<Vec<_>>>()?;
+        let mut words = key.split_whitespace();
+        let (key, op) = (label(words.next()?)?, words.next()?);
+        if words.next().is_some() {
+            return None;
+        }
+        return match op {
+            "in" => Some(LabelRequirement::In(key, values)),
+            "notin" => Some(LabelRequirement::NotIn(key, values)),
+            _ => None,
+        };
+    }
+    Some(LabelRequirement::Exists(label(s)?))
+}
+
+fn label(s: &str) -> Option<String> {
+    let s = s.trim();
+    is_valid_label(s).then(|| s.to_string())
+}
+
+/// label keys and values are limited to ascii letters, digits, `-`, `_`, `.` and `/`, so
+/// any label can be written in a selector.
+pub(crate) fn is_valid_label(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= 63
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+}
+
+/// sql condition on the `labels` jsonb column. Containment (`@>`) and key existence (`?`)
+/// are used so the GIN index on labels applies.
+impl ToSql for LabelSelector {
+    fn to_sql(&self) -> String {
+        if self.0.is_empty() {
+            return "TRUE".to_string();
+        }
+        self.0
+            .iter()
+            .map(|req| req.to_sql())
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    }
+}
+
+impl ToSql for LabelRequirement {
+    fn to_sql(&self) -> String {
+        let contains =
+            |k: &str, v: &str| format!("labels @> {}::jsonb", quote(&json!({ k: v }).to_string()));
+        let any_of = |k: &str, vs: &[String]| {
+            let conds: Vec<_> = vs.iter().map(|v| contains(k, v)).collect();
+            format!("({})", conds.join(" OR "))
+        };
+
+        match self {
+            Self::Equals(k, v) => contains(k, v),
+            Self::NotEquals(k, v) => format!("NOT {}", contains(k, v)),
+            Self::In(k, vs) => any_of(k, vs),
+            Self::NotIn(k, vs) => format!("NOT {}", any_of(k, vs)),
+            Self::Exists(k) => format!("labels ? {}", quote(k)),
+            Self::NotExists(k) => format!("NOT labels ? {}", quote(k)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_selector_should_parse() {
+        let selector: LabelSelector = "team=infra, env in (lab, prod),!temporary,owner!=bob,gpu"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            selector,
+            LabelSelector(vec![
+                LabelRequirement::Equals("team".into(), "infra".into()),
+                LabelRequirement::In("env".into(), vec!["lab".into(), "prod".into()]),
+                LabelRequirement::NotExists("temporary".into()),
+                LabelRequirement::NotEquals("owner".into(), "bob".into()),
+                LabelRequirement::Exists("gpu".into()),
+            ])
+        );
+
+        let selector: LabelSelector = "env notin (lab),a==b".parse().unwrap();
+        assert_eq!(
+            selector,
+            LabelSelector(vec![
+                LabelRequirement::NotIn("env".into(), vec!["lab".into()]),
+                LabelRequirement::Equals("a".into(), "b".into()),
+            ])
+        );
+
+        assert!("".parse::<LabelSelector>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_label_selector_should_be_rejected() {
+        for s in [
+            "team=",
+            "=infra",
+            "team=in fra",
+            "env in (lab,prod",
+            "env in lab)",
+            "env within (lab)",
+            "a,,b",
+            "team='x'",
+        ] {
+            assert_eq!(
+                s.parse::<LabelSelector>(),
+                Err(Error::InvalidLabelSelector(s.to_string())),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn label_selector_should_generate_correct_sql() {
+        let selector: LabelSelector = "team=infra,env in (lab,prod),!temporary".parse().unwrap();
+        assert_eq!(
+            selector.to_sql(),
+            r#"labels @> '{"team":"infra"}'::jsonb AND (labels @> '{"env":"lab"}'::jsonb OR labels @> '{"env":"prod"}'::jsonb) AND NOT labels ? 'temporary'"#
+        );
+        assert_eq!(LabelSelector::default().to_sql(), "TRUE");
+    }
+
+    #[test]
+    fn label_selector_should_match_labels() {
+        let labels = HashMap::from([
+            ("team".to_string(), "infra".to_string()),
+            ("env".to_string(), "lab".to_string()),
+        ]);
+        let selector: LabelSelector = "team=infra,env in (lab,prod),!temporary".parse().unwrap();
+        assert!(selector.matches(&labels));
+
+        let selector: LabelSelector = "env notin (lab)".parse().unwrap();
+        assert!(!selector.matches(&labels));
+    }
+}
@@ -1,8 +1,18 @@
+mod approval;
+mod availability;
+mod booking_policy;
+mod business_hours;
+mod label_selector;
+mod preemption;
+mod quota;
 mod request;
 mod reservation;
 mod reservation_filter;
 mod reservation_query;
+mod reservation_search;
 mod reservation_status;
+mod resource;
+mod waitlist;
 
 use std::ops::Bound;
 
@@ -12,6 +22,10 @@ use sqlx::postgres::types::PgRange;
 
 use crate::{convert_to_utc_time, Error};
 
+pub use availability::{merge_spans, subtract_spans, Span};
+pub use label_selector::{LabelRequirement, LabelSelector};
+pub use reservation_search::SearchHit;
+
 pub fn validate_range(start: Option<&Timestamp>, end: Option<&Timestamp>) -> Result<(), Error> {
     if start.is_none() || end.is_none() {
         return Err(Error::InvalidTime);
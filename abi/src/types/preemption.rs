@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, FromRow, Row};
+
+use crate::{convert_to_timestamp, Preemption, Reservation};
+
+/// rows of `rsvp.preemption`, with the id of the cancelled reservation selected as `id`.
+impl FromRow<'_, PgRow> for Preemption {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let created_at: DateTime<Utc> = row.get("created_at");
+        Ok(Self {
+            reservation: Some(Reservation::from_row(row)?),
+            preempted_by: row.get("preempted_by"),
+            reason: row.get("reason"),
+            created_at: Some(convert_to_timestamp(created_at)),
+        })
+    }
+}
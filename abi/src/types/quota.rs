@@ -0,0 +1,272 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, SecondsFormat, TimeZone, Utc};
+use sqlx::{postgres::PgRow, types::Json, FromRow, Row};
+
+use crate::{
+    convert_to_utc_time, quote, AttributeCondition, Error, Quota, QuotaKind, QuotaPeriod,
+    Reservation, ToSql, Validator,
+};
+
+use super::{availability::Span, label_selector::is_valid_label};
+
+impl Quota {
+    pub fn active_reservations(id: impl Into<String>, limit: i64) -> Self {
+        Self {
+            id: id.into(),
+            kind: QuotaKind::ActiveReservations as i32,
+            limit,
+            period: QuotaPeriod::Unknown as i32,
+            conditions: vec![],
+        }
+    }
+
+    pub fn booked_minutes(id: impl Into<String>, limit: i64, period: QuotaPeriod) -> Self {
+        Self {
+            id: id.into(),
+            kind: QuotaKind::BookedMinutes as i32,
+            limit,
+            period: period as i32,
+            conditions: vec![],
+        }
+    }
+
+    pub fn with_condition(mut self, condition: AttributeCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn get_kind(&self) -> QuotaKind {
+        QuotaKind::from_i32(self.kind).unwrap_or(QuotaKind::Unknown)
+    }
+
+    pub fn get_period(&self) -> QuotaPeriod {
+        QuotaPeriod::from_i32(self.period).unwrap_or(QuotaPeriod::Unknown)
+    }
+
+    /// sql telling whether the quota applies to resource `$1` of tenant `$2`, None if it
+    /// applies to all.
+    pub fn applies_sql(&self) -> Option<String> {
+        self.resource_conditions().map(|conditions| {
+            format!("SELECT EXISTS (SELECT 1 FROM rsvp.resource WHERE tenant_id = $2 AND id = $1 AND {conditions})")
+        })
+    }
+
+    /// sql of the current usage of user `$1` in tenant `$2`, for a reservation made at `now`.
+    pub fn usage_sql(&self, rsvp: &Reservation, now: DateTime<Utc>) -> String {
+        let resources = match self.resource_conditions() {
+            Some(conditions) => {
+                format!(" AND resource_id IN (SELECT id FROM rsvp.resource WHERE tenant_id = $2 AND {conditions})")
+            }
+            None => String::new(),
+        };
+        match self.get_kind() {
+            QuotaKind::BookedMinutes => {
+                let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+                let (lower, upper) = self.get_period().window(start);
+                format!(
+                    "SELECT COALESCE(floor(sum(extract(epoch FROM upper(timespan) - lower(timespan))) / 60), 0)::bigint FROM rsvp.reservation WHERE tenant_id = $2 AND user_id = $1 AND lower(timespan) >= {} AND lower(timespan) < {}{resources}",
+                    timestamp(lower),
+                    timestamp(upper)
+                )
+            }
+            _ => format!(
+                "SELECT count(*) FROM rsvp.reservation WHERE tenant_id = $2 AND user_id = $1 AND upper(timespan) > {}{resources}",
+                timestamp(now)
+            ),
+        }
+    }
+
+    /// what the reservation adds to the usage of the quota.
+    pub fn requested(&self, rsvp: &Reservation) -> i64 {
+        match self.get_kind() {
+            QuotaKind::BookedMinutes => {
+                let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+                let end = convert_to_utc_time(rsvp.end.clone().unwrap());
+                (end - start).num_minutes()
+            }
+            _ => 1,
+        }
+    }
+
+    /// make sure the reservation fits into what's left of the quota.
+    pub fn check(&self, usage: i64, rsvp: &Reservation) -> Result<(), Error> {
+        if usage + self.requested(rsvp) > self.limit {
+            return Err(Error::QuotaExceeded {
+                quota: self.id.clone(),
+                limit: self.limit,
+                usage,
+            });
+        }
+        Ok(())
+    }
+
+    fn resource_conditions(&self) -> Option<String> {
+        if self.conditions.is_empty() {
+            return None;
+        }
+        let conditions: Vec<_> = self.conditions.iter().map(|c| c.to_sql()).collect();
+        Some(conditions.join(" AND "))
+    }
+}
+
+impl QuotaPeriod {
+    /// the period `at` falls in.
+    pub fn window(&self, at: DateTime<Utc>) -> Span {
+        let day = at.date_naive();
+        let (first, last) = match self {
+            QuotaPeriod::Week => {
+                let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+                (monday, monday + Duration::days(7))
+            }
+            QuotaPeriod::Month => {
+                let first = day.with_day(1).unwrap();
+                let next = match first.month() {
+                    12 => NaiveDate::from_ymd_opt(first.year() + 1, 1, 1),
+                    m => NaiveDate::from_ymd_opt(first.year(), m + 1, 1),
+                };
+                (first, next.unwrap())
+            }
+            _ => (day, day + Duration::days(1)),
+        };
+        let midnight = |d: NaiveDate| Utc.from_utc_datetime(&d.and_hms_opt(0, 0, 0).unwrap());
+        (midnight(first), midnight(last))
+    }
+}
+
+impl Validator for Quota {
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = |detail: String| Err(Error::InvalidQuota(detail));
+        if !is_valid_label(&self.id) {
+            return invalid(format!("invalid id {:?}", self.id));
+        }
+        match self.get_kind() {
+            QuotaKind::Unknown => return invalid("kind is required".to_string()),
+            QuotaKind::BookedMinutes if self.get_period() == QuotaPeriod::Unknown => {
+                return invalid("booked minutes need a period".to_string());
+            }
+            _ => {}
+        }
+        if self.limit < 0 {
+            return invalid("limit can't be negative".to_string());
+        }
+        for condition in self.conditions.iter() {
+            condition.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl FromRow<'_, PgRow> for Quota {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let kind: String = row.get("kind");
+        let period: String = row.get("period");
+        let conditions: Json<Vec<AttributeCondition>> = row.get("conditions");
+        Ok(Self {
+            id: row.get("id"),
+            kind: QuotaKind::from_str_name(&kind).unwrap_or(QuotaKind::Unknown) as i32,
+            limit: row.get("quota_limit"),
+            period: QuotaPeriod::from_str_name(&period).unwrap_or(QuotaPeriod::Unknown) as i32,
+            conditions: conditions.0,
+        })
+    }
+}
+
+fn timestamp(t: DateTime<Utc>) -> String {
+    quote(&t.to_rfc3339_opts(SecondsFormat::Micros, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AttributeOp;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn rsvp(start: &str, end: &str) -> Reservation {
+        Reservation::new_pending(
+            "alice",
+            "room-1",
+            start.parse().unwrap(),
+            end.parse().unwrap(),
+            "",
+        )
+    }
+
+    #[test]
+    fn quota_period_window_should_work() {
+        let at = utc("2022-12-25T15:00:00Z");
+        assert_eq!(
+            QuotaPeriod::Day.window(at),
+            (utc("2022-12-25T00:00:00Z"), utc("2022-12-26T00:00:00Z"))
+        );
+        assert_eq!(
+            QuotaPeriod::Week.window(at),
+            (utc("2022-12-19T00:00:00Z"), utc("2022-12-26T00:00:00Z"))
+        );
+        assert_eq!(
+            QuotaPeriod::Month.window(at),
+            (utc("2022-12-01T00:00:00Z"), utc("2023-01-01T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn quota_should_generate_correct_sql() {
+        let quota = Quota::active_reservations("rooms", 3).with_condition(AttributeCondition::new(
+            "type",
+            AttributeOp::Eq,
+            "room",
+        ));
+        quota.validate().unwrap();
+        let booking = rsvp("2022-12-25T15:00:00+0800", "2022-12-25T17:00:00+0800");
+        assert_eq!(
+            quota.applies_sql().unwrap(),
+            r#"SELECT EXISTS (SELECT 1 FROM rsvp.resource WHERE tenant_id = $2 AND id = $1 AND attributes @@ '$."type" == "room"')"#
+        );
+        assert_eq!(
+            quota.usage_sql(&booking, utc("2022-12-20T00:00:00Z")),
+            r#"SELECT count(*) FROM rsvp.reservation WHERE tenant_id = $2 AND user_id = $1 AND upper(timespan) > '2022-12-20T00:00:00.000000Z' AND resource_id IN (SELECT id FROM rsvp.resource WHERE tenant_id = $2 AND attributes @@ '$."type" == "room"')"#
+        );
+
+        let quota = Quota::booked_minutes("weekly", 20 * 60, QuotaPeriod::Week);
+        assert_eq!(quota.applies_sql(), None);
+        assert_eq!(
+            quota.usage_sql(&booking, utc("2022-12-20T00:00:00Z")),
+            "SELECT COALESCE(floor(sum(extract(epoch FROM upper(timespan) - lower(timespan))) / 60), 0)::bigint FROM rsvp.reservation WHERE tenant_id = $2 AND user_id = $1 AND lower(timespan) >= '2022-12-19T00:00:00.000000Z' AND lower(timespan) < '2022-12-26T00:00:00.000000Z'"
+        );
+    }
+
+    #[test]
+    fn quota_check_should_report_limit_and_usage() {
+        let booking = rsvp("2022-12-25T15:00:00+0800", "2022-12-25T17:00:00+0800");
+        let quota = Quota::active_reservations("rooms", 3);
+        assert!(quota.check(2, &booking).is_ok());
+        assert_eq!(
+            quota.check(3, &booking),
+            Err(Error::QuotaExceeded {
+                quota: "rooms".into(),
+                limit: 3,
+                usage: 3,
+            })
+        );
+
+        let quota = Quota::booked_minutes("weekly", 180, QuotaPeriod::Week);
+        assert_eq!(quota.requested(&booking), 120);
+        assert!(quota.check(60, &booking).is_ok());
+        assert!(quota.check(61, &booking).is_err());
+    }
+
+    #[test]
+    fn invalid_quota_should_be_rejected() {
+        assert!(Quota::active_reservations("", 3).validate().is_err());
+        assert!(Quota::active_reservations("rooms", -1).validate().is_err());
+        assert!(Quota::booked_minutes("weekly", 60, QuotaPeriod::Unknown)
+            .validate()
+            .is_err());
+        let quota = Quota {
+            kind: QuotaKind::Unknown as i32,
+            ..Quota::active_reservations("rooms", 3)
+        };
+        assert!(quota.validate().is_err());
+    }
+}
@@ -1,5 +1,11 @@
+use chrono::{DateTime, FixedOffset, Utc};
+
 use crate::{
-    FilterRequest, QueryRequest, Reservation, ReservationFilter, ReservationQuery, ReserveRequest,
+    convert_to_timestamp, ApprovalQuery, ApproveRequest, AvailabilityRequest, DeleteQuotaRequest,
+    FilterRequest, GetResourceRequest, ListPendingApprovalsRequest, ListWaitlistRequest,
+    PutQuotaRequest, PutResourceRequest, QueryRequest, Quota, RejectRequest, Reservation,
+    ReservationFilter, ReservationQuery, ReservationSearch, ReserveRequest, Resource,
+    SearchRequest,
 };
 
 macro_rules! impl_new {
@@ -13,9 +19,91 @@ macro_rules! impl_new {
         }
     };
 }
-impl_new!(ReserveRequest, reservation, Reservation);
 impl_new!(FilterRequest, filter, ReservationFilter);
 impl_new!(QueryRequest, query, ReservationQuery);
+impl_new!(SearchRequest, search, ReservationSearch);
+impl_new!(PutResourceRequest, resource, Resource);
+impl_new!(PutQuotaRequest, quota, Quota);
+impl_new!(ListPendingApprovalsRequest, query, ApprovalQuery);
+
+macro_rules! impl_decision_new {
+    ($request_name: ident) => {
+        impl $request_name {
+            pub fn new(id: i64, approver: impl Into<String>, reason: impl Into<String>) -> Self {
+                Self {
+                    id,
+                    approver: approver.into(),
+                    reason: reason.into(),
+                }
+            }
+        }
+    };
+}
+impl_decision_new!(ApproveRequest);
+impl_decision_new!(RejectRequest);
+
+impl ReserveRequest {
+    pub fn new(reservation: Reservation) -> Self {
+        Self {
+            reservation: Some(reservation),
+            ..Default::default()
+        }
+    }
+
+    /// join the waitlist if the window is taken.
+    pub fn new_or_wait(reservation: Reservation) -> Self {
+        Self {
+            reservation: Some(reservation),
+            waitlist: true,
+            ..Default::default()
+        }
+    }
+
+    /// cancel overlapping reservations of a lower priority for the given reason.
+    pub fn new_preempting(reservation: Reservation, reason: impl Into<String>) -> Self {
+        Self {
+            reservation: Some(reservation),
+            preempt: true,
+            preempt_reason: reason.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListWaitlistRequest {
+    pub fn new(resource_id: impl Into<String>, user_id: impl Into<String>) -> Self {
+        Self {
+            resource_id: resource_id.into(),
+            user_id: user_id.into(),
+        }
+    }
+}
+
+impl AvailabilityRequest {
+    pub fn new(
+        resource_id: impl Into<String>,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Self {
+        Self {
+            resource_id: resource_id.into(),
+            start: Some(convert_to_timestamp(start.with_timezone(&Utc))),
+            end: Some(convert_to_timestamp(end.with_timezone(&Utc))),
+        }
+    }
+}
+
+impl GetResourceRequest {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+impl DeleteQuotaRequest {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
 // impl ReserveRequest {
 //     pub fn new(reservation: Reservation) -> Self {
 //         Self {
@@ -1,14 +1,17 @@
 use crate::{
-    convert_to_timestamp, pager::Id, Error, Reservation, ReservationStatus, RsvpStatus, Validator,
+    convert_to_micros, convert_to_timestamp, convert_to_utc_time,
+    pager::{Id, SortKey},
+    Error, Reservation, ReservationSortKey, ReservationStatus, RsvpStatus, Validator,
 };
-use chrono::{DateTime, FixedOffset, Utc};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
 use sqlx::{
     postgres::{types::PgRange, PgRow},
+    types::Json,
     FromRow, Row,
 };
-use std::ops::Bound;
+use std::{collections::HashMap, ops::Bound};
 
-use super::{get_timespan, validate_range};
+use super::{get_timespan, label_selector::is_valid_label, validate_range};
 
 impl Reservation {
     pub fn new_pending(
@@ -26,22 +29,87 @@ impl Reservation {
             end: Some(convert_to_timestamp(end.with_timezone(&Utc))),
             note: note.into(),
             status: ReservationStatus::Pending as i32,
+            labels: Default::default(),
+            priority: 0,
+            checked_in_at: None,
+            tenant_id: String::new(),
         }
     }
 
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_labels<K, V>(mut self, labels: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.labels = labels
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        self
+    }
+
     pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
         get_timespan(self.start.as_ref(), self.end.as_ref())
     }
+
+    pub fn get_status(&self) -> ReservationStatus {
+        ReservationStatus::from_i32(self.status).unwrap_or(ReservationStatus::Unknown)
+    }
+
+    /// make sure the user may check in at `now`. Check-in opens the check-in window of the
+    /// resource before the start and closes at the end.
+    pub fn check_in_allowed(
+        &self,
+        window: Option<Duration>,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        if self.get_status() != ReservationStatus::Confirmed {
+            return Err(Error::CheckInNotAllowed(format!(
+                "reservation {} isn't confirmed",
+                self.id
+            )));
+        }
+        let start = convert_to_utc_time(self.start.clone().unwrap());
+        let end = convert_to_utc_time(self.end.clone().unwrap());
+        let opens = start - window.unwrap_or_else(Duration::zero);
+        if now < opens || now >= end {
+            return Err(Error::CheckInNotAllowed(format!(
+                "check-in of reservation {} is open from {opens} to {end}",
+                self.id
+            )));
+        }
+        Ok(())
+    }
 }
 
 impl Validator for Reservation {
     fn validate(&self) -> Result<(), Error> {
+        if self.resource_id.is_empty() {
+            return Err(Error::InvalidResourceId(self.resource_id.clone()));
+        }
+
+        self.validate_without_resource()
+    }
+}
+
+impl Reservation {
+    /// everything but the resource, for reservations where we pick the resource ourselves.
+    pub(crate) fn validate_without_resource(&self) -> Result<(), Error> {
         if self.user_id.is_empty() {
             return Err(Error::InvalidUserId(self.user_id.clone()));
         }
 
-        if self.resource_id.is_empty() {
-            return Err(Error::InvalidResourceId(self.resource_id.clone()));
+        if let Some((k, v)) = self
+            .labels
+            .iter()
+            .find(|(k, v)| !is_valid_label(k) || !is_valid_label(v))
+        {
+            return Err(Error::InvalidLabel(format!("{k}={v}")));
         }
 
         validate_range(self.start.as_ref(), self.end.as_ref())
@@ -61,6 +129,12 @@ impl FromRow<'_, PgRow> for Reservation {
         let end = range.end.unwrap();
 
         let status: RsvpStatus = row.get("status");
+        let labels: Json<HashMap<String, String>> = row.get("labels");
+        // rows of reservations cancelled by preemption don't keep the check-in
+        let checked_in_at: Option<DateTime<Utc>> = match row.try_get("checked_in_at") {
+            Err(sqlx::Error::ColumnNotFound(_)) => None,
+            v => v?,
+        };
 
         Ok(Self {
             id,
@@ -70,6 +144,10 @@ impl FromRow<'_, PgRow> for Reservation {
             start: Some(convert_to_timestamp(start)),
             end: Some(convert_to_timestamp(end)),
             note: row.get("note"),
+            labels: labels.0,
+            priority: row.get("priority"),
+            checked_in_at: checked_in_at.map(convert_to_timestamp),
+            tenant_id: row.get("tenant_id"),
         })
     }
 }
@@ -80,10 +158,20 @@ impl Id for Reservation {
     }
 }
 
+impl SortKey<ReservationSortKey> for Reservation {
+    fn sort_key(&self, by: ReservationSortKey) -> i64 {
+        match by {
+            ReservationSortKey::Id => self.id,
+            ReservationSortKey::Start => self.start.as_ref().map(convert_to_micros).unwrap_or(0),
+            ReservationSortKey::End => self.end.as_ref().map(convert_to_micros).unwrap_or(0),
+        }
+    }
+}
+
 #[derive(Debug)]
-struct NaiveRange<T> {
-    start: Option<T>,
-    end: Option<T>,
+pub(crate) struct NaiveRange<T> {
+    pub(crate) start: Option<T>,
+    pub(crate) end: Option<T>,
 }
 
 impl<T> From<PgRange<T>> for NaiveRange<T> {
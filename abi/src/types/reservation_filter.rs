@@ -1,9 +1,13 @@
 use std::collections::VecDeque;
 
+use chrono::SecondsFormat;
+use sha2::{Digest, Sha256};
+
 use crate::{
-    pager::{Id, PageInfo, Pager, Paginator},
-    Error, FilterPager, Normalizer, ReservationFilter, ReservationFilterBuilder, ReservationStatus,
-    ToSql, Validator,
+    convert_micros_to_utc_time,
+    pager::{Cursor, PageInfo, PageSigner, PageToken, Paginator, SortKey},
+    quote, Error, FilterPager, LabelSelector, Normalizer, ReservationFilter,
+    ReservationFilterBuilder, ReservationSortKey, ReservationStatus, ToSql, Validator,
 };
 
 impl ReservationFilterBuilder {
@@ -21,13 +25,13 @@ impl Validator for ReservationFilter {
         if self.page_size < 10 || self.page_size > 100 {
             return Err(Error::InvalidPageSize(self.page_size));
         }
-        if let Some(cursor) = self.cursor {
-            if cursor < 0 {
-                return Err(Error::InvalidCursor(cursor));
-            }
+        if let Some(cursor) = self.cursor.as_ref() {
+            PageToken::parse(cursor)?;
         }
 
         ReservationStatus::from_i32(self.status).ok_or(Error::InvalidStatus(self.status))?;
+        ReservationSortKey::from_i32(self.order_by).ok_or(Error::InvalidSortKey(self.order_by))?;
+        self.get_label_selector()?;
 
         Ok(())
     }
@@ -41,97 +45,189 @@ impl Normalizer for ReservationFilter {
     }
 }
 
-impl From<Pager> for FilterPager {
-    fn from(value: Pager) -> Self {
-        Self {
-            prev: value.prev,
-            next: value.next,
-            total: value.total,
-        }
-    }
-}
-
-impl From<&FilterPager> for Pager {
-    fn from(pager: &FilterPager) -> Self {
-        Self {
-            prev: pager.prev,
-            next: pager.next,
-            total: pager.total,
-        }
-    }
-}
-
 impl ReservationFilter {
     fn page_info(&self) -> PageInfo {
         PageInfo {
-            cursor: self.cursor,
+            cursor: self
+                .cursor
+                .as_deref()
+                .and_then(|v| PageToken::parse(v).ok())
+                .map(|token| token.cursor),
             page_size: self.page_size,
             desc: self.desc,
         }
     }
 
-    pub fn get_pager<T: Id>(&self, data: &mut VecDeque<T>) -> FilterPager {
-        let page_info = self.page_info();
-        let pager = page_info.get_pager(data);
-        pager.into()
+    /// identifies which rows the filter selects and in what order. A page token is only
+    /// accepted by filters with the same fingerprint as the one it was issued for.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.resource_id.as_str(),
+            self.user_id.as_str(),
+            &self.status.to_string(),
+            if self.desc { "desc" } else { "asc" },
+            &self.order_by.to_string(),
+            self.label_selector.as_str(),
+            self.tenant_id.as_str(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        let digest = hasher.finalize();
+        u64::from_be_bytes(digest[..8].try_into().unwrap())
+    }
+
+    /// make sure the cursor was signed by us for this very filter.
+    pub fn verify_cursor(&self, signer: &PageSigner) -> Result<(), Error> {
+        if let Some(cursor) = self.cursor.as_ref() {
+            let token = signer.verify(cursor)?;
+            if token.fingerprint != self.fingerprint() {
+                return Err(Error::InvalidCursor(cursor.clone()));
+            }
+        }
+        Ok(())
     }
 
-    pub fn get_cursor(&self) -> i64 {
-        self.cursor.unwrap_or(if self.desc { i64::MAX } else { 0 })
+    pub fn get_pager<T: SortKey<ReservationSortKey>>(
+        &self,
+        data: &mut VecDeque<T>,
+        signer: &PageSigner,
+    ) -> FilterPager {
+        let order_by = self.get_order_by();
+        let pager = self
+            .page_info()
+            .get_pager_by(data, |v| v.sort_key(order_by));
+
+        let fingerprint = self.fingerprint();
+        let sign = |cursor: Option<Cursor>| {
+            cursor.map(|cursor| signer.sign(&PageToken::new(cursor, fingerprint)))
+        };
+
+        FilterPager {
+            prev: sign(pager.prev),
+            next: sign(pager.next),
+            total: pager.total,
+            total_estimated: false,
+        }
     }
 
     pub fn get_status(&self) -> ReservationStatus {
         ReservationStatus::from_i32(self.status).unwrap()
     }
 
+    pub fn get_order_by(&self) -> ReservationSortKey {
+        ReservationSortKey::from_i32(self.order_by).unwrap()
+    }
+
+    pub fn get_label_selector(&self) -> Result<LabelSelector, Error> {
+        self.label_selector.parse()
+    }
+
+    pub fn prev_page(&self, pager: &FilterPager) -> Option<Self> {
+        self.with_cursor(pager.prev.as_ref())
+    }
+
     pub fn next_page(&self, pager: &FilterPager) -> Option<Self> {
-        let page_info = self.page_info();
-        let next_page_info = page_info.next_page(&pager.into());
-
-        next_page_info.map(|page_info| Self {
-            resource_id: self.resource_id.clone(),
-            user_id: self.user_id.clone(),
-            status: self.status,
-            cursor: page_info.cursor,
-            page_size: page_info.page_size,
-            desc: page_info.desc,
+        self.with_cursor(pager.next.as_ref())
+    }
+
+    fn with_cursor(&self, cursor: Option<&String>) -> Option<Self> {
+        cursor.map(|cursor| Self {
+            cursor: Some(cursor.clone()),
+            ..self.clone()
         })
     }
 }
 
 impl ToSql for ReservationFilter {
     fn to_sql(&self) -> String {
-        let middle_plus = if self.cursor.is_none() { 0 } else { 1 };
+        let page = self.page_info();
+        // rows before the cursor are fetched in reverse order, the pager flips them back
+        let desc = self.desc != page.is_backward();
 
         let mut sql = format!(
-            "SELECT * FROM rsvp.reservation WHERE status='{}'::rsvp.reservation_status",
+            "SELECT * FROM rsvp.reservation WHERE tenant_id = {} AND status='{}'::rsvp.reservation_status",
+            quote(&self.tenant_id),
             self.get_status()
         );
-        if self.desc {
-            sql.push_str(&format!(" AND id <= {}", self.get_cursor()));
-        } else {
-            sql.push_str(&format!(" AND id >= {}", self.get_cursor()));
+        let op = if desc { "<" } else { ">" };
+        let order = if desc { "DESC" } else { "ASC" };
+        let column = match self.get_order_by() {
+            ReservationSortKey::Id => None,
+            ReservationSortKey::Start => Some("lower(timespan)"),
+            ReservationSortKey::End => Some("upper(timespan)"),
+        };
+
+        if let Some(cursor) = page.cursor {
+            match column {
+                None => sql.push_str(&format!(" AND id {op} {}", cursor.id)),
+                // compare as a row so rows with the same time are paged by id
+                Some(column) => sql.push_str(&format!(
+                    " AND ({column}, id) {op} ('{}'::timestamptz, {})",
+                    convert_micros_to_utc_time(cursor.key)
+                        .to_rfc3339_opts(SecondsFormat::Micros, true),
+                    cursor.id
+                )),
+            }
+        }
+        sql.push_str(&self.conditions());
+
+        match column {
+            None => sql.push_str(&format!(" ORDER BY id {order}")),
+            Some(column) => sql.push_str(&format!(" ORDER BY {column} {order}, id {order}")),
+        }
+        sql.push_str(&format!(" LIMIT {}", self.page_size + 1));
+
+        sql
+    }
+}
+
+impl ReservationFilter {
+    /// all rows matching the filter regardless of the page, used to estimate the total.
+    pub fn to_rows_sql(&self) -> String {
+        format!(
+            "SELECT * FROM rsvp.reservation WHERE tenant_id = {} AND status='{}'::rsvp.reservation_status{}",
+            quote(&self.tenant_id),
+            self.get_status(),
+            self.conditions()
+        )
+    }
+
+    /// exact number of rows matching the filter regardless of the page.
+    pub fn to_count_sql(&self) -> String {
+        format!(
+            "SELECT count(*) FROM rsvp.reservation WHERE tenant_id = {} AND status='{}'::rsvp.reservation_status{}",
+            quote(&self.tenant_id),
+            self.get_status(),
+            self.conditions()
+        )
+    }
+
+    fn conditions(&self) -> String {
+        let mut sql = self.id_conditions();
+        // validated when the filter is built or normalized
+        let selector = self.get_label_selector().unwrap_or_default();
+        if !selector.is_empty() {
+            sql.push_str(&format!(" AND {}", selector.to_sql()));
         }
+        sql
+    }
+
+    fn id_conditions(&self) -> String {
         if self.user_id.is_empty() && self.resource_id.is_empty() {
-            sql.push_str(" AND TRUE");
+            " AND TRUE".to_string()
         } else if self.user_id.is_empty() {
-            sql.push_str(&format!(" AND resource_id = '{}'", self.resource_id));
+            format!(" AND resource_id = {}", quote(&self.resource_id))
         } else if self.resource_id.is_empty() {
-            sql.push_str(&format!(" AND user_id = '{}'", self.user_id));
+            format!(" AND user_id = {}", quote(&self.user_id))
         } else {
-            sql.push_str(&format!(
-                " AND user_id = '{}' AND resource_id = '{}'",
-                self.user_id, self.resource_id,
-            ));
+            format!(
+                " AND user_id = {} AND resource_id = {}",
+                quote(&self.user_id),
+                quote(&self.resource_id),
+            )
         }
-
-        sql.push_str(&format!(
-            " ORDER BY id {} LIMIT {}",
-            if self.desc { "DESC" } else { "ASC" },
-            self.page_size + 1 + middle_plus
-        ));
-
-        sql
     }
 }
 
@@ -140,6 +236,10 @@ mod tests {
     use super::*;
     use crate::{pager::pager_test_utils::generate_test_ids, ReservationFilterBuilder};
 
+    fn sign(filter: &ReservationFilter, cursor: Cursor) -> String {
+        PageSigner::new("secret").sign(&PageToken::new(cursor, filter.fingerprint()))
+    }
+
     #[test]
     fn filter_should_generate_correct_sql() {
         let filter = ReservationFilterBuilder::default()
@@ -147,13 +247,13 @@ mod tests {
             .build()
             .unwrap();
         let sql = filter.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE status='pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'luckychacha-id' ORDER BY id ASC LIMIT 11");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND user_id = 'luckychacha-id' ORDER BY id ASC LIMIT 11");
         let filter = ReservationFilterBuilder::default()
             .resource_id("test-r-id")
             .build()
             .unwrap();
         let sql = filter.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE status='pending'::rsvp.reservation_status AND id >= 0 AND resource_id = 'test-r-id' ORDER BY id ASC LIMIT 11");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND resource_id = 'test-r-id' ORDER BY id ASC LIMIT 11");
 
         let filter = ReservationFilterBuilder::default()
             .resource_id("test-r-id")
@@ -161,54 +261,204 @@ mod tests {
             .build()
             .unwrap();
         let sql = filter.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE status='pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'luckychacha-id' AND resource_id = 'test-r-id' ORDER BY id ASC LIMIT 11");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND user_id = 'luckychacha-id' AND resource_id = 'test-r-id' ORDER BY id ASC LIMIT 11");
 
         let filter = ReservationFilterBuilder::default()
             .desc(true)
             .build()
             .unwrap();
         let sql = filter.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE status='pending'::rsvp.reservation_status AND id <= 9223372036854775807 AND TRUE ORDER BY id DESC LIMIT 11");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND TRUE ORDER BY id DESC LIMIT 11");
 
         let filter = ReservationFilterBuilder::default()
-            .cursor(10)
             .user_id("luckychacha-id")
             .build()
             .unwrap();
+        let filter = ReservationFilter {
+            cursor: Some(sign(&filter, Cursor::forward(10, 10))),
+            ..filter
+        };
         let sql = filter.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE status='pending'::rsvp.reservation_status AND id >= 10 AND user_id = 'luckychacha-id' ORDER BY id ASC LIMIT 12");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND id > 10 AND user_id = 'luckychacha-id' ORDER BY id ASC LIMIT 11");
+
+        let filter = ReservationFilter {
+            cursor: Some(sign(&filter, Cursor::backward(10, 10))),
+            ..filter
+        };
+        let sql = filter.to_sql();
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND id < 10 AND user_id = 'luckychacha-id' ORDER BY id DESC LIMIT 11");
 
         let filter = ReservationFilterBuilder::default()
             .user_id("test-r-id")
-            .cursor(100)
             .desc(true)
             .build()
             .unwrap();
+        let filter = ReservationFilter {
+            cursor: Some(sign(&filter, Cursor::forward(100, 100))),
+            ..filter
+        };
+        let sql = filter.to_sql();
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND id < 100 AND user_id = 'test-r-id' ORDER BY id DESC LIMIT 11");
+
+        let filter = ReservationFilter {
+            cursor: Some(sign(&filter, Cursor::backward(100, 100))),
+            ..filter
+        };
         let sql = filter.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE status='pending'::rsvp.reservation_status AND id <= 100 AND user_id = 'test-r-id' ORDER BY id DESC LIMIT 12");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND id > 100 AND user_id = 'test-r-id' ORDER BY id ASC LIMIT 11");
     }
 
     #[test]
     fn filter_with_pager_should_generate_correct_sql() {
+        let signer = PageSigner::new("secret");
         let filter = ReservationFilterBuilder::default()
             .resource_id("router-1")
             .build()
             .unwrap();
         let mut data = generate_test_ids(1, 11);
-        let pager = filter.get_pager(&mut data);
+        let pager = filter.get_pager(&mut data, &signer);
         assert_eq!(pager.prev, None);
-        assert_eq!(pager.next, Some(10));
+        assert!(pager.next.is_some());
 
         let filter = filter.next_page(&pager).unwrap();
+        filter.verify_cursor(&signer).unwrap();
         let sql = filter.to_sql();
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservation WHERE status='pending'::rsvp.reservation_status AND id >= 10 AND resource_id = 'router-1' ORDER BY id ASC LIMIT 12"
+            "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND id > 10 AND resource_id = 'router-1' ORDER BY id ASC LIMIT 11"
         );
 
-        let mut data = generate_test_ids(10, 20);
-        let pager = filter.get_pager(&mut data);
-        assert_eq!(pager.prev, Some(11));
+        let mut data = generate_test_ids(11, 20);
+        let pager = filter.get_pager(&mut data, &signer);
+        assert!(pager.prev.is_some());
         assert_eq!(pager.next, None);
+
+        let filter = filter.prev_page(&pager).unwrap();
+        filter.verify_cursor(&signer).unwrap();
+        let sql = filter.to_sql();
+        assert_eq!(
+            sql,
+            "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND id < 11 AND resource_id = 'router-1' ORDER BY id DESC LIMIT 11"
+        );
+    }
+
+    #[test]
+    fn filter_sorted_by_time_should_generate_correct_sql() {
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("router-1")
+            .order_by(ReservationSortKey::Start as i32)
+            .build()
+            .unwrap();
+        assert_eq!(filter.to_sql(), "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND resource_id = 'router-1' ORDER BY lower(timespan) ASC, id ASC LIMIT 11");
+
+        // 2022-12-25T07:00:00Z
+        let key = 1_671_951_600_000_000;
+        let filter = ReservationFilter {
+            cursor: Some(sign(&filter, Cursor::forward(key, 10))),
+            ..filter
+        };
+        assert_eq!(filter.to_sql(), "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND (lower(timespan), id) > ('2022-12-25T07:00:00.000000Z'::timestamptz, 10) AND resource_id = 'router-1' ORDER BY lower(timespan) ASC, id ASC LIMIT 11");
+
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("router-1")
+            .order_by(ReservationSortKey::End as i32)
+            .desc(true)
+            .build()
+            .unwrap();
+        let filter = ReservationFilter {
+            cursor: Some(sign(&filter, Cursor::backward(key, 10))),
+            ..filter
+        };
+        assert_eq!(filter.to_sql(), "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND (upper(timespan), id) > ('2022-12-25T07:00:00.000000Z'::timestamptz, 10) AND resource_id = 'router-1' ORDER BY upper(timespan) ASC, id ASC LIMIT 11");
+    }
+
+    #[test]
+    fn filter_should_generate_correct_count_sql() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("luckychacha-id")
+            .include_total(true)
+            .build()
+            .unwrap();
+        let filter = ReservationFilter {
+            cursor: Some(sign(&filter, Cursor::forward(10, 10))),
+            ..filter
+        };
+
+        assert_eq!(filter.to_count_sql(), "SELECT count(*) FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND user_id = 'luckychacha-id'");
+        assert_eq!(filter.to_rows_sql(), "SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND user_id = 'luckychacha-id'");
+    }
+
+    #[test]
+    fn filter_ids_should_be_escaped() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("luckychacha-id")
+            .resource_id("x' OR 'a'='a")
+            .tenant_id("sales")
+            .build()
+            .unwrap();
+
+        assert_eq!(filter.to_count_sql(), "SELECT count(*) FROM rsvp.reservation WHERE tenant_id = 'sales' AND status='pending'::rsvp.reservation_status AND user_id = 'luckychacha-id' AND resource_id = 'x'' OR ''a''=''a'");
+        assert!(filter
+            .to_sql()
+            .contains(" AND resource_id = 'x'' OR ''a''=''a' ORDER BY id ASC"));
+    }
+
+    #[test]
+    fn filter_with_label_selector_should_generate_correct_sql() {
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("router-1")
+            .label_selector("team=infra,!temporary")
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter.to_sql(),
+            r#"SELECT * FROM rsvp.reservation WHERE tenant_id = '' AND status='pending'::rsvp.reservation_status AND resource_id = 'router-1' AND labels @> '{"team":"infra"}'::jsonb AND NOT labels ? 'temporary' ORDER BY id ASC LIMIT 11"#
+        );
+
+        let other = ReservationFilter {
+            label_selector: "team=infra".into(),
+            ..filter.clone()
+        };
+        assert_ne!(filter.fingerprint(), other.fingerprint());
+
+        let err = ReservationFilterBuilder::default()
+            .label_selector("team in infra")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidLabelSelector("team in infra".into()));
+    }
+
+    #[test]
+    fn cursor_reused_with_another_filter_should_be_rejected() {
+        let signer = PageSigner::new("secret");
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("router-1")
+            .build()
+            .unwrap();
+        let pager = filter.get_pager(&mut generate_test_ids(1, 11), &signer);
+        let cursor = pager.next.unwrap();
+
+        let sorted = ReservationFilter {
+            order_by: ReservationSortKey::Start as i32,
+            cursor: Some(cursor.clone()),
+            ..filter.clone()
+        };
+        assert!(sorted.verify_cursor(&signer).is_err());
+
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("router-2")
+            .cursor(cursor.clone())
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter.verify_cursor(&signer),
+            Err(Error::InvalidCursor(cursor))
+        );
+
+        let err = ReservationFilterBuilder::default()
+            .cursor("10")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidCursor("10".into()));
     }
 }
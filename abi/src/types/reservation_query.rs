@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use sqlx::postgres::types::PgRange;
 
-use crate::{ReservationQuery, Validator};
+use crate::{Error, LabelSelector, ReservationQuery, Validator};
 
 use super::{get_timespan, validate_range};
 
@@ -31,6 +31,10 @@ impl ReservationQuery {
     pub fn get_timepspan(&self) -> PgRange<DateTime<Utc>> {
         get_timespan(self.start.as_ref(), self.end.as_ref())
     }
+
+    pub fn get_label_selector(&self) -> Result<LabelSelector, Error> {
+        self.label_selector.parse()
+    }
 }
 
 impl Validator for ReservationQuery {
@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+
+use chrono::SecondsFormat;
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgRow, FromRow, Row};
+
+use crate::{
+    convert_to_utc_time,
+    pager::{Cursor, Id, PageInfo, PageSigner, PageToken, Paginator},
+    quote, Error, FilterPager, Reservation, ReservationSearch, ReservationSearchBuilder,
+    ReservationStatus, ToSql, Validator,
+};
+
+/// a reservation matching a search, with how well its note matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub reservation: Reservation,
+    pub rank: f32,
+}
+
+impl ReservationSearchBuilder {
+    pub fn build(&self) -> Result<ReservationSearch, Error> {
+        let search = self
+            .private_build()
+            .expect("failed to build ReservationSearch");
+        search.validate()?;
+        Ok(search)
+    }
+}
+
+impl Validator for ReservationSearch {
+    fn validate(&self) -> Result<(), Error> {
+        if self.text.trim().is_empty() {
+            return Err(Error::InvalidSearchText(self.text.clone()));
+        }
+        if self.page_size < 10 || self.page_size > 100 {
+            return Err(Error::InvalidPageSize(self.page_size));
+        }
+        if let Some(cursor) = self.cursor.as_ref() {
+            PageToken::parse(cursor)?;
+        }
+        if let (Some(start), Some(end)) = (self.start.as_ref(), self.end.as_ref()) {
+            if start.seconds >= end.seconds {
+                return Err(Error::InvalidTime);
+            }
+        }
+
+        ReservationStatus::from_i32(self.status).ok_or(Error::InvalidStatus(self.status))?;
+
+        Ok(())
+    }
+}
+
+impl ReservationSearch {
+    // best matches come first, so pages always go down the rank
+    fn page_info(&self) -> PageInfo {
+        PageInfo {
+            cursor: self
+                .cursor
+                .as_deref()
+                .and_then(|v| PageToken::parse(v).ok())
+                .map(|token| token.cursor),
+            page_size: self.page_size,
+            desc: true,
+        }
+    }
+
+    /// see `ReservationFilter::fingerprint`.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.text.as_str(),
+            self.resource_id.as_str(),
+            self.user_id.as_str(),
+            &self.status.to_string(),
+            &format!("{:?}", self.start),
+            &format!("{:?}", self.end),
+            self.tenant_id.as_str(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        let digest = hasher.finalize();
+        u64::from_be_bytes(digest[..8].try_into().unwrap())
+    }
+
+    /// make sure the cursor was signed by us for this very search.
+    pub fn verify_cursor(&self, signer: &PageSigner) -> Result<(), Error> {
+        if let Some(cursor) = self.cursor.as_ref() {
+            let token = signer.verify(cursor)?;
+            if token.fingerprint != self.fingerprint() {
+                return Err(Error::InvalidCursor(cursor.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_pager(&self, data: &mut VecDeque<SearchHit>, signer: &PageSigner) -> FilterPager {
+        // the rank goes into the cursor as is, so it can be compared exactly
+        let pager = self
+            .page_info()
+            .get_pager_by(data, |hit| hit.rank.to_bits() as i64);
+
+        let fingerprint = self.fingerprint();
+        let sign = |cursor: Option<Cursor>| {
+            cursor.map(|cursor| signer.sign(&PageToken::new(cursor, fingerprint)))
+        };
+
+        FilterPager {
+            prev: sign(pager.prev),
+            next: sign(pager.next),
+            total: None,
+            total_estimated: false,
+        }
+    }
+
+    pub fn prev_page(&self, pager: &FilterPager) -> Option<Self> {
+        self.with_cursor(pager.prev.as_ref())
+    }
+
+    pub fn next_page(&self, pager: &FilterPager) -> Option<Self> {
+        self.with_cursor(pager.next.as_ref())
+    }
+
+    fn with_cursor(&self, cursor: Option<&String>) -> Option<Self> {
+        cursor.map(|cursor| Self {
+            cursor: Some(cursor.clone()),
+            ..self.clone()
+        })
+    }
+
+    fn conditions(&self) -> String {
+        let mut sql = format!(" AND tenant_id = {}", quote(&self.tenant_id));
+        if self.status != ReservationStatus::Unknown as i32 {
+            sql.push_str(&format!(
+                " AND status = '{}'::rsvp.reservation_status",
+                ReservationStatus::from_i32(self.status).unwrap()
+            ));
+        }
+        if !self.resource_id.is_empty() {
+            sql.push_str(&format!(" AND resource_id = {}", quote(&self.resource_id)));
+        }
+        if !self.user_id.is_empty() {
+            sql.push_str(&format!(" AND user_id = {}", quote(&self.user_id)));
+        }
+        if self.start.is_some() || self.end.is_some() {
+            let bound = |ts: Option<&prost_types::Timestamp>| match ts {
+                Some(ts) => quote(
+                    &convert_to_utc_time(ts.clone()).to_rfc3339_opts(SecondsFormat::Micros, true),
+                ),
+                None => "NULL".to_string(),
+            };
+            sql.push_str(&format!(
+                " AND timespan && tstzrange({}, {}, '[)')",
+                bound(self.start.as_ref()),
+                bound(self.end.as_ref())
+            ));
+        }
+        sql
+    }
+}
+
+impl ToSql for ReservationSearch {
+    fn to_sql(&self) -> String {
+        let page = self.page_info();
+        let backward = page.is_backward();
+        let tsquery = format!("websearch_to_tsquery('english', {})", quote(&self.text));
+
+        let mut sql = format!(
+            "SELECT * FROM (SELECT *, ts_rank(note_tsv, {tsquery}) AS rank FROM rsvp.reservation WHERE note_tsv @@ {tsquery}{}) hits",
+            self.conditions()
+        );
+        if let Some(cursor) = page.cursor {
+            sql.push_str(&format!(
+                " WHERE (rank, id) {} ({:?}::real, {})",
+                if backward { ">" } else { "<" },
+                f32::from_bits(cursor.key as u32),
+                cursor.id
+            ));
+        }
+
+        let order = if backward { "ASC" } else { "DESC" };
+        sql.push_str(&format!(
+            " ORDER BY rank {order}, id {order} LIMIT {}",
+            self.page_size + 1
+        ));
+
+        sql
+    }
+}
+
+impl FromRow<'_, PgRow> for SearchHit {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            reservation: Reservation::from_row(row)?,
+            rank: row.try_get("rank")?,
+        })
+    }
+}
+
+impl Id for SearchHit {
+    fn id(&self) -> i64 {
+        self.reservation.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(id: i64, rank: f32) -> SearchHit {
+        SearchHit {
+            reservation: Reservation {
+                id,
+                ..Default::default()
+            },
+            rank,
+        }
+    }
+
+    #[test]
+    fn search_should_generate_correct_sql() {
+        let search = ReservationSearchBuilder::default()
+            .text("projector broken")
+            .resource_id("room-1")
+            .build()
+            .unwrap();
+        assert_eq!(search.to_sql(), "SELECT * FROM (SELECT *, ts_rank(note_tsv, websearch_to_tsquery('english', 'projector broken')) AS rank FROM rsvp.reservation WHERE note_tsv @@ websearch_to_tsquery('english', 'projector broken') AND tenant_id = '' AND resource_id = 'room-1') hits ORDER BY rank DESC, id DESC LIMIT 11");
+
+        let search = ReservationSearchBuilder::default()
+            .text("it's broken")
+            .user_id("alice")
+            .status(ReservationStatus::Confirmed as i32)
+            .start(
+                "2022-12-25T15:00:00+0800"
+                    .parse::<prost_types::Timestamp>()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(search.to_sql(), "SELECT * FROM (SELECT *, ts_rank(note_tsv, websearch_to_tsquery('english', 'it''s broken')) AS rank FROM rsvp.reservation WHERE note_tsv @@ websearch_to_tsquery('english', 'it''s broken') AND tenant_id = '' AND status = 'confirmed'::rsvp.reservation_status AND user_id = 'alice' AND timespan && tstzrange('2022-12-25T07:00:00.000000Z', NULL, '[)')) hits ORDER BY rank DESC, id DESC LIMIT 11");
+    }
+
+    #[test]
+    fn search_pages_should_follow_rank() {
+        let signer = PageSigner::new("secret");
+        let search = ReservationSearchBuilder::default()
+            .text("projector")
+            .build()
+            .unwrap();
+
+        let mut hits: VecDeque<_> = (0..11).map(|i| hit(100 - i, 0.5)).collect();
+        let pager = search.get_pager(&mut hits, &signer);
+        assert_eq!(hits.len(), 10);
+        assert!(pager.prev.is_none());
+
+        let search = search.next_page(&pager).unwrap();
+        search.verify_cursor(&signer).unwrap();
+        assert_eq!(search.to_sql(), "SELECT * FROM (SELECT *, ts_rank(note_tsv, websearch_to_tsquery('english', 'projector')) AS rank FROM rsvp.reservation WHERE note_tsv @@ websearch_to_tsquery('english', 'projector') AND tenant_id = '') hits WHERE (rank, id) < (0.5::real, 91) ORDER BY rank DESC, id DESC LIMIT 11");
+
+        let mut hits: VecDeque<_> = (0..5).map(|i| hit(90 - i, 0.25)).collect();
+        let pager = search.get_pager(&mut hits, &signer);
+        let search = search.prev_page(&pager).unwrap();
+        assert_eq!(search.to_sql(), "SELECT * FROM (SELECT *, ts_rank(note_tsv, websearch_to_tsquery('english', 'projector')) AS rank FROM rsvp.reservation WHERE note_tsv @@ websearch_to_tsquery('english', 'projector') AND tenant_id = '') hits WHERE (rank, id) > (0.25::real, 90) ORDER BY rank ASC, id ASC LIMIT 11");
+
+        let other = ReservationSearch {
+            text: "whiteboard".into(),
+            ..search
+        };
+        assert!(other.verify_cursor(&signer).is_err());
+    }
+
+    #[test]
+    fn empty_search_should_be_rejected() {
+        let err = ReservationSearchBuilder::default()
+            .text("  ")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidSearchText("  ".into()));
+    }
+}
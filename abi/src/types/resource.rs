@@ -0,0 +1,433 @@
+use chrono::{Duration, SecondsFormat};
+use serde_json::{Map, Number, Value};
+use sqlx::{
+    postgres::{types::PgInterval, PgRow},
+    types::Json,
+    FromRow, Row,
+};
+
+use crate::{
+    attribute_value, convert_interval_to_duration, convert_to_duration, convert_to_pb_duration,
+    convert_to_utc_time, quote, AttributeCondition, AttributeOp, AttributeValue, BookingPolicy,
+    BusinessHours, Error, ReserveAnyRequest, Resource, ToSql, Validator,
+};
+
+use super::label_selector::is_valid_label;
+
+impl Resource {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            attributes: Default::default(),
+            parent_id: String::new(),
+            policy: None,
+            business_hours: None,
+            pre_buffer: None,
+            post_buffer: None,
+            approvers: vec![],
+            requires_approval: false,
+            check_in_window: None,
+            tenant_id: String::new(),
+        }
+    }
+
+    /// confirmed reservations not checked in within the window after their start are
+    /// released as no-shows.
+    pub fn with_check_in_window(mut self, window: Duration) -> Self {
+        self.check_in_window = Some(convert_to_pb_duration(window));
+        self
+    }
+
+    pub fn check_in_window(&self) -> Option<Duration> {
+        self.check_in_window.as_ref().map(convert_to_duration)
+    }
+
+    /// pending reservations have to be approved by one of the approvers.
+    pub fn with_approvers(
+        mut self,
+        approvers: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.approvers = approvers.into_iter().map(|v| v.into()).collect();
+        self.requires_approval = true;
+        self
+    }
+
+    pub fn with_buffers(mut self, pre: Duration, post: Duration) -> Self {
+        self.pre_buffer = Some(convert_to_pb_duration(pre));
+        self.post_buffer = Some(convert_to_pb_duration(post));
+        self
+    }
+
+    pub fn pre_buffer(&self) -> Option<Duration> {
+        self.pre_buffer.as_ref().map(convert_to_duration)
+    }
+
+    pub fn post_buffer(&self) -> Option<Duration> {
+        self.post_buffer.as_ref().map(convert_to_duration)
+    }
+
+    pub fn with_business_hours(mut self, business_hours: BusinessHours) -> Self {
+        self.business_hours = Some(business_hours);
+        self
+    }
+
+    pub fn with_policy(mut self, policy: BookingPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn with_parent(mut self, parent_id: impl Into<String>) -> Self {
+        self.parent_id = parent_id.into();
+        self
+    }
+
+    pub fn with_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<AttributeValue>,
+    ) -> Self {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// attributes as stored in the database, a json object of plain json values.
+    pub fn attributes_json(&self) -> Value {
+        let attributes: Map<_, _> = self
+            .attributes
+            .iter()
+            .filter_map(|(k, v)| Some((k.clone(), v.to_json()?)))
+            .collect();
+        Value::Object(attributes)
+    }
+}
+
+impl Validator for Resource {
+    fn validate(&self) -> Result<(), Error> {
+        if self.id.is_empty() {
+            return Err(Error::InvalidResourceId(self.id.clone()));
+        }
+        if self.parent_id == self.id {
+            return Err(Error::InvalidParentResource(self.parent_id.clone()));
+        }
+        for (k, v) in self.attributes.iter() {
+            if !is_valid_label(k) || v.to_json().is_none() {
+                return Err(Error::InvalidAttribute(k.clone()));
+            }
+        }
+        if let Some(policy) = self.policy.as_ref() {
+            policy.validate()?;
+        }
+        if let Some(business_hours) = self.business_hours.as_ref() {
+            business_hours.validate()?;
+        }
+        let buffers = [self.pre_buffer(), self.post_buffer()];
+        if buffers.iter().flatten().any(|d| *d < Duration::zero()) {
+            return Err(Error::InvalidBuffer(
+                "buffers can't be negative".to_string(),
+            ));
+        }
+        if matches!(self.check_in_window(), Some(d) if d <= Duration::zero()) {
+            return Err(Error::InvalidCheckInWindow(
+                "check-in window has to be positive".to_string(),
+            ));
+        }
+        if let Some(approver) = self.approvers.iter().find(|v| v.is_empty() || v.len() > 64) {
+            return Err(Error::InvalidApprover(approver.clone()));
+        }
+        if self.requires_approval && self.approvers.is_empty() {
+            return Err(Error::InvalidApprover(String::new()));
+        }
+        Ok(())
+    }
+}
+
+impl FromRow<'_, PgRow> for Resource {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let attributes: Json<Map<String, Value>> = row.get("attributes");
+        let parent_id: Option<String> = row.get("parent_id");
+        let business_hours: Option<Json<BusinessHours>> = row.get("business_hours");
+        let buffer = |column: &str| -> Result<Option<prost_types::Duration>, sqlx::Error> {
+            let interval: Option<PgInterval> = row.try_get(column)?;
+            Ok(interval.map(|v| convert_to_pb_duration(convert_interval_to_duration(v))))
+        };
+        Ok(Self {
+            id: row.get("id"),
+            parent_id: parent_id.unwrap_or_default(),
+            policy: BookingPolicy::from_resource_row(row)?,
+            business_hours: business_hours.map(|v| v.0),
+            pre_buffer: buffer("pre_buffer")?,
+            post_buffer: buffer("post_buffer")?,
+            approvers: row.get("approvers"),
+            requires_approval: row.get("requires_approval"),
+            check_in_window: buffer("check_in_window")?,
+            tenant_id: row.get("tenant_id"),
+            attributes: attributes
+                .0
+                .into_iter()
+                .filter_map(|(k, v)| Some((k, AttributeValue::from_json(&v)?)))
+                .collect(),
+        })
+    }
+}
+
+impl AttributeValue {
+    /// None if the value isn't set or is a float json can't represent.
+    pub fn to_json(&self) -> Option<Value> {
+        match self.value.as_ref()? {
+            attribute_value::Value::StringValue(v) => Some(Value::String(v.clone())),
+            attribute_value::Value::IntValue(v) => Some(Value::Number((*v).into())),
+            attribute_value::Value::DoubleValue(v) => Number::from_f64(*v).map(Value::Number),
+            attribute_value::Value::BoolValue(v) => Some(Value::Bool(*v)),
+        }
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let value = match value {
+            Value::String(v) => attribute_value::Value::StringValue(v.clone()),
+            Value::Number(v) => match v.as_i64() {
+                Some(v) => attribute_value::Value::IntValue(v),
+                None => attribute_value::Value::DoubleValue(v.as_f64()?),
+            },
+            Value::Bool(v) => attribute_value::Value::BoolValue(*v),
+            _ => return None,
+        };
+        Some(Self { value: Some(value) })
+    }
+}
+
+macro_rules! impl_attribute_value_from {
+    ($ty: ty, $variant: ident) => {
+        impl From<$ty> for AttributeValue {
+            fn from(v: $ty) -> Self {
+                Self {
+                    value: Some(attribute_value::Value::$variant(v.into())),
+                }
+            }
+        }
+    };
+}
+impl_attribute_value_from!(&str, StringValue);
+impl_attribute_value_from!(String, StringValue);
+impl_attribute_value_from!(i64, IntValue);
+impl_attribute_value_from!(f64, DoubleValue);
+impl_attribute_value_from!(bool, BoolValue);
+
+impl AttributeCondition {
+    pub fn new(key: impl Into<String>, op: AttributeOp, value: impl Into<AttributeValue>) -> Self {
+        Self {
+            key: key.into(),
+            op: op as i32,
+            value: Some(value.into()),
+        }
+    }
+
+    pub fn exists(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            op: AttributeOp::Exists as i32,
+            value: None,
+        }
+    }
+
+    pub fn get_op(&self) -> AttributeOp {
+        AttributeOp::from_i32(self.op).unwrap()
+    }
+}
+
+impl Validator for AttributeCondition {
+    fn validate(&self) -> Result<(), Error> {
+        let invalid = || Error::InvalidAttribute(self.key.clone());
+        if !is_valid_label(&self.key) {
+            return Err(invalid());
+        }
+        let op = AttributeOp::from_i32(self.op).ok_or_else(invalid)?;
+        let value = self.value.as_ref().and_then(|v| v.to_json());
+        if op != AttributeOp::Exists && value.is_none() {
+            return Err(invalid());
+        }
+        Ok(())
+    }
+}
+
+/// sql condition on the `attributes` of a resource. Comparisons are jsonpath predicates,
+/// which are false for missing attributes and values of another type.
+impl ToSql for AttributeCondition {
+    fn to_sql(&self) -> String {
+        let op = match self.get_op() {
+            AttributeOp::Exists => return format!("attributes ? {}", quote(&self.key)),
+            AttributeOp::Eq => "==",
+            AttributeOp::Ne => "!=",
+            AttributeOp::Lt => "<",
+            AttributeOp::Le => "<=",
+            AttributeOp::Gt => ">",
+            AttributeOp::Ge => ">=",
+        };
+        // json strings and numbers are valid jsonpath literals as well
+        let value = self.value.as_ref().and_then(|v| v.to_json()).unwrap();
+        let path = format!("$.{} {op} {value}", Value::String(self.key.clone()));
+        format!("attributes @@ {}", quote(&path))
+    }
+}
+
+impl Validator for ReserveAnyRequest {
+    fn validate(&self) -> Result<(), Error> {
+        self.reservation
+            .as_ref()
+            .ok_or_else(|| Error::MissingField("reservation".into()))?
+            .validate_without_resource()?;
+        for condition in self.conditions.iter() {
+            condition.validate()?;
+        }
+        if !self.best_fit.is_empty() && !is_valid_label(&self.best_fit) {
+            return Err(Error::InvalidAttribute(self.best_fit.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// resources matching the conditions, in the order they should be tried. Resources already
+/// booked in the window, turnaround buffers included, come last with `busy` set.
+impl ToSql for ReserveAnyRequest {
+    fn to_sql(&self) -> String {
+        let rsvp = self.reservation.as_ref().unwrap();
+        let bound = |ts: &prost_types::Timestamp| {
+            quote(&convert_to_utc_time(ts.clone()).to_rfc3339_opts(SecondsFormat::Micros, true))
+        };
+        let timespan = format!(
+            "tstzrange({}, {}, '[)')",
+            bound(rsvp.start.as_ref().unwrap()),
+            bound(rsvp.end.as_ref().unwrap())
+        );
+
+        let conditions = if self.conditions.is_empty() {
+            "TRUE".to_string()
+        } else {
+            self.conditions
+                .iter()
+                .map(|c| c.to_sql())
+                .collect::<Vec<_>>()
+                .join(" AND ")
+        };
+
+        let fit = if self.best_fit.is_empty() {
+            String::new()
+        } else {
+            let key = quote(&self.best_fit);
+            format!(
+                "CASE WHEN jsonb_typeof(attributes->{key}) = 'number' THEN (attributes->>{key})::numeric END ASC NULLS LAST, "
+            )
+        };
+
+        format!(
+            "SELECT id, EXISTS (SELECT 1 FROM rsvp.reservation WHERE tenant_id = r.tenant_id AND resource_id = r.id AND rsvp.blocked_span(resource_id, timespan, tenant_id) && rsvp.blocked_span(r.id, {timespan}, r.tenant_id)) AS busy FROM rsvp.resource r WHERE r.tenant_id = {} AND {conditions} ORDER BY busy ASC, {fit}id ASC",
+            quote(&rsvp.tenant_id)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reservation;
+
+    #[test]
+    fn attribute_condition_should_generate_correct_sql() {
+        let condition = AttributeCondition::new("seats", AttributeOp::Ge, 10);
+        assert_eq!(condition.to_sql(), r#"attributes @@ '$."seats" >= 10'"#);
+
+        let condition = AttributeCondition::new("projector", AttributeOp::Eq, true);
+        assert_eq!(
+            condition.to_sql(),
+            r#"attributes @@ '$."projector" == true'"#
+        );
+
+        let condition = AttributeCondition::new("building", AttributeOp::Ne, "ocean's");
+        assert_eq!(
+            condition.to_sql(),
+            r#"attributes @@ '$."building" != "ocean''s"'"#
+        );
+
+        assert_eq!(
+            AttributeCondition::exists("whiteboard").to_sql(),
+            "attributes ? 'whiteboard'"
+        );
+    }
+
+    #[test]
+    fn invalid_attribute_condition_should_be_rejected() {
+        let condition = AttributeCondition {
+            key: "seats".into(),
+            op: AttributeOp::Ge as i32,
+            value: None,
+        };
+        assert_eq!(
+            condition.validate(),
+            Err(Error::InvalidAttribute("seats".into()))
+        );
+
+        let condition = AttributeCondition::new("seats", AttributeOp::Ge, f64::NAN);
+        assert!(condition.validate().is_err());
+
+        let condition = AttributeCondition::new("seats'", AttributeOp::Ge, 10);
+        assert!(condition.validate().is_err());
+    }
+
+    #[test]
+    fn attribute_values_should_round_trip_through_json() {
+        let resource = Resource::new("room-1")
+            .with_attribute("seats", 10)
+            .with_attribute("area", 25.5)
+            .with_attribute("projector", true)
+            .with_attribute("building", "ocean");
+        let json = resource.attributes_json();
+        assert_eq!(
+            json,
+            serde_json::json!({"seats": 10, "area": 25.5, "projector": true, "building": "ocean"})
+        );
+
+        for (k, v) in json.as_object().unwrap() {
+            assert_eq!(
+                AttributeValue::from_json(v).as_ref(),
+                resource.attributes.get(k)
+            );
+        }
+    }
+
+    #[test]
+    fn reserve_any_should_generate_correct_sql() {
+        let request = ReserveAnyRequest {
+            reservation: Some(Reservation::new_pending(
+                "alice",
+                "",
+                "2022-12-25T15:00:00+0800".parse().unwrap(),
+                "2022-12-28T11:00:00+0800".parse().unwrap(),
+                "",
+            )),
+            conditions: vec![
+                AttributeCondition::new("seats", AttributeOp::Ge, 10),
+                AttributeCondition::new("projector", AttributeOp::Eq, true),
+            ],
+            best_fit: "seats".into(),
+        };
+        request.validate().unwrap();
+        assert_eq!(
+            request.to_sql(),
+            r#"SELECT id, EXISTS (SELECT 1 FROM rsvp.reservation WHERE tenant_id = r.tenant_id AND resource_id = r.id AND rsvp.blocked_span(resource_id, timespan, tenant_id) && rsvp.blocked_span(r.id, tstzrange('2022-12-25T07:00:00.000000Z', '2022-12-28T03:00:00.000000Z', '[)'), r.tenant_id)) AS busy FROM rsvp.resource r WHERE r.tenant_id = '' AND attributes @@ '$."seats" >= 10' AND attributes @@ '$."projector" == true' ORDER BY busy ASC, CASE WHEN jsonb_typeof(attributes->'seats') = 'number' THEN (attributes->>'seats')::numeric END ASC NULLS LAST, id ASC"#
+        );
+
+        let request = ReserveAnyRequest {
+            conditions: vec![],
+            best_fit: "".into(),
+            ..request
+        };
+        assert_eq!(request.to_sql(), "SELECT id, EXISTS (SELECT 1 FROM rsvp.reservation WHERE tenant_id = r.tenant_id AND resource_id = r.id AND rsvp.blocked_span(resource_id, timespan, tenant_id) && rsvp.blocked_span(r.id, tstzrange('2022-12-25T07:00:00.000000Z', '2022-12-28T03:00:00.000000Z', '[)'), r.tenant_id)) AS busy FROM rsvp.resource r WHERE r.tenant_id = '' AND TRUE ORDER BY busy ASC, id ASC");
+        let request = ReserveAnyRequest {
+            reservation: None,
+            ..request
+        };
+        assert_eq!(
+            request.validate(),
+            Err(Error::MissingField("reservation".into()))
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{
+    postgres::{types::PgRange, PgRow},
+    types::Json,
+    FromRow, Row,
+};
+
+use crate::{convert_to_timestamp, Reservation, ReservationStatus, WaitlistEntry};
+
+use super::reservation::NaiveRange;
+
+impl WaitlistEntry {
+    pub fn get_reservation(&self) -> Reservation {
+        self.reservation.clone().unwrap_or_default()
+    }
+}
+
+/// a waiting entry, its reservation is pending and doesn't have an id yet.
+impl FromRow<'_, PgRow> for WaitlistEntry {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let range: PgRange<DateTime<Utc>> = row.get("timespan");
+        let range: NaiveRange<DateTime<Utc>> = range.into();
+        let labels: Json<HashMap<String, String>> = row.get("labels");
+        let created_at: DateTime<Utc> = row.get("created_at");
+
+        Ok(Self {
+            id: row.get("id"),
+            reservation: Some(Reservation {
+                id: 0,
+                user_id: row.get("user_id"),
+                status: ReservationStatus::Pending as i32,
+                resource_id: row.get("resource_id"),
+                start: range.start.map(convert_to_timestamp),
+                end: range.end.map(convert_to_timestamp),
+                note: row.get("note"),
+                labels: labels.0,
+                priority: row.get("priority"),
+                checked_in_at: None,
+                tenant_id: row.get("tenant_id"),
+            }),
+            created_at: Some(convert_to_timestamp(created_at)),
+        })
+    }
+}
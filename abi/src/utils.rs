@@ -1,5 +1,6 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use prost_types::Timestamp;
+use sqlx::postgres::types::PgInterval;
 
 pub fn convert_to_utc_time(ts: Timestamp) -> DateTime<Utc> {
     DateTime::<Utc>::from_utc(
@@ -14,3 +15,49 @@ pub fn convert_to_timestamp(dt: DateTime<Utc>) -> Timestamp {
         nanos: dt.timestamp_subsec_nanos() as _,
     }
 }
+
+/// microseconds since the epoch, the precision postgres keeps for timestamps.
+pub fn convert_to_micros(ts: &Timestamp) -> i64 {
+    ts.seconds * 1_000_000 + ts.nanos as i64 / 1_000
+}
+
+pub fn convert_micros_to_utc_time(micros: i64) -> DateTime<Utc> {
+    convert_to_utc_time(Timestamp {
+        seconds: micros.div_euclid(1_000_000),
+        nanos: (micros.rem_euclid(1_000_000) * 1_000) as _,
+    })
+}
+
+pub fn convert_to_duration(d: &prost_types::Duration) -> Duration {
+    Duration::seconds(d.seconds) + Duration::nanoseconds(d.nanos as _)
+}
+
+pub fn convert_to_pb_duration(d: Duration) -> prost_types::Duration {
+    let seconds = d.num_seconds();
+    prost_types::Duration {
+        seconds,
+        nanos: (d - Duration::seconds(seconds))
+            .num_nanoseconds()
+            .unwrap_or(0) as _,
+    }
+}
+
+/// intervals we store are always made of microseconds only.
+pub fn convert_to_interval(d: Duration) -> PgInterval {
+    PgInterval {
+        months: 0,
+        days: 0,
+        microseconds: d.num_microseconds().unwrap_or(i64::MAX),
+    }
+}
+
+/// intervals written by hand may use days and months, a month is taken as 30 days.
+pub fn convert_interval_to_duration(interval: PgInterval) -> Duration {
+    Duration::days(interval.months as i64 * 30 + interval.days as i64)
+        + Duration::microseconds(interval.microseconds)
+}
+
+/// quote a string as a sql literal, as postgres' quote_literal does.
+pub(crate) fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
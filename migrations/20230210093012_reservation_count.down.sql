@@ -0,0 +1 @@
+DROP FUNCTION IF EXISTS rsvp.count_estimate;
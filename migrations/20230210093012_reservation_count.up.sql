@@ -0,0 +1,10 @@
+-- estimated number of rows a query returns, read from the planner statistics
+-- instead of scanning the table.
+CREATE OR REPLACE FUNCTION rsvp.count_estimate(query text) RETURNS bigint AS $$
+DECLARE
+    _plan json;
+BEGIN
+    EXECUTE 'EXPLAIN (FORMAT JSON) ' || query INTO _plan;
+    RETURN (_plan->0->'Plan'->>'Plan Rows')::bigint;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS rsvp.reservations_start_id_idx;
+DROP INDEX IF EXISTS rsvp.reservations_end_id_idx;
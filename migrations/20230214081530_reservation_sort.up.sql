@@ -0,0 +1,3 @@
+-- keyset pagination when filtering reservations by start or end time
+CREATE INDEX reservations_start_id_idx ON rsvp.reservation (lower(timespan), id);
+CREATE INDEX reservations_end_id_idx ON rsvp.reservation (upper(timespan), id);
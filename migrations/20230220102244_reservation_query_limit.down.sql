@@ -0,0 +1,37 @@
+DROP FUNCTION IF EXISTS rsvp.query(text, text, timestamp with time zone, timestamp with time zone, rsvp.reservation_status, bool, bigint);
+
+CREATE OR REPLACE FUNCTION rsvp.query(
+    uid text,
+    rid text,
+    _start timestamp with time zone,
+    _end timestamp with time zone,
+    status rsvp.reservation_status DEFAULT 'pending',
+    is_desc bool DEFAULT FALSE
+) RETURNS TABLE (LIKE rsvp.reservation) AS $$
+DECLARE
+    _during tstzrange;
+    _sql text;
+BEGIN
+    _during := tstzrange(
+        COALESCE(_start, '-infinity'),
+        COALESCE(_end, 'infinity'),
+        '[)'
+    );
+    _sql := format(
+        'SELECT * FROM rsvp.reservation WHERE %L @> timespan AND status = %L AND %s ORDER BY lower(timespan) %s',
+        _during,
+        status,
+        CASE
+            WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+            WHEN uid IS NULL THEN 'resource_id = ' || quote_literal(rid)
+            WHEN rid IS NULL THEN 'user_id = ' || quote_literal(uid)
+            ELSE 'user_id = ' || quote_literal(uid) || ' AND resource_id = ' || quote_literal(rid)
+        END,
+        CASE
+            WHEN is_desc THEN 'DESC'
+            ELSE 'ASC'
+        END
+    );
+    RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS rsvp.reservations_note_tsv_idx;
+ALTER TABLE rsvp.reservation DROP COLUMN IF EXISTS note_tsv;
@@ -0,0 +1,5 @@
+-- full-text search over reservation notes
+ALTER TABLE rsvp.reservation
+    ADD COLUMN note_tsv tsvector GENERATED ALWAYS AS (to_tsvector('english', COALESCE(note, ''))) STORED;
+
+CREATE INDEX reservations_note_tsv_idx ON rsvp.reservation USING gin (note_tsv);
@@ -0,0 +1,50 @@
+DROP FUNCTION IF EXISTS rsvp.query(text, text, timestamp with time zone, timestamp with time zone, rsvp.reservation_status, bool, bigint, text);
+
+DROP INDEX IF EXISTS rsvp.reservations_labels_idx;
+ALTER TABLE rsvp.reservation DROP COLUMN IF EXISTS labels;
+
+-- back to rsvp.query with a limit only
+CREATE OR REPLACE FUNCTION rsvp.query(
+    uid text,
+    rid text,
+    _start timestamp with time zone,
+    _end timestamp with time zone,
+    status rsvp.reservation_status DEFAULT 'pending',
+    is_desc bool DEFAULT FALSE,
+    _limit bigint DEFAULT NULL
+) RETURNS TABLE (LIKE rsvp.reservation) AS $$
+DECLARE
+    _during tstzrange;
+    _sql text;
+BEGIN
+    -- if start or end is null, use infinity
+    _during := tstzrange(
+        COALESCE(_start, '-infinity'),
+        COALESCE(_end, 'infinity'),
+        '[)'
+    );
+    -- format the query based on parameters
+    _sql := format(
+        'SELECT * FROM rsvp.reservation WHERE %L @> timespan AND status = %L AND %s ORDER BY lower(timespan) %s LIMIT %s',
+        _during,
+        status,
+        CASE
+            WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+            WHEN uid IS NULL THEN 'resource_id = ' || quote_literal(rid)
+            WHEN rid IS NULL THEN 'user_id = ' || quote_literal(uid)
+            ELSE 'user_id = ' || quote_literal(uid) || ' AND resource_id = ' || quote_literal(rid)
+        END,
+        CASE
+            WHEN is_desc THEN 'DESC'
+            ELSE 'ASC'
+        END,
+        CASE
+            WHEN _limit IS NULL OR _limit <= 0 THEN 'ALL'
+            ELSE _limit::text
+        END
+    );
+
+    -- execute
+    RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,9 @@
+-- bookable resources, described by typed attributes so they can be booked by requirement
+CREATE TABLE rsvp.resource (
+    id VARCHAR(64) NOT NULL,
+    attributes jsonb NOT NULL DEFAULT '{}',
+
+    CONSTRAINT resource_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX resources_attributes_idx ON rsvp.resource USING gin (attributes);
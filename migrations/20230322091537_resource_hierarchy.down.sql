@@ -0,0 +1,8 @@
+DROP FUNCTION IF EXISTS rsvp.resource_root(text);
+DROP FUNCTION IF EXISTS rsvp.resource_descendants(text);
+DROP FUNCTION IF EXISTS rsvp.resource_ancestors(text);
+
+DROP INDEX IF EXISTS rsvp.resources_parent_id_idx;
+ALTER TABLE rsvp.resource
+    DROP CONSTRAINT IF EXISTS resource_parent_not_self,
+    DROP COLUMN IF EXISTS parent_id;
@@ -0,0 +1,37 @@
+-- a resource may be part of a larger one, e.g. partitions of a conference hall. Booking a
+-- resource conflicts with bookings of its ancestors and descendants in the same window.
+ALTER TABLE rsvp.resource
+    ADD COLUMN parent_id VARCHAR(64) REFERENCES rsvp.resource(id) ON DELETE SET NULL,
+    ADD CONSTRAINT resource_parent_not_self CHECK (parent_id <> id);
+
+CREATE INDEX resources_parent_id_idx ON rsvp.resource (parent_id);
+
+-- the resource itself and all resources it is part of
+CREATE OR REPLACE FUNCTION rsvp.resource_ancestors(rid text) RETURNS SETOF varchar AS $$
+    WITH RECURSIVE ancestors(id, parent_id) AS (
+        SELECT rid::varchar, (SELECT parent_id FROM rsvp.resource WHERE id = rid)
+        UNION
+        SELECT r.id, r.parent_id FROM rsvp.resource r JOIN ancestors a ON r.id = a.parent_id
+    )
+    SELECT id FROM ancestors;
+$$ LANGUAGE sql STABLE;
+
+-- the resource itself and all resources which are part of it
+CREATE OR REPLACE FUNCTION rsvp.resource_descendants(rid text) RETURNS SETOF varchar AS $$
+    WITH RECURSIVE descendants(id) AS (
+        SELECT rid::varchar
+        UNION
+        SELECT r.id FROM rsvp.resource r JOIN descendants d ON r.parent_id = d.id
+    )
+    SELECT id FROM descendants;
+$$ LANGUAGE sql STABLE;
+
+-- the top most resource the resource is part of, or the resource itself
+CREATE OR REPLACE FUNCTION rsvp.resource_root(rid text) RETURNS varchar AS $$
+    WITH RECURSIVE ancestors(id, parent_id, depth) AS (
+        SELECT rid::varchar, (SELECT parent_id FROM rsvp.resource WHERE id = rid), 0
+        UNION ALL
+        SELECT r.id, r.parent_id, a.depth + 1 FROM rsvp.resource r JOIN ancestors a ON r.id = a.parent_id
+    )
+    SELECT id FROM ancestors ORDER BY depth DESC LIMIT 1;
+$$ LANGUAGE sql STABLE;
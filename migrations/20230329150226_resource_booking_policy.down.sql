@@ -0,0 +1,6 @@
+ALTER TABLE rsvp.resource
+    DROP COLUMN IF EXISTS min_duration,
+    DROP COLUMN IF EXISTS max_duration,
+    DROP COLUMN IF EXISTS slot_minutes,
+    DROP COLUMN IF EXISTS min_lead_time,
+    DROP COLUMN IF EXISTS max_horizon;
@@ -0,0 +1,7 @@
+-- per-resource booking rules, a NULL interval means the rule doesn't apply
+ALTER TABLE rsvp.resource
+    ADD COLUMN min_duration interval,
+    ADD COLUMN max_duration interval,
+    ADD COLUMN slot_minutes integer NOT NULL DEFAULT 0,
+    ADD COLUMN min_lead_time interval,
+    ADD COLUMN max_horizon interval;
@@ -0,0 +1 @@
+ALTER TABLE rsvp.resource DROP COLUMN IF EXISTS business_hours;
@@ -0,0 +1,2 @@
+-- opening hours and holidays of a resource, NULL if it can be booked at any time
+ALTER TABLE rsvp.resource ADD COLUMN business_hours jsonb;
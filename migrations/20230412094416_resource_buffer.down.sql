@@ -0,0 +1,5 @@
+DROP FUNCTION IF EXISTS rsvp.blocked_span(text, tstzrange);
+
+ALTER TABLE rsvp.resource
+    DROP COLUMN IF EXISTS pre_buffer,
+    DROP COLUMN IF EXISTS post_buffer;
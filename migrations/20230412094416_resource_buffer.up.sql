@@ -0,0 +1,13 @@
+-- turnaround time around every booking of a resource, NULL if there is none
+ALTER TABLE rsvp.resource
+    ADD COLUMN pre_buffer interval,
+    ADD COLUMN post_buffer interval;
+
+-- the window a booking keeps its resource busy, the booked timespan plus buffers
+CREATE OR REPLACE FUNCTION rsvp.blocked_span(rid text, span tstzrange) RETURNS tstzrange AS $$
+    SELECT tstzrange(
+        lower(span) - COALESCE((SELECT pre_buffer FROM rsvp.resource WHERE id = rid), '0'),
+        upper(span) + COALESCE((SELECT post_buffer FROM rsvp.resource WHERE id = rid), '0'),
+        '[)'
+    );
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,11 @@
+-- fair-use limits every user's reservations are checked against
+CREATE TABLE rsvp.quota (
+    id VARCHAR(64) NOT NULL,
+    kind VARCHAR(64) NOT NULL,
+    quota_limit BIGINT NOT NULL,
+    period VARCHAR(64) NOT NULL,
+    -- attribute conditions on the resources the quota applies to, all of them if empty
+    conditions JSONB NOT NULL DEFAULT '[]',
+
+    CONSTRAINT quota_pkey PRIMARY KEY (id)
+);
@@ -0,0 +1,15 @@
+-- reservations waiting for their window to become free, first come first served
+CREATE TABLE rsvp.waitlist (
+    id BIGSERIAL NOT NULL,
+    user_id VARCHAR(64) NOT NULL,
+    resource_id VARCHAR(64) NOT NULL,
+    timespan TSTZRANGE NOT NULL,
+    note TEXT,
+    labels JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT waitlist_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX waitlist_resource_id_idx ON rsvp.waitlist (resource_id);
+CREATE INDEX waitlist_user_id_idx ON rsvp.waitlist (user_id);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS rsvp.preemption;
+
+ALTER TABLE rsvp.waitlist DROP COLUMN IF EXISTS priority;
+ALTER TABLE rsvp.reservation DROP COLUMN IF EXISTS priority;
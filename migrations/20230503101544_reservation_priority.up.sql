@@ -0,0 +1,23 @@
+-- reservations of a higher priority may preempt overlapping ones of a lower priority
+ALTER TABLE rsvp.reservation ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE rsvp.waitlist ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+-- reservations cancelled to make room for a more important one, kept so the users affected
+-- know why their reservation is gone
+CREATE TABLE rsvp.preemption (
+    reservation_id BIGINT NOT NULL,
+    user_id VARCHAR(64) NOT NULL,
+    status rsvp.reservation_status NOT NULL,
+    resource_id VARCHAR(64) NOT NULL,
+    timespan TSTZRANGE NOT NULL,
+    note TEXT,
+    labels JSONB NOT NULL DEFAULT '{}',
+    priority INTEGER NOT NULL,
+    preempted_by BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT preemption_pkey PRIMARY KEY (reservation_id)
+);
+
+CREATE INDEX preemptions_user_id_idx ON rsvp.preemption (user_id);
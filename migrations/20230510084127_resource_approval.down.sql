@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS rsvp.approval;
+DROP TYPE IF EXISTS rsvp.approval_decision;
+
+ALTER TABLE rsvp.resource
+    DROP COLUMN IF EXISTS approvers,
+    DROP COLUMN IF EXISTS requires_approval;
@@ -0,0 +1,23 @@
+-- reservations of some resources have to be signed off by an owner of the resource
+ALTER TABLE rsvp.resource
+    ADD COLUMN approvers VARCHAR(64)[] NOT NULL DEFAULT '{}',
+    ADD COLUMN requires_approval BOOLEAN NOT NULL DEFAULT false;
+
+CREATE TYPE rsvp.approval_decision AS ENUM('approved', 'rejected');
+
+-- decisions of approvers, with their reasons
+CREATE TABLE rsvp.approval (
+    id BIGSERIAL NOT NULL,
+    reservation_id BIGINT NOT NULL,
+    user_id VARCHAR(64) NOT NULL,
+    resource_id VARCHAR(64) NOT NULL,
+    timespan TSTZRANGE NOT NULL,
+    approver VARCHAR(64) NOT NULL,
+    decision rsvp.approval_decision NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT approval_pkey PRIMARY KEY (id)
+);
+
+CREATE INDEX approvals_reservation_id_idx ON rsvp.approval (reservation_id);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS rsvp.cancellation;
+
+ALTER TABLE rsvp.reservation DROP COLUMN IF EXISTS checked_in_at;
+ALTER TABLE rsvp.resource DROP COLUMN IF EXISTS check_in_window;
@@ -0,0 +1,17 @@
+-- confirmed reservations not checked in within the window of their resource are released
+ALTER TABLE rsvp.resource ADD COLUMN check_in_window INTERVAL;
+ALTER TABLE rsvp.reservation ADD COLUMN checked_in_at TIMESTAMPTZ;
+
+-- reservations cancelled by the system rather than their user, e.g. no-shows
+CREATE TABLE rsvp.cancellation (
+    reservation_id BIGINT NOT NULL,
+    user_id VARCHAR(64) NOT NULL,
+    resource_id VARCHAR(64) NOT NULL,
+    timespan TSTZRANGE NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT cancellation_pkey PRIMARY KEY (reservation_id)
+);
+
+CREATE INDEX cancellations_user_id_reason_idx ON rsvp.cancellation (user_id, reason);
@@ -0,0 +1,177 @@
+-- only works while everything belongs to the default tenant
+DROP INDEX IF EXISTS rsvp.reservation_changes_tenant_id_idx;
+
+CREATE OR REPLACE FUNCTION rsvp.reservation_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO rsvp.reservation_change (reservation_id, op) VALUES (NEW.id, 'create');
+    ELSIF TG_OP = 'UPDATE' THEN
+        IF OLD.status <> NEW.status THEN
+            INSERT INTO rsvp.reservation_change (reservation_id, op) VALUES (NEW.id, 'update');
+        END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO rsvp.reservation_change (reservation_id, op) VALUES (OLD.id, 'delete');
+    END IF;
+    NOTIFY reservaption_update;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS rsvp.filter(text, text, rsvp.reservation_status, bigint, bool, bigint, text);
+
+CREATE OR REPLACE FUNCTION rsvp.filter(
+    uid text,
+    rid text,
+    status rsvp.reservation_status,
+    cursor bigint DEFAULT NULL,
+    is_desc bool DEFAULT FALSE,
+    page_size bigint DEFAULT 10
+) RETURNS TABLE (LIKE rsvp.reservation) AS $$
+DECLARE
+    _sql text;
+BEGIN
+    IF page_size < 10 OR page_size > 100 THEN
+        page_size := 10;
+    END IF;
+    IF cursor IS NULL OR cursor < 0 THEN
+        IF is_desc THEN
+            cursor := 9223372036854775807;
+        ELSE
+            cursor := 0;
+        END IF;
+    END IF;
+    _sql := format(
+        'SELECT * FROM rsvp.reservation WHERE %s AND status = %L AND %s ORDER BY id %s LIMIT %L::integer',
+        CASE
+            WHEN is_desc THEN 'id <= ' || cursor
+            ELSE 'id >= ' || cursor
+        END,
+        status,
+        CASE
+            WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+            WHEN uid IS NULL THEN 'resource_id = ' || quote_literal(rid)
+            WHEN rid IS NULL THEN 'user_id = ' || quote_literal(uid)
+            ELSE 'user_id = ' || quote_literal(uid) || ' AND resource_id = ' || quote_literal(rid)
+        END,
+        CASE
+            WHEN is_desc THEN 'DESC'
+            ELSE 'ASC'
+        END,
+        page_size + 1
+    );
+    RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS rsvp.query(text, text, timestamp with time zone, timestamp with time zone, rsvp.reservation_status, bool, bigint, text, text);
+
+CREATE OR REPLACE FUNCTION rsvp.query(
+    uid text,
+    rid text,
+    _start timestamp with time zone,
+    _end timestamp with time zone,
+    status rsvp.reservation_status DEFAULT 'pending',
+    is_desc bool DEFAULT FALSE,
+    _limit bigint DEFAULT NULL,
+    labels_cond text DEFAULT NULL
+) RETURNS TABLE (LIKE rsvp.reservation) AS $$
+DECLARE
+    _during tstzrange;
+    _sql text;
+BEGIN
+    _during := tstzrange(
+        COALESCE(_start, '-infinity'),
+        COALESCE(_end, 'infinity'),
+        '[)'
+    );
+    _sql := format(
+        'SELECT * FROM rsvp.reservation WHERE %L @> timespan AND status = %L AND %s AND %s ORDER BY lower(timespan) %s LIMIT %s',
+        _during,
+        status,
+        CASE
+            WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+            WHEN uid IS NULL THEN 'resource_id = ' || quote_literal(rid)
+            WHEN rid IS NULL THEN 'user_id = ' || quote_literal(uid)
+            ELSE 'user_id = ' || quote_literal(uid) || ' AND resource_id = ' || quote_literal(rid)
+        END,
+        COALESCE(labels_cond, 'TRUE'),
+        CASE
+            WHEN is_desc THEN 'DESC'
+            ELSE 'ASC'
+        END,
+        CASE
+            WHEN _limit IS NULL OR _limit <= 0 THEN 'ALL'
+            ELSE _limit::text
+        END
+    );
+    RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS rsvp.blocked_span(text, tstzrange, text);
+DROP FUNCTION IF EXISTS rsvp.resource_root(text, text);
+DROP FUNCTION IF EXISTS rsvp.resource_descendants(text, text);
+DROP FUNCTION IF EXISTS rsvp.resource_ancestors(text, text);
+
+CREATE OR REPLACE FUNCTION rsvp.resource_ancestors(rid text) RETURNS SETOF varchar AS $$
+    WITH RECURSIVE ancestors(id, parent_id) AS (
+        SELECT rid::varchar, (SELECT parent_id FROM rsvp.resource WHERE id = rid)
+        UNION
+        SELECT r.id, r.parent_id FROM rsvp.resource r JOIN ancestors a ON r.id = a.parent_id
+    )
+    SELECT id FROM ancestors;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION rsvp.resource_descendants(rid text) RETURNS SETOF varchar AS $$
+    WITH RECURSIVE descendants(id) AS (
+        SELECT rid::varchar
+        UNION
+        SELECT r.id FROM rsvp.resource r JOIN descendants d ON r.parent_id = d.id
+    )
+    SELECT id FROM descendants;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION rsvp.resource_root(rid text) RETURNS varchar AS $$
+    WITH RECURSIVE ancestors(id, parent_id, depth) AS (
+        SELECT rid::varchar, (SELECT parent_id FROM rsvp.resource WHERE id = rid), 0
+        UNION ALL
+        SELECT r.id, r.parent_id, a.depth + 1 FROM rsvp.resource r JOIN ancestors a ON r.id = a.parent_id
+    )
+    SELECT id FROM ancestors ORDER BY depth DESC LIMIT 1;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION rsvp.blocked_span(rid text, span tstzrange) RETURNS tstzrange AS $$
+    SELECT tstzrange(
+        lower(span) - COALESCE((SELECT pre_buffer FROM rsvp.resource WHERE id = rid), '0'),
+        upper(span) + COALESCE((SELECT post_buffer FROM rsvp.resource WHERE id = rid), '0'),
+        '[)'
+    );
+$$ LANGUAGE sql STABLE;
+
+DROP INDEX IF EXISTS rsvp.waitlist_tenant_id_idx;
+DROP INDEX IF EXISTS rsvp.reservations_tenant_id_idx;
+ALTER TABLE rsvp.reservation
+    DROP CONSTRAINT reservation_conflict,
+    ADD CONSTRAINT reservation_conflict EXCLUDE USING gist (resource_id WITH =, timespan WITH &&);
+
+ALTER TABLE rsvp.quota
+    DROP CONSTRAINT quota_pkey,
+    ADD CONSTRAINT quota_pkey PRIMARY KEY (id);
+
+DROP INDEX IF EXISTS rsvp.resources_parent_id_idx;
+ALTER TABLE rsvp.resource
+    DROP CONSTRAINT resource_parent_id_fkey,
+    DROP CONSTRAINT resource_pkey,
+    ADD CONSTRAINT resource_pkey PRIMARY KEY (id),
+    ADD CONSTRAINT resource_parent_id_fkey FOREIGN KEY (parent_id)
+        REFERENCES rsvp.resource(id) ON DELETE SET NULL;
+CREATE INDEX resources_parent_id_idx ON rsvp.resource (parent_id);
+
+ALTER TABLE rsvp.reservation_change DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE rsvp.cancellation DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE rsvp.approval DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE rsvp.preemption DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE rsvp.quota DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE rsvp.waitlist DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE rsvp.reservation DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE rsvp.resource DROP COLUMN IF EXISTS tenant_id;
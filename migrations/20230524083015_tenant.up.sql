@@ -0,0 +1,203 @@
+-- business units sharing a deployment, each only sees its own resources and reservations.
+-- Everything there is so far belongs to the default tenant ''.
+ALTER TABLE rsvp.resource ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.reservation ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.waitlist ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.quota ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.preemption ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.approval ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.cancellation ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.reservation_change ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+
+-- resource ids are only unique within a tenant, and so are parents
+ALTER TABLE rsvp.resource
+    DROP CONSTRAINT resource_parent_id_fkey,
+    DROP CONSTRAINT resource_pkey,
+    ADD CONSTRAINT resource_pkey PRIMARY KEY (tenant_id, id),
+    ADD CONSTRAINT resource_parent_id_fkey FOREIGN KEY (tenant_id, parent_id)
+        REFERENCES rsvp.resource(tenant_id, id) ON DELETE SET NULL (parent_id);
+DROP INDEX rsvp.resources_parent_id_idx;
+CREATE INDEX resources_parent_id_idx ON rsvp.resource (tenant_id, parent_id);
+
+ALTER TABLE rsvp.quota
+    DROP CONSTRAINT quota_pkey,
+    ADD CONSTRAINT quota_pkey PRIMARY KEY (tenant_id, id);
+
+-- the same resource id booked by two tenants is two different resources
+ALTER TABLE rsvp.reservation
+    DROP CONSTRAINT reservation_conflict,
+    ADD CONSTRAINT reservation_conflict EXCLUDE USING gist (
+        tenant_id WITH =, resource_id WITH =, timespan WITH &&
+    );
+CREATE INDEX reservations_tenant_id_idx ON rsvp.reservation (tenant_id);
+CREATE INDEX waitlist_tenant_id_idx ON rsvp.waitlist (tenant_id);
+
+-- same as the previous functions, within the tenant `tid`
+DROP FUNCTION rsvp.resource_ancestors(text);
+DROP FUNCTION rsvp.resource_descendants(text);
+DROP FUNCTION rsvp.resource_root(text);
+DROP FUNCTION rsvp.blocked_span(text, tstzrange);
+
+CREATE OR REPLACE FUNCTION rsvp.resource_ancestors(rid text, tid text DEFAULT '') RETURNS SETOF varchar AS $$
+    WITH RECURSIVE ancestors(id, parent_id) AS (
+        SELECT rid::varchar, (SELECT parent_id FROM rsvp.resource WHERE tenant_id = tid AND id = rid)
+        UNION
+        SELECT r.id, r.parent_id FROM rsvp.resource r
+        JOIN ancestors a ON r.tenant_id = tid AND r.id = a.parent_id
+    )
+    SELECT id FROM ancestors;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION rsvp.resource_descendants(rid text, tid text DEFAULT '') RETURNS SETOF varchar AS $$
+    WITH RECURSIVE descendants(id) AS (
+        SELECT rid::varchar
+        UNION
+        SELECT r.id FROM rsvp.resource r
+        JOIN descendants d ON r.tenant_id = tid AND r.parent_id = d.id
+    )
+    SELECT id FROM descendants;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION rsvp.resource_root(rid text, tid text DEFAULT '') RETURNS varchar AS $$
+    WITH RECURSIVE ancestors(id, parent_id, depth) AS (
+        SELECT rid::varchar, (SELECT parent_id FROM rsvp.resource WHERE tenant_id = tid AND id = rid), 0
+        UNION ALL
+        SELECT r.id, r.parent_id, a.depth + 1 FROM rsvp.resource r
+        JOIN ancestors a ON r.tenant_id = tid AND r.id = a.parent_id
+    )
+    SELECT id FROM ancestors ORDER BY depth DESC LIMIT 1;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION rsvp.blocked_span(rid text, span tstzrange, tid text DEFAULT '') RETURNS tstzrange AS $$
+    SELECT tstzrange(
+        lower(span) - COALESCE((SELECT pre_buffer FROM rsvp.resource WHERE tenant_id = tid AND id = rid), '0'),
+        upper(span) + COALESCE((SELECT post_buffer FROM rsvp.resource WHERE tenant_id = tid AND id = rid), '0'),
+        '[)'
+    );
+$$ LANGUAGE sql STABLE;
+
+-- same as the previous rsvp.query, only returning reservations of the tenant `tid`
+DROP FUNCTION IF EXISTS rsvp.query(text, text, timestamp with time zone, timestamp with time zone, rsvp.reservation_status, bool, bigint, text);
+
+CREATE OR REPLACE FUNCTION rsvp.query(
+    uid text,
+    rid text,
+    _start timestamp with time zone,
+    _end timestamp with time zone,
+    status rsvp.reservation_status DEFAULT 'pending',
+    is_desc bool DEFAULT FALSE,
+    _limit bigint DEFAULT NULL,
+    labels_cond text DEFAULT NULL,
+    tid text DEFAULT ''
+) RETURNS TABLE (LIKE rsvp.reservation) AS $$
+DECLARE
+    _during tstzrange;
+    _sql text;
+BEGIN
+    -- if start or end is null, use infinity
+    _during := tstzrange(
+        COALESCE(_start, '-infinity'),
+        COALESCE(_end, 'infinity'),
+        '[)'
+    );
+    -- format the query based on parameters
+    _sql := format(
+        'SELECT * FROM rsvp.reservation WHERE tenant_id = %L AND %L @> timespan AND status = %L AND %s AND %s ORDER BY lower(timespan) %s LIMIT %s',
+        COALESCE(tid, ''),
+        _during,
+        status,
+        CASE
+            WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+            WHEN uid IS NULL THEN 'resource_id = ' || quote_literal(rid)
+            WHEN rid IS NULL THEN 'user_id = ' || quote_literal(uid)
+            ELSE 'user_id = ' || quote_literal(uid) || ' AND resource_id = ' || quote_literal(rid)
+        END,
+        COALESCE(labels_cond, 'TRUE'),
+        CASE
+            WHEN is_desc THEN 'DESC'
+            ELSE 'ASC'
+        END,
+        CASE
+            WHEN _limit IS NULL OR _limit <= 0 THEN 'ALL'
+            ELSE _limit::text
+        END
+    );
+
+    -- execute
+    RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
+
+-- same as the previous rsvp.filter, only returning reservations of the tenant `tid`
+DROP FUNCTION IF EXISTS rsvp.filter(text, text, rsvp.reservation_status, bigint, bool, bigint);
+
+CREATE OR REPLACE FUNCTION rsvp.filter(
+    uid text,
+    rid text,
+    status rsvp.reservation_status,
+    cursor bigint DEFAULT NULL,
+    is_desc bool DEFAULT FALSE,
+    page_size bigint DEFAULT 10,
+    tid text DEFAULT ''
+) RETURNS TABLE (LIKE rsvp.reservation) AS $$
+DECLARE
+    _sql text;
+BEGIN
+    -- if page_size is not between 10 and 100, set it to 10
+    IF page_size < 10 OR page_size > 100 THEN
+        page_size := 10;
+    END IF;
+    -- if cursor is NULL or less than 0, set it to 0 if is_desc is false, or to 2^63 -1 if is_desc is true
+    IF cursor IS NULL OR cursor < 0 THEN
+        IF is_desc THEN
+            cursor := 9223372036854775807;
+        ELSE
+            cursor := 0;
+        END IF;
+    END IF;
+    _sql := format(
+        'SELECT * FROM rsvp.reservation WHERE tenant_id = %L AND %s AND status = %L AND %s ORDER BY id %s LIMIT %L::integer',
+        COALESCE(tid, ''),
+        CASE
+            WHEN is_desc THEN 'id <= ' || cursor
+            ELSE 'id >= ' || cursor
+        END,
+        status,
+        CASE
+            WHEN uid IS NULL AND rid IS NULL THEN 'TRUE'
+            WHEN uid IS NULL THEN 'resource_id = ' || quote_literal(rid)
+            WHEN rid IS NULL THEN 'user_id = ' || quote_literal(uid)
+            ELSE 'user_id = ' || quote_literal(uid) || ' AND resource_id = ' || quote_literal(rid)
+        END,
+        CASE
+            WHEN is_desc THEN 'DESC'
+            ELSE 'ASC'
+        END,
+        page_size + 1
+    );
+
+    -- execute
+    RETURN QUERY EXECUTE _sql;
+END;
+$$ LANGUAGE plpgsql;
+
+-- changes are queued and announced per tenant, listeners only get the ones of their own
+CREATE OR REPLACE FUNCTION rsvp.reservation_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO rsvp.reservation_change (reservation_id, op, tenant_id) VALUES (NEW.id, 'create', NEW.tenant_id);
+        PERFORM pg_notify('reservaption_update', NEW.tenant_id);
+    ELSIF TG_OP = 'UPDATE' THEN
+        IF OLD.status <> NEW.status THEN
+            INSERT INTO rsvp.reservation_change (reservation_id, op, tenant_id) VALUES (NEW.id, 'update', NEW.tenant_id);
+        END IF;
+        PERFORM pg_notify('reservaption_update', NEW.tenant_id);
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO rsvp.reservation_change (reservation_id, op, tenant_id) VALUES (OLD.id, 'delete', OLD.tenant_id);
+        PERFORM pg_notify('reservaption_update', OLD.tenant_id);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE INDEX reservation_changes_tenant_id_idx ON rsvp.reservation_change (tenant_id, id);
@@ -0,0 +1,18 @@
+-- releasing and extending keep the reservation but move its end, which listeners have to know
+CREATE OR REPLACE FUNCTION rsvp.reservation_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO rsvp.reservation_change (reservation_id, op, tenant_id) VALUES (NEW.id, 'create', NEW.tenant_id);
+        PERFORM pg_notify('reservaption_update', NEW.tenant_id);
+    ELSIF TG_OP = 'UPDATE' THEN
+        IF OLD.status <> NEW.status OR OLD.timespan <> NEW.timespan THEN
+            INSERT INTO rsvp.reservation_change (reservation_id, op, tenant_id) VALUES (NEW.id, 'update', NEW.tenant_id);
+        END IF;
+        PERFORM pg_notify('reservaption_update', NEW.tenant_id);
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO rsvp.reservation_change (reservation_id, op, tenant_id) VALUES (OLD.id, 'delete', OLD.tenant_id);
+        PERFORM pg_notify('reservaption_update', OLD.tenant_id);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
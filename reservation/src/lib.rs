@@ -1,13 +1,28 @@
 mod manager;
+mod metrics;
+
+use std::sync::Arc;
 
 use async_trait::async_trait;
-use luckychacha_reservation_abi::{Error, FilterPager, ReservationId};
+use chrono::{DateTime, Utc};
+use luckychacha_reservation_abi::{Error, FilterPager, PageSigner, ReservationId};
 use sqlx::PgPool;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
+
+pub use metrics::{QueryMetricsSnapshot, QueryOutcome, QueryStreamStats};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ReservationManager {
     pool: PgPool,
+    signer: PageSigner,
+    exact_count_threshold: i64,
+    query_metrics: Arc<metrics::QueryMetrics>,
+    /// every query is scoped to the tenant, see `for_tenant`.
+    tenant: String,
+    /// may decide on any pending reservation, see `as_admin`.
+    admin: bool,
+    /// set once the manager is closed, streams still open stop then, see `close`.
+    closing: Arc<watch::Sender<bool>>,
 }
 
 #[async_trait]
@@ -17,11 +32,62 @@ pub trait Rsvp {
         rsvp: luckychacha_reservation_abi::Reservation,
     ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
 
+    /// reserve, cancelling overlapping pending or confirmed reservations of a lower priority
+    /// for the reason given. Returns the reservation made and the ones cancelled.
+    async fn reserve_preempting(
+        &self,
+        rsvp: luckychacha_reservation_abi::Reservation,
+        reason: String,
+    ) -> Result<
+        (
+            luckychacha_reservation_abi::Reservation,
+            Vec<luckychacha_reservation_abi::Reservation>,
+        ),
+        Error,
+    >;
+
     async fn change_status(
         &self,
         id: ReservationId,
     ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
 
+    /// the user showed up for a confirmed reservation, it won't be released as a no-show.
+    async fn check_in(
+        &self,
+        id: ReservationId,
+    ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
+
+    /// cancel confirmed reservations not checked in within the check-in window of their
+    /// resource, and hand their windows to the waitlist. Returns the reservations released.
+    async fn release_no_shows(
+        &self,
+    ) -> Result<Vec<luckychacha_reservation_abi::Reservation>, Error>;
+
+    /// number of reservations of the user released as no-shows.
+    async fn count_no_shows(&self, user_id: &str) -> Result<i64, Error>;
+
+    /// confirm a pending reservation as one of the approvers of its resource.
+    async fn approve(
+        &self,
+        id: ReservationId,
+        approver: String,
+        reason: String,
+    ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
+
+    /// cancel a pending reservation as one of the approvers of its resource.
+    async fn reject(
+        &self,
+        id: ReservationId,
+        approver: String,
+        reason: String,
+    ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
+
+    /// pending reservations waiting for the approver, oldest first.
+    async fn list_pending_approvals(
+        &self,
+        query: luckychacha_reservation_abi::ApprovalQuery,
+    ) -> Result<(FilterPager, Vec<luckychacha_reservation_abi::Reservation>), Error>;
+
     async fn update_note(
         &self,
         id: ReservationId,
@@ -33,6 +99,22 @@ pub trait Rsvp {
         id: ReservationId,
     ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
 
+    /// move the end of the reservation to `end`, now if None, freeing the rest of its
+    /// window. The reservation keeps its id and start.
+    async fn release(
+        &self,
+        id: ReservationId,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
+
+    /// move the end of a running reservation by `by`. If the time after it
+    /// is taken, the error tells the longest extension possible.
+    async fn extend(
+        &self,
+        id: ReservationId,
+        by: chrono::Duration,
+    ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
+
     async fn get(
         &self,
         id: ReservationId,
@@ -52,4 +134,68 @@ pub trait Rsvp {
         (FilterPager, Vec<luckychacha_reservation_abi::Reservation>),
         luckychacha_reservation_abi::Error,
     >;
+
+    async fn search(
+        &self,
+        search: luckychacha_reservation_abi::ReservationSearch,
+    ) -> Result<
+        (FilterPager, Vec<luckychacha_reservation_abi::Reservation>),
+        luckychacha_reservation_abi::Error,
+    >;
+
+    /// book the first or best fitting free resource matching the request's conditions.
+    async fn reserve_any(
+        &self,
+        request: luckychacha_reservation_abi::ReserveAnyRequest,
+    ) -> Result<luckychacha_reservation_abi::Reservation, Error>;
+
+    async fn put_resource(
+        &self,
+        resource: luckychacha_reservation_abi::Resource,
+    ) -> Result<luckychacha_reservation_abi::Resource, Error>;
+
+    async fn get_resource(&self, id: &str) -> Result<luckychacha_reservation_abi::Resource, Error>;
+
+    /// free slots of a resource in the request's window.
+    async fn availability(
+        &self,
+        request: luckychacha_reservation_abi::AvailabilityRequest,
+    ) -> Result<Vec<luckychacha_reservation_abi::TimeSlot>, Error>;
+
+    async fn put_quota(
+        &self,
+        quota: luckychacha_reservation_abi::Quota,
+    ) -> Result<luckychacha_reservation_abi::Quota, Error>;
+
+    async fn list_quotas(&self) -> Result<Vec<luckychacha_reservation_abi::Quota>, Error>;
+
+    async fn delete_quota(&self, id: &str) -> Result<luckychacha_reservation_abi::Quota, Error>;
+
+    /// reservations of the user cancelled by preemption, latest first.
+    async fn list_preemptions(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<luckychacha_reservation_abi::Preemption>, Error>;
+
+    /// wait for the reservation's window to become free. The returned entry carries the
+    /// reservation made if it's free already, windows which have passed are rejected.
+    async fn join_waitlist(
+        &self,
+        rsvp: luckychacha_reservation_abi::Reservation,
+    ) -> Result<luckychacha_reservation_abi::WaitlistEntry, Error>;
+
+    async fn list_waitlist(
+        &self,
+        request: luckychacha_reservation_abi::ListWaitlistRequest,
+    ) -> Result<Vec<luckychacha_reservation_abi::WaitlistEntry>, Error>;
+
+    async fn get_waitlist_entry(
+        &self,
+        id: i64,
+    ) -> Result<luckychacha_reservation_abi::WaitlistEntry, Error>;
+
+    async fn leave_waitlist(
+        &self,
+        id: i64,
+    ) -> Result<luckychacha_reservation_abi::WaitlistEntry, Error>;
 }
@@ -1,45 +1,128 @@
-use crate::{ReservationId, ReservationManager, Rsvp};
+use crate::{
+    metrics::QueryMetrics, QueryMetricsSnapshot, QueryOutcome, QueryStreamStats, ReservationId,
+    ReservationManager, Rsvp,
+};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use luckychacha_reservation_abi::{
-    convert_to_utc_time, DbConfig, FilterPager, Normalizer, Reservation, ToSql, Validator,
+    convert_interval_to_duration, convert_to_interval, convert_to_timestamp, convert_to_utc_time,
+    subtract_spans, ApprovalQuery, AvailabilityRequest, Config, Error, FilterPager,
+    ListWaitlistRequest, Normalizer, PagerConfig, Preemption, Quota, Reservation,
+    ReservationConflict, ReservationConflictInfo, ReservationFilter, ReservationWindow, Resource,
+    SearchHit, Span, TimeSlot, ToSql, Validator, WaitlistEntry,
 };
 use sqlx::{
-    postgres::{types::PgRange, PgPoolOptions},
-    PgPool, Row,
+    postgres::{
+        types::{PgInterval, PgRange},
+        PgPoolOptions,
+    },
+    types::Json,
+    Connection, PgConnection, PgPool, Row,
+};
+use std::{
+    ops::Bound,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use tokio::sync::mpsc;
-use tracing::{info, warn};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::MissedTickBehavior,
+};
+use tracing::{debug, info, warn};
+
+/// rows buffered for a query stream before we wait for the client to catch up.
+const QUERY_STREAM_BUFFER: usize = 128;
+
+/// cancellation reason of reservations released because nobody checked in.
+const NO_SHOW: &str = "no_show";
+
+/// how far past its end opening hours are looked at for extending a reservation, resources
+/// open around the clock would be open forever otherwise.
+const EXTENSION_SCAN_DAYS: i64 = 7;
 
 #[async_trait]
 impl Rsvp for ReservationManager {
     async fn reserve(
         &self,
-        mut rsvp: Reservation,
+        rsvp: Reservation,
     ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
         rsvp.validate()?;
 
-        let status = luckychacha_reservation_abi::ReservationStatus::from_i32(rsvp.status)
-            .unwrap_or(luckychacha_reservation_abi::ReservationStatus::Pending);
+        let mut tx = self.pool.begin().await?;
+        self.lock_family(&mut tx, &rsvp.resource_id).await?;
+        let rsvp = self.reserve_in(&mut tx, rsvp).await?;
+        tx.commit().await?;
 
-        let timespan: PgRange<DateTime<Utc>> = rsvp.get_timespan();
+        Ok(rsvp)
+    }
 
-        let id = sqlx::query(
-            "INSERT INTO rsvp.reservation(user_id, resource_id, timespan, note, status) VALUES ($1, $2, $3, $4, $5::rsvp.reservation_status) RETURNING id",
+    async fn reserve_preempting(
+        &self,
+        rsvp: Reservation,
+        reason: String,
+    ) -> Result<(Reservation, Vec<Reservation>), luckychacha_reservation_abi::Error> {
+        rsvp.validate()?;
+
+        let mut tx = self.pool.begin().await?;
+        self.lock_family(&mut tx, &rsvp.resource_id).await?;
+        // bookings in the way with the same or a higher priority still conflict, which rolls
+        // back the cancellations as well
+        let mut preempted: Vec<Reservation> = sqlx::query_as(
+            "
+                DELETE FROM rsvp.reservation
+                WHERE tenant_id = $4
+                    AND resource_id IN (
+                        SELECT rsvp.resource_ancestors($1, $4)
+                        UNION SELECT rsvp.resource_descendants($1, $4)
+                    )
+                    AND rsvp.blocked_span(resource_id, timespan, $4) && rsvp.blocked_span($1, $2, $4)
+                    AND priority < $3
+                    AND status IN ('pending', 'confirmed')
+                RETURNING *
+            ",
         )
-            .bind(rsvp.user_id.clone())
-            .bind(rsvp.resource_id.clone())
-            .bind(timespan)
-            .bind(rsvp.note.clone())
-            .bind(status.to_string())
-            .fetch_one(&self.pool)
-            .await?
-            .get(0);
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .bind(rsvp.priority)
+        .bind(&self.tenant)
+        .fetch_all(&mut tx)
+        .await?;
+        preempted.sort_by_key(|v| v.id);
 
-        rsvp.id = id;
+        let rsvp = self.reserve_in(&mut tx, rsvp).await?;
+        let reason = if reason.is_empty() {
+            format!("preempted by a reservation of priority {}", rsvp.priority)
+        } else {
+            reason
+        };
+        for v in preempted.iter() {
+            sqlx::query(
+                "
+                    INSERT INTO rsvp.preemption(
+                        reservation_id, user_id, status, resource_id, timespan, note, labels,
+                        priority, preempted_by, reason, tenant_id
+                    ) VALUES ($1, $2, $3::rsvp.reservation_status, $4, $5, $6, $7, $8, $9, $10, $11)
+                ",
+            )
+            .bind(v.id)
+            .bind(&v.user_id)
+            .bind(v.get_status().to_string())
+            .bind(&v.resource_id)
+            .bind(v.get_timespan())
+            .bind(&v.note)
+            .bind(Json(&v.labels))
+            .bind(v.priority)
+            .bind(rsvp.id)
+            .bind(&reason)
+            .bind(&self.tenant)
+            .execute(&mut tx)
+            .await?;
+        }
+        tx.commit().await?;
 
-        Ok(rsvp)
+        Ok((rsvp, preempted))
     }
 
     async fn change_status(
@@ -47,21 +130,198 @@ impl Rsvp for ReservationManager {
         id: ReservationId,
     ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
         id.validate()?;
+        let mut tx = self.pool.begin().await?;
+        // those have to be approved instead. The resource is locked until confirmed, so that
+        // it can't start requiring approval in between
+        let requires_approval: Option<bool> = sqlx::query_scalar(
+            "SELECT requires_approval FROM rsvp.resource WHERE tenant_id = $2 AND id = (SELECT resource_id FROM rsvp.reservation WHERE tenant_id = $2 AND id = $1) FOR SHARE",
+        )
+        .bind(id)
+        .bind(&self.tenant)
+        .fetch_optional(&mut tx)
+        .await?;
+        if requires_approval == Some(true) {
+            return Err(Error::ApprovalRequired(id));
+        }
+
         let rsvp: luckychacha_reservation_abi::Reservation = sqlx::query_as(
             "
                 UPDATE rsvp.reservation
                     SET status = 'confirmed'
-                WHERE id = $1
+                WHERE tenant_id = $2
+                    AND id = $1
                     AND status = 'pending'
                 RETURNING *
             ",
         )
         .bind(id)
+        .bind(&self.tenant)
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(rsvp)
+    }
+
+    async fn check_in(
+        &self,
+        id: ReservationId,
+    ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
+        id.validate()?;
+        let mut tx = self.pool.begin().await?;
+        let rsvp: Reservation = sqlx::query_as(
+            "SELECT * FROM rsvp.reservation WHERE tenant_id = $2 AND id = $1 FOR UPDATE",
+        )
+        .bind(id)
+        .bind(&self.tenant)
+        .fetch_one(&mut tx)
+        .await?;
+        // checking in twice keeps the first check-in
+        if rsvp.checked_in_at.is_some() {
+            return Ok(rsvp);
+        }
+        let window: Option<PgInterval> = sqlx::query_scalar(
+            "SELECT check_in_window FROM rsvp.resource WHERE tenant_id = $2 AND id = $1",
+        )
+        .bind(&rsvp.resource_id)
+        .bind(&self.tenant)
+        .fetch_optional(&mut tx)
+        .await?
+        .flatten();
+        rsvp.check_in_allowed(window.map(convert_interval_to_duration), Utc::now())?;
+        let rsvp = sqlx::query_as(
+            "UPDATE rsvp.reservation SET checked_in_at = now() WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(rsvp)
+    }
+
+    async fn release_no_shows(
+        &self,
+    ) -> Result<Vec<Reservation>, luckychacha_reservation_abi::Error> {
+        // no-shows of every tenant, each released within its own tenant
+        let candidates: Vec<(i64, String, String)> = sqlx::query_as(
+            "
+                SELECT v.id, v.resource_id, v.tenant_id FROM rsvp.reservation v
+                JOIN rsvp.resource r ON r.tenant_id = v.tenant_id AND r.id = v.resource_id
+                WHERE v.status = 'confirmed'
+                    AND v.checked_in_at IS NULL
+                    AND lower(v.timespan) + r.check_in_window <= now()
+                    AND upper(v.timespan) > now()
+                ORDER BY v.id
+            ",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut released = vec![];
+        for (id, resource_id, tenant_id) in candidates {
+            let manager = self.for_tenant(tenant_id);
+            let mut tx = self.pool.begin().await?;
+            manager.lock_family(&mut tx, &resource_id).await?;
+            // the user may have checked in or cancelled in the meantime
+            let rsvp: Option<Reservation> = sqlx::query_as(
+                "
+                    DELETE FROM rsvp.reservation
+                    WHERE id = $1 AND status = 'confirmed' AND checked_in_at IS NULL
+                    RETURNING *
+                ",
+            )
+            .bind(id)
+            .fetch_optional(&mut tx)
+            .await?;
+            let Some(rsvp) = rsvp else {
+                continue;
+            };
+            manager.record_cancellation(&mut tx, &rsvp, NO_SHOW).await?;
+            // the rest of the window may be what someone on the waitlist is waiting for
+            manager.promote_waitlist(&mut tx, &resource_id).await?;
+            tx.commit().await?;
+            info!(
+                "released reservation {} of {} as a no-show",
+                rsvp.id, rsvp.user_id
+            );
+            released.push(rsvp);
+        }
+        Ok(released)
+    }
+
+    async fn count_no_shows(
+        &self,
+        user_id: &str,
+    ) -> Result<i64, luckychacha_reservation_abi::Error> {
+        let count = sqlx::query_scalar(
+            "SELECT count(*) FROM rsvp.cancellation WHERE tenant_id = $3 AND user_id = $1 AND reason = $2",
+        )
+        .bind(user_id)
+        .bind(NO_SHOW)
+        .bind(&self.tenant)
         .fetch_one(&self.pool)
         .await?;
+        Ok(count)
+    }
+
+    async fn approve(
+        &self,
+        id: ReservationId,
+        approver: String,
+        reason: String,
+    ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
+        id.validate()?;
+        let mut tx = self.pool.begin().await?;
+        self.pending_for_approver(&mut tx, id, &approver).await?;
+        let rsvp: Reservation = sqlx::query_as(
+            "UPDATE rsvp.reservation SET status = 'confirmed' WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .fetch_one(&mut tx)
+        .await?;
+        self.record_decision(&mut tx, &rsvp, &approver, "approved", &reason)
+            .await?;
+        tx.commit().await?;
         Ok(rsvp)
     }
 
+    async fn reject(
+        &self,
+        id: ReservationId,
+        approver: String,
+        reason: String,
+    ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
+        id.validate()?;
+        let mut tx = self.pool.begin().await?;
+        let resource_id = self.resource_of(&mut tx, id).await?;
+        self.lock_family(&mut tx, &resource_id).await?;
+        self.pending_for_approver(&mut tx, id, &approver).await?;
+        let rsvp: Reservation =
+            sqlx::query_as("DELETE FROM rsvp.reservation WHERE id = $1 RETURNING *")
+                .bind(id)
+                .fetch_one(&mut tx)
+                .await?;
+        self.record_decision(&mut tx, &rsvp, &approver, "rejected", &reason)
+            .await?;
+        self.promote_waitlist(&mut tx, &resource_id).await?;
+        tx.commit().await?;
+        Ok(rsvp)
+    }
+
+    async fn list_pending_approvals(
+        &self,
+        mut query: ApprovalQuery,
+    ) -> Result<(FilterPager, Vec<Reservation>), luckychacha_reservation_abi::Error> {
+        query.tenant_id = self.tenant.clone();
+        query.validate()?;
+        query.verify_cursor(&self.signer)?;
+        let rsvps: Vec<Reservation> = sqlx::query_as(&query.to_sql())
+            .fetch_all(&self.pool)
+            .await?;
+        let mut rsvps = rsvps.into_iter().collect();
+        let pager = query.get_pager(&mut rsvps, &self.signer);
+        Ok((pager, rsvps.into_iter().collect()))
+    }
+
     async fn update_note(
         &self,
         id: ReservationId,
@@ -72,12 +332,13 @@ impl Rsvp for ReservationManager {
             "
                 UPDATE rsvp.reservation
                     SET note = $1
-                WHERE id = $2
+                WHERE tenant_id = $3 AND id = $2
                 RETURNING *
             ",
         )
         .bind(note)
         .bind(id)
+        .bind(&self.tenant)
         .fetch_one(&self.pool)
         .await?;
 
@@ -89,11 +350,108 @@ impl Rsvp for ReservationManager {
         id: ReservationId,
     ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
         id.validate()?;
+        let mut tx = self.pool.begin().await?;
+        let resource_id = self.resource_of(&mut tx, id).await?;
+        self.lock_family(&mut tx, &resource_id).await?;
         let rsvp: luckychacha_reservation_abi::Reservation =
             sqlx::query_as("DELETE FROM rsvp.reservation WHERE id= $1 RETURNING *")
                 .bind(id)
-                .fetch_one(&self.pool)
+                .fetch_one(&mut tx)
+                .await?;
+        // the window may be what someone on the waitlist is waiting for
+        self.promote_waitlist(&mut tx, &resource_id).await?;
+        tx.commit().await?;
+        Ok(rsvp)
+    }
+
+    async fn release(
+        &self,
+        id: ReservationId,
+        end: Option<DateTime<Utc>>,
+    ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
+        id.validate()?;
+        let mut tx = self.pool.begin().await?;
+        let resource_id = self.resource_of(&mut tx, id).await?;
+        self.lock_family(&mut tx, &resource_id).await?;
+        let rsvp: Reservation = sqlx::query_as("SELECT * FROM rsvp.reservation WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut tx)
+            .await?;
+        let end = end.unwrap_or_else(Utc::now);
+        let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+        if end <= start || end >= convert_to_utc_time(rsvp.end.clone().unwrap()) {
+            return Err(Error::InvalidTime);
+        }
+        let rsvp = sqlx::query_as(
+            "UPDATE rsvp.reservation SET timespan = tstzrange(lower(timespan), $2) WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(end)
+        .fetch_one(&mut tx)
+        .await?;
+        // the rest of the window may be what someone on the waitlist is waiting for
+        self.promote_waitlist(&mut tx, &resource_id).await?;
+        tx.commit().await?;
+        Ok(rsvp)
+    }
+
+    async fn extend(
+        &self,
+        id: ReservationId,
+        by: chrono::Duration,
+    ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
+        id.validate()?;
+        if by <= chrono::Duration::zero() {
+            return Err(Error::InvalidTime);
+        }
+        let mut tx = self.pool.begin().await?;
+        let resource_id = self.resource_of(&mut tx, id).await?;
+        self.lock_family(&mut tx, &resource_id).await?;
+        let rsvp: Reservation =
+            sqlx::query_as("SELECT * FROM rsvp.reservation WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_one(&mut tx)
+                .await?;
+        // only running reservations can be extended
+        let now = Utc::now();
+        let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+        let end = convert_to_utc_time(rsvp.end.clone().unwrap());
+        if now < start || end <= now {
+            return Err(Error::InvalidTime);
+        }
+        let extended = Reservation {
+            end: Some(convert_to_timestamp(end + by)),
+            ..rsvp.clone()
+        };
+
+        let resource = self.find_resource(&mut tx, &resource_id).await?;
+        if let Some(resource) = resource.as_ref() {
+            if let Some(policy) = resource.policy.as_ref() {
+                policy.check_duration(&extended)?;
+            }
+            if let Some(business_hours) = resource.business_hours.as_ref() {
+                business_hours.check(&extended)?;
+            }
+        }
+        if let Some(conflict) = self.family_conflict(&mut tx, &extended).await? {
+            let max_extension = self
+                .max_extension(&mut tx, &rsvp, resource.as_ref())
+                .await?;
+            return Err(Error::ExtensionConflict {
+                id,
+                conflict,
+                max_extension,
+            });
+        }
+        self.check_quotas(&mut tx, &extended, Some(&rsvp)).await?;
+
+        let rsvp =
+            sqlx::query_as("UPDATE rsvp.reservation SET timespan = $2 WHERE id = $1 RETURNING *")
+                .bind(id)
+                .bind(extended.get_timespan())
+                .fetch_one(&mut tx)
                 .await?;
+        tx.commit().await?;
         Ok(rsvp)
     }
 
@@ -104,10 +462,11 @@ impl Rsvp for ReservationManager {
         id.validate()?;
         let rsvp: luckychacha_reservation_abi::Reservation = sqlx::query_as(
             "
-            SELECT * FROM rsvp.reservation WHERE id = $1
+            SELECT * FROM rsvp.reservation WHERE tenant_id = $2 AND id = $1
             ",
         )
         .bind(id)
+        .bind(&self.tenant)
         .fetch_one(&self.pool)
         .await?;
 
@@ -125,15 +484,38 @@ impl Rsvp for ReservationManager {
         let status = luckychacha_reservation_abi::ReservationStatus::from_i32(query.status)
             .unwrap_or(luckychacha_reservation_abi::ReservationStatus::Pending);
 
+        let labels = query.get_label_selector();
         let start = query.start.map(convert_to_utc_time);
         let end = query.end.map(convert_to_utc_time);
 
+        let (tx, rx) = mpsc::channel(QUERY_STREAM_BUFFER);
+        // the receiver is still in our hands, there's room for the error
+        if query.limit < 0 {
+            let _ = tx.try_send(Err(Error::InvalidQueryLimit(query.limit)));
+            return rx;
+        }
+        let labels = match labels {
+            Ok(selector) if selector.is_empty() => None,
+            Ok(selector) => Some(selector.to_sql()),
+            Err(e) => {
+                let _ = tx.try_send(Err(e));
+                return rx;
+            }
+        };
+
         let pool = self.pool.clone();
-        let (tx, rx) = mpsc::channel(128);
+        let tenant = self.tenant.clone();
+        let metrics = self.query_metrics.clone();
+        let mut closing = self.closing.subscribe();
+        // a slot for the last word, the client may not be reading when the manager is closed
+        let mut shutting_down = tx.clone().try_reserve_owned().ok();
+        metrics.stream_started();
 
         tokio::spawn(async move {
+            let started = Instant::now();
+            let mut rows = 0;
             let mut rsvp_rows = sqlx::query_as(
-                "SELECT * FROM rsvp.query($1, $2, $3, $4, $5::rsvp.reservation_status, $6)",
+                "SELECT * FROM rsvp.query($1, $2, $3, $4, $5::rsvp.reservation_status, $6, $7, $8, $9)",
             )
             .bind(user_id)
             .bind(resource_id)
@@ -141,25 +523,65 @@ impl Rsvp for ReservationManager {
             .bind(end)
             .bind(status.to_string())
             .bind(query.desc)
+            .bind(query.limit)
+            .bind(labels)
+            .bind(tenant)
             .fetch_many(&pool);
-            while let Some(ret) = rsvp_rows.next().await {
+
+            // sending waits for the client to consume rows, so a slow client slows down
+            // the query instead of piling rows up in memory. If the client goes away we
+            // stop right away, even when the database hasn't returned the next row yet.
+            let outcome = loop {
+                let ret = tokio::select! {
+                    _ = tx.closed() => break QueryOutcome::Cancelled,
+                    _ = closed(&mut closing) => {
+                        if let Some(permit) = shutting_down.take() {
+                            permit.send(Err(Error::ShuttingDown));
+                        }
+                        break QueryOutcome::Cancelled;
+                    }
+                    ret = rsvp_rows.next() => ret,
+                };
                 match ret {
-                    Ok(sqlx::Either::Left(r)) => {
-                        info!("Query result: {r:?}");
+                    None => break QueryOutcome::Completed,
+                    Some(Ok(sqlx::Either::Left(r))) => {
+                        debug!("Query result: {r:?}");
                     }
-                    Ok(sqlx::Either::Right(r)) => {
-                        if tx.send(Ok(r)).await.is_err() {
-                            break;
+                    Some(Ok(sqlx::Either::Right(r))) => {
+                        let sent = tokio::select! {
+                            ret = tx.send(Ok(r)) => ret.is_ok(),
+                            _ = closed(&mut closing) => {
+                                if let Some(permit) = shutting_down.take() {
+                                    permit.send(Err(Error::ShuttingDown));
+                                }
+                                false
+                            }
+                        };
+                        if !sent {
+                            break QueryOutcome::Cancelled;
                         }
+                        rows += 1;
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         warn!("Query error: {e:?}");
-                        if tx.send(Err(e.into())).await.is_err() {
-                            break;
-                        }
+                        let _ = tx.send(Err(e.into())).await;
+                        break QueryOutcome::Failed;
                     }
                 }
-            }
+            };
+
+            let stats = QueryStreamStats {
+                rows,
+                elapsed: started.elapsed(),
+                outcome,
+            };
+            info!(
+                rows = stats.rows,
+                elapsed_ms = stats.elapsed.as_millis() as u64,
+                outcome = ?stats.outcome,
+                "query stream finished"
+            );
+            metrics.stream_finished(&stats);
         });
 
         rx
@@ -172,226 +594,2642 @@ impl Rsvp for ReservationManager {
         (FilterPager, Vec<luckychacha_reservation_abi::Reservation>),
         luckychacha_reservation_abi::Error,
     > {
+        filter.tenant_id = self.tenant.clone();
         filter.normalize()?;
+        filter.verify_cursor(&self.signer)?;
         let sql = filter.to_sql();
-        let rsvps = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+        let (rsvps, total) = tokio::try_join!(
+            sqlx::query_as(&sql).fetch_all(&self.pool),
+            self.count(&filter),
+        )?;
         let mut rsvps = rsvps.into_iter().collect();
-        let pager = filter.get_pager(&mut rsvps);
+        let mut pager = filter.get_pager(&mut rsvps, &self.signer);
+        if let Some((total, estimated)) = total {
+            pager.total = Some(total);
+            pager.total_estimated = estimated;
+        }
         Ok((pager, rsvps.into_iter().collect()))
     }
-}
 
-impl ReservationManager {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    async fn search(
+        &self,
+        mut search: luckychacha_reservation_abi::ReservationSearch,
+    ) -> Result<
+        (FilterPager, Vec<luckychacha_reservation_abi::Reservation>),
+        luckychacha_reservation_abi::Error,
+    > {
+        search.tenant_id = self.tenant.clone();
+        search.validate()?;
+        search.verify_cursor(&self.signer)?;
+        let sql = search.to_sql();
+        let hits: Vec<SearchHit> = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+        let mut hits = hits.into_iter().collect();
+        let pager = search.get_pager(&mut hits, &self.signer);
+        Ok((pager, hits.into_iter().map(|hit| hit.reservation).collect()))
     }
 
-    pub async fn from_config(
-        db_config: &DbConfig,
-    ) -> Result<Self, luckychacha_reservation_abi::Error> {
-        let pool = PgPoolOptions::default()
-            .max_connections(db_config.max_connections)
-            .connect(&db_config.db_url())
+    async fn reserve_any(
+        &self,
+        mut request: luckychacha_reservation_abi::ReserveAnyRequest,
+    ) -> Result<Reservation, luckychacha_reservation_abi::Error> {
+        request.validate()?;
+        if let Some(rsvp) = request.reservation.as_mut() {
+            rsvp.tenant_id = self.tenant.clone();
+        }
+        let candidates: Vec<(String, bool)> = sqlx::query_as(&request.to_sql())
+            .fetch_all(&self.pool)
             .await?;
-        Ok(Self::new(pool))
-    }
-}
-
-// fn str_to_option(s: &str) -> Option<&str> {
-//     if s.is_empty() {
-//         None
-//     } else {
-//         Some(s)
-//     }
-// }
 
-fn string_to_option(s: &str) -> Option<String> {
-    if s.is_empty() {
-        None
-    } else {
-        Some(s.to_string())
+        // a free resource may be taken by someone else before we get to it, the exclusion
+        // constraint tells us so and we move on to the next one. Busy resources are only
+        // tried to report the conflict the same way `reserve` does.
+        let template = request.reservation.unwrap();
+        let mut err = Error::NoResourceMatched;
+        for (resource_id, busy) in candidates {
+            let rsvp = Reservation {
+                resource_id,
+                ..template.clone()
+            };
+            match self.reserve(rsvp).await {
+                Err(e @ Error::ConflictReservation(_)) if busy => return Err(e),
+                // another resource may allow what this one doesn't
+                Err(e @ (Error::ConflictReservation(_) | Error::PolicyViolation { .. })) => err = e,
+                ret => return ret,
+            }
+        }
+        Err(err)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use luckychacha_reservation_abi::{
-        Reservation, ReservationConflictInfo, ReservationFilterBuilder, ReservationQueryBuilder,
-    };
-    use prost_types::Timestamp;
+    async fn put_resource(
+        &self,
+        resource: Resource,
+    ) -> Result<Resource, luckychacha_reservation_abi::Error> {
+        resource.validate()?;
+        let mut tx = self.pool.begin().await?;
+        if !resource.parent_id.is_empty() {
+            // the parent can't be the resource itself or part of it
+            let ancestors: Vec<String> =
+                sqlx::query_scalar("SELECT rsvp.resource_ancestors($1, $2)")
+                    .bind(&resource.parent_id)
+                    .bind(&self.tenant)
+                    .fetch_all(&mut tx)
+                    .await?;
+            if ancestors.contains(&resource.id) {
+                return Err(Error::InvalidParentResource(resource.parent_id));
+            }
+            self.get_resource_in(&mut tx, &resource.parent_id).await?;
+        }
 
-    use super::*;
+        let policy = resource.policy.clone().unwrap_or_default();
+        let interval = |d: Option<chrono::Duration>| d.map(convert_to_interval);
+        let resource = sqlx::query_as(
+            "
+                INSERT INTO rsvp.resource(
+                    id, attributes, parent_id,
+                    min_duration, max_duration, slot_minutes, min_lead_time, max_horizon,
+                    business_hours, pre_buffer, post_buffer, approvers, requires_approval,
+                    check_in_window, tenant_id
+                ) VALUES (
+                    $1, $2, NULLIF($3, ''), $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
+                )
+                ON CONFLICT (tenant_id, id) DO UPDATE SET
+                    attributes = EXCLUDED.attributes,
+                    parent_id = EXCLUDED.parent_id,
+                    min_duration = EXCLUDED.min_duration,
+                    max_duration = EXCLUDED.max_duration,
+                    slot_minutes = EXCLUDED.slot_minutes,
+                    min_lead_time = EXCLUDED.min_lead_time,
+                    max_horizon = EXCLUDED.max_horizon,
+                    business_hours = EXCLUDED.business_hours,
+                    pre_buffer = EXCLUDED.pre_buffer,
+                    post_buffer = EXCLUDED.post_buffer,
+                    approvers = EXCLUDED.approvers,
+                    requires_approval = EXCLUDED.requires_approval,
+                    check_in_window = EXCLUDED.check_in_window
+                RETURNING *
+            ",
+        )
+        .bind(&resource.id)
+        .bind(Json(resource.attributes_json()))
+        .bind(&resource.parent_id)
+        .bind(interval(policy.min_duration()))
+        .bind(interval(policy.max_duration()))
+        .bind(policy.slot_minutes)
+        .bind(interval(policy.min_lead_time()))
+        .bind(interval(policy.max_horizon()))
+        .bind(resource.business_hours.as_ref().map(Json))
+        .bind(interval(resource.pre_buffer()))
+        .bind(interval(resource.post_buffer()))
+        .bind(&resource.approvers)
+        .bind(resource.requires_approval)
+        .bind(interval(resource.check_in_window()))
+        .bind(&self.tenant)
+        .fetch_one(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(resource)
+    }
 
-    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn reserve_should_work_for_valid_window() {
-        let (_manager, rsvp) = make_luckychacha_reservation(migrated_pool.clone()).await;
-        assert_ne!(rsvp.id, 0);
+    async fn get_resource(&self, id: &str) -> Result<Resource, luckychacha_reservation_abi::Error> {
+        let mut conn = self.pool.acquire().await?;
+        self.get_resource_in(&mut conn, id).await
     }
 
-    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn reserve_conflict_reservation_should_reject() {
-        let (manager, _rsvp1) = make_luckychacha_reservation(migrated_pool.clone()).await;
+    async fn availability(
+        &self,
+        request: AvailabilityRequest,
+    ) -> Result<Vec<TimeSlot>, luckychacha_reservation_abi::Error> {
+        request.validate()?;
+        let start = convert_to_utc_time(request.start.unwrap());
+        let end = convert_to_utc_time(request.end.unwrap());
 
-        let rsvp2 = Reservation::new_pending(
-            "luckychacha-id",
-            "ocean-view-room-666",
-            "2022-12-25T15:00:00+0800".parse().unwrap(),
-            "2022-12-28T11:00:00+0800".parse().unwrap(),
-            String::from(
-                "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
-            ),
-        );
+        let mut conn = self.pool.acquire().await?;
+        let resource = self.find_resource(&mut conn, &request.resource_id).await?;
+        let open = match resource.as_ref().and_then(|r| r.business_hours.clone()) {
+            Some(business_hours) => business_hours.open_spans(start, end)?,
+            None => vec![(start, end)],
+        };
 
-        // let _rsvp1 = manager.reserve(rsvp1).await.unwrap();
+        // a parent or child being booked makes the resource unavailable as well. Bookings
+        // keep it busy for their buffers, and a new booking needs room for its own.
+        let (pre, post) = resource
+            .as_ref()
+            .map(|r| (r.pre_buffer(), r.post_buffer()))
+            .unwrap_or_default();
+        let busy: Vec<Span> = sqlx::query_as(
+            "
+                SELECT lower(blocked) - $4, upper(blocked) + $5 FROM (
+                    SELECT rsvp.blocked_span(resource_id, timespan, $6) AS blocked
+                    FROM rsvp.reservation
+                    WHERE tenant_id = $6
+                        AND resource_id IN (
+                            SELECT rsvp.resource_ancestors($1, $6)
+                            UNION SELECT rsvp.resource_descendants($1, $6)
+                        )
+                ) b
+                WHERE tstzrange(lower(blocked) - $4, upper(blocked) + $5, '[)')
+                    && tstzrange($2, $3, '[)')
+            ",
+        )
+        .bind(&request.resource_id)
+        .bind(start)
+        .bind(end)
+        .bind(convert_to_interval(
+            post.unwrap_or_else(chrono::Duration::zero),
+        ))
+        .bind(convert_to_interval(
+            pre.unwrap_or_else(chrono::Duration::zero),
+        ))
+        .bind(&self.tenant)
+        .fetch_all(&mut conn)
+        .await?;
 
-        let err = manager.reserve(rsvp2).await.unwrap_err();
-        if let luckychacha_reservation_abi::Error::ConflictReservation(
-            ReservationConflictInfo::Parsed(info),
-        ) = err
-        {
-            assert_eq!(info.old.rid, "ocean-view-room-666");
-        }
-        // assert!(err, Error::Conflict);
+        Ok(subtract_spans(open, busy)
+            .into_iter()
+            .map(TimeSlot::from)
+            .collect())
     }
 
-    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn reservation_change_status_should_work() {
-        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
-        println!("rsvp: {rsvp:?}");
-        assert!(rsvp.id > 0);
-
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+    async fn put_quota(&self, quota: Quota) -> Result<Quota, luckychacha_reservation_abi::Error> {
+        quota.validate()?;
+        let quota = sqlx::query_as(
+            "
+                INSERT INTO rsvp.quota(id, kind, quota_limit, period, conditions, tenant_id)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (tenant_id, id) DO UPDATE SET
+                    kind = EXCLUDED.kind,
+                    quota_limit = EXCLUDED.quota_limit,
+                    period = EXCLUDED.period,
+                    conditions = EXCLUDED.conditions
+                RETURNING *
+            ",
+        )
+        .bind(&quota.id)
+        .bind(quota.get_kind().as_str_name())
+        .bind(quota.limit)
+        .bind(quota.get_period().as_str_name())
+        .bind(Json(&quota.conditions))
+        .bind(&self.tenant)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(quota)
+    }
 
-        assert_eq!(
-            rsvp.status,
-            luckychacha_reservation_abi::ReservationStatus::Confirmed as i32
-        );
+    async fn list_quotas(&self) -> Result<Vec<Quota>, luckychacha_reservation_abi::Error> {
+        let mut conn = self.pool.acquire().await?;
+        Ok(self.quotas(&mut conn).await?)
     }
 
-    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn reservation_change_status_twice_should_do_nothing() {
-        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
+    async fn delete_quota(&self, id: &str) -> Result<Quota, luckychacha_reservation_abi::Error> {
+        sqlx::query_as("DELETE FROM rsvp.quota WHERE tenant_id = $2 AND id = $1 RETURNING *")
+            .bind(id)
+            .bind(&self.tenant)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| Error::QuotaNotFound(id.to_string()))
+    }
 
-        assert!(rsvp.id > 0);
+    async fn list_preemptions(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<Preemption>, luckychacha_reservation_abi::Error> {
+        let preemptions = sqlx::query_as(
+            "
+                SELECT *, reservation_id AS id FROM rsvp.preemption
+                WHERE tenant_id = $2 AND user_id = $1
+                ORDER BY created_at DESC, reservation_id DESC
+            ",
+        )
+        .bind(user_id)
+        .bind(&self.tenant)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(preemptions)
+    }
 
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+    async fn join_waitlist(
+        &self,
+        rsvp: Reservation,
+    ) -> Result<WaitlistEntry, luckychacha_reservation_abi::Error> {
+        rsvp.validate()?;
+        // there's nothing to wait for once the window has passed
+        if convert_to_utc_time(rsvp.end.clone().unwrap()) <= Utc::now() {
+            return Err(Error::InvalidTime);
+        }
 
-        let rsvp = manager.change_status(rsvp.id).await.unwrap_err();
+        let mut tx = self.pool.begin().await?;
+        self.lock_family(&mut tx, &rsvp.resource_id).await?;
+        let mut entry: WaitlistEntry = sqlx::query_as(
+            "INSERT INTO rsvp.waitlist(user_id, resource_id, timespan, note, labels, priority, tenant_id) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        )
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .bind(&rsvp.note)
+        .bind(Json(&rsvp.labels))
+        .bind(rsvp.priority)
+        .bind(&self.tenant)
+        .fetch_one(&mut tx)
+        .await?;
+        // the window may have become free since the reservation failed
+        let promoted = self.promote_waitlist(&mut tx, &rsvp.resource_id).await?;
+        tx.commit().await?;
 
-        assert_eq!(
-            rsvp,
-            luckychacha_reservation_abi::Error::ReservationNotFound,
-        );
+        if let Some((_, rsvp)) = promoted.into_iter().find(|(id, _)| *id == entry.id) {
+            entry.reservation = Some(rsvp);
+        }
+        Ok(entry)
     }
 
-    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn list_waitlist(
+        &self,
+        request: ListWaitlistRequest,
+    ) -> Result<Vec<WaitlistEntry>, luckychacha_reservation_abi::Error> {
+        let entries = sqlx::query_as(
+            "
+                SELECT * FROM rsvp.waitlist
+                WHERE tenant_id = $3 AND ($1 = '' OR resource_id = $1) AND ($2 = '' OR user_id = $2)
+                ORDER BY id
+            ",
+        )
+        .bind(&request.resource_id)
+        .bind(&request.user_id)
+        .bind(&self.tenant)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(entries)
+    }
+
+    async fn get_waitlist_entry(
+        &self,
+        id: i64,
+    ) -> Result<WaitlistEntry, luckychacha_reservation_abi::Error> {
+        sqlx::query_as("SELECT * FROM rsvp.waitlist WHERE tenant_id = $2 AND id = $1")
+            .bind(id)
+            .bind(&self.tenant)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(Error::WaitlistEntryNotFound(id))
+    }
+
+    async fn leave_waitlist(
+        &self,
+        id: i64,
+    ) -> Result<WaitlistEntry, luckychacha_reservation_abi::Error> {
+        sqlx::query_as("DELETE FROM rsvp.waitlist WHERE tenant_id = $2 AND id = $1 RETURNING *")
+            .bind(id)
+            .bind(&self.tenant)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(Error::WaitlistEntryNotFound(id))
+    }
+}
+
+impl ReservationManager {
+    pub fn new(pool: PgPool) -> Self {
+        Self::with_pager_config(pool, &PagerConfig::default())
+    }
+
+    pub fn with_pager_config(pool: PgPool, config: &PagerConfig) -> Self {
+        Self {
+            pool,
+            signer: config.into(),
+            exact_count_threshold: config.exact_count_threshold,
+            query_metrics: Arc::new(QueryMetrics::default()),
+            tenant: String::new(),
+            admin: false,
+            closing: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// the same manager, only seeing and changing the data of the tenant. Managers start out
+    /// with the default tenant.
+    pub fn for_tenant(&self, tenant_id: impl Into<String>) -> Self {
+        Self {
+            tenant: tenant_id.into(),
+            ..self.clone()
+        }
+    }
+
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    /// the same manager, approving and rejecting reservations of resources the approver isn't
+    /// an approver of. Only for callers already authorized to do so, e.g. admins.
+    pub fn as_admin(&self) -> Self {
+        Self {
+            admin: true,
+            ..self.clone()
+        }
+    }
+
+    /// release no-shows every `period` until the returned task is aborted.
+    pub fn spawn_no_show_release(self: Arc<Self>, period: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.release_no_shows().await {
+                    warn!("failed to release no-shows: {e:?}");
+                }
+            }
+        })
+    }
+
+    /// whether the database answers within `timeout`.
+    pub async fn is_healthy(&self, timeout: Duration) -> bool {
+        let ping = sqlx::query("SELECT 1").execute(&self.pool);
+        matches!(tokio::time::timeout(timeout, ping).await, Ok(Ok(_)))
+    }
+
+    /// close the connections to the database once the ones in use are returned. Streams of
+    /// `query` of every manager sharing the pool end with `ShuttingDown` right away, so that
+    /// they return theirs.
+    pub async fn close(&self) {
+        self.closing.send_replace(true);
+        self.pool.close().await
+    }
+
+    /// counters over all streams opened by `query` so far.
+    pub fn query_metrics(&self) -> QueryMetricsSnapshot {
+        self.query_metrics.snapshot()
+    }
+
+    pub async fn from_config(config: &Config) -> Result<Self, luckychacha_reservation_abi::Error> {
+        let pool = PgPoolOptions::default()
+            .max_connections(config.db.max_connections)
+            .connect(&config.db.db_url())
+            .await?;
+        Ok(Self::with_pager_config(pool, &config.pager))
+    }
+
+    async fn get_resource_in(&self, conn: &mut PgConnection, id: &str) -> Result<Resource, Error> {
+        self.find_resource(conn, id)
+            .await?
+            .ok_or_else(|| Error::ResourceNotFound(id.to_string()))
+    }
+
+    async fn find_resource(
+        &self,
+        conn: &mut PgConnection,
+        id: &str,
+    ) -> Result<Option<Resource>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM rsvp.resource WHERE tenant_id = $2 AND id = $1")
+            .bind(id)
+            .bind(&self.tenant)
+            .fetch_optional(conn)
+            .await
+    }
+
+    /// resource of the reservation, which has to be one of the tenant.
+    async fn resource_of(
+        &self,
+        conn: &mut PgConnection,
+        id: ReservationId,
+    ) -> Result<String, Error> {
+        let resource_id = sqlx::query_scalar(
+            "SELECT resource_id FROM rsvp.reservation WHERE tenant_id = $2 AND id = $1",
+        )
+        .bind(id)
+        .bind(&self.tenant)
+        .fetch_one(conn)
+        .await?;
+        Ok(resource_id)
+    }
+
+    async fn quotas(&self, conn: &mut PgConnection) -> Result<Vec<Quota>, sqlx::Error> {
+        sqlx::query_as("SELECT * FROM rsvp.quota WHERE tenant_id = $1 ORDER BY id")
+            .bind(&self.tenant)
+            .fetch_all(conn)
+            .await
+    }
+
+    /// the pending reservation, locked, if the approver may decide on it. Admins may decide on
+    /// every one.
+    async fn pending_for_approver(
+        &self,
+        conn: &mut PgConnection,
+        id: ReservationId,
+        approver: &str,
+    ) -> Result<Reservation, Error> {
+        let rsvp: Reservation = sqlx::query_as(
+            "SELECT * FROM rsvp.reservation WHERE tenant_id = $2 AND id = $1 AND status = 'pending' FOR UPDATE",
+        )
+        .bind(id)
+        .bind(&self.tenant)
+        .fetch_one(&mut *conn)
+        .await?;
+        let approvers = self
+            .find_resource(conn, &rsvp.resource_id)
+            .await?
+            .map(|v| v.approvers);
+        if !self.admin && !approvers.unwrap_or_default().iter().any(|v| v == approver) {
+            return Err(Error::PermissionDenied(format!(
+                "{approver:?} is not an approver of {}",
+                rsvp.resource_id
+            )));
+        }
+        Ok(rsvp)
+    }
+
+    async fn record_cancellation(
+        &self,
+        conn: &mut PgConnection,
+        rsvp: &Reservation,
+        reason: &str,
+    ) -> Result<(), Error> {
+        sqlx::query(
+            "
+                INSERT INTO rsvp.cancellation(
+                    reservation_id, user_id, resource_id, timespan, reason, tenant_id
+                ) VALUES ($1, $2, $3, $4, $5, $6)
+            ",
+        )
+        .bind(rsvp.id)
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .bind(reason)
+        .bind(&self.tenant)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    async fn record_decision(
+        &self,
+        conn: &mut PgConnection,
+        rsvp: &Reservation,
+        approver: &str,
+        decision: &str,
+        reason: &str,
+    ) -> Result<(), Error> {
+        sqlx::query(
+            "
+                INSERT INTO rsvp.approval(
+                    reservation_id, user_id, resource_id, timespan, approver, decision, reason,
+                    tenant_id
+                ) VALUES ($1, $2, $3, $4, $5, $6::rsvp.approval_decision, $7, $8)
+            ",
+        )
+        .bind(rsvp.id)
+        .bind(&rsvp.user_id)
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .bind(approver)
+        .bind(decision)
+        .bind(reason)
+        .bind(&self.tenant)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// the exclusion constraint only covers bookings of the very same resource. Bookings in
+    /// one resource hierarchy are serialized on its root instead, so two of them can't both
+    /// miss each other.
+    async fn lock_family(&self, conn: &mut PgConnection, resource_id: &str) -> Result<(), Error> {
+        sqlx::query(
+            "SELECT pg_advisory_xact_lock(hashtext($2 || '/' || rsvp.resource_root($1, $2)))",
+        )
+        .bind(resource_id)
+        .bind(&self.tenant)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// check and insert a reservation, its resource hierarchy has to be locked already.
+    async fn reserve_in(
+        &self,
+        conn: &mut PgConnection,
+        mut rsvp: Reservation,
+    ) -> Result<Reservation, Error> {
+        let status = luckychacha_reservation_abi::ReservationStatus::from_i32(rsvp.status)
+            .unwrap_or(luckychacha_reservation_abi::ReservationStatus::Pending);
+
+        let timespan: PgRange<DateTime<Utc>> = rsvp.get_timespan();
+
+        self.check_policy(&mut *conn, &rsvp).await?;
+        if let Some(conflict) = self.family_conflict(&mut *conn, &rsvp).await? {
+            return Err(Error::ConflictReservation(conflict));
+        }
+        self.check_quotas(&mut *conn, &rsvp, None).await?;
+
+        let id = sqlx::query(
+            "INSERT INTO rsvp.reservation(user_id, resource_id, timespan, note, status, labels, priority, tenant_id) VALUES ($1, $2, $3, $4, $5::rsvp.reservation_status, $6, $7, $8) RETURNING id",
+        )
+            .bind(rsvp.user_id.clone())
+            .bind(rsvp.resource_id.clone())
+            .bind(timespan)
+            .bind(rsvp.note.clone())
+            .bind(status.to_string())
+            .bind(Json(&rsvp.labels))
+            .bind(rsvp.priority)
+            .bind(&self.tenant)
+            .fetch_one(conn)
+            .await?
+            .get(0);
+
+        rsvp.id = id;
+        rsvp.tenant_id = self.tenant.clone();
+
+        Ok(rsvp)
+    }
+
+    /// turn waitlist entries of the resource's hierarchy into pending reservations, oldest
+    /// first, as far as their windows are free by now. Returns the promoted entry ids and
+    /// their reservations. The hierarchy has to be locked already.
+    ///
+    /// Nothing happens when a blocking reservation merely ends, it still overlaps the windows
+    /// waiting for it. Entries whose own window has ended by then are dropped here instead.
+    async fn promote_waitlist(
+        &self,
+        conn: &mut PgConnection,
+        resource_id: &str,
+    ) -> Result<Vec<(i64, Reservation)>, Error> {
+        sqlx::query(
+            "
+                DELETE FROM rsvp.waitlist
+                WHERE tenant_id = $2
+                    AND rsvp.resource_root(resource_id, $2) = rsvp.resource_root($1, $2)
+                    AND upper(timespan) <= now()
+            ",
+        )
+        .bind(resource_id)
+        .bind(&self.tenant)
+        .execute(&mut *conn)
+        .await?;
+
+        let entries: Vec<WaitlistEntry> = sqlx::query_as(
+            "
+                SELECT * FROM rsvp.waitlist
+                WHERE tenant_id = $2
+                    AND rsvp.resource_root(resource_id, $2) = rsvp.resource_root($1, $2)
+                ORDER BY id
+            ",
+        )
+        .bind(resource_id)
+        .bind(&self.tenant)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut promoted = vec![];
+        for entry in entries {
+            let mut savepoint = conn.begin().await?;
+            match self
+                .reserve_in(&mut savepoint, entry.get_reservation())
+                .await
+            {
+                Ok(rsvp) => {
+                    sqlx::query("DELETE FROM rsvp.waitlist WHERE id = $1")
+                        .bind(entry.id)
+                        .execute(&mut savepoint)
+                        .await?;
+                    savepoint.commit().await?;
+                    promoted.push((entry.id, rsvp));
+                }
+                // still taken, or not allowed any more, keep waiting
+                Err(
+                    Error::ConflictReservation(_)
+                    | Error::PolicyViolation { .. }
+                    | Error::QuotaExceeded { .. },
+                ) => savepoint.rollback().await?,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(promoted)
+    }
+
+    /// how much longer the reservation could last: until the next booking in its resource
+    /// hierarchy, the longest duration allowed, or the resource closing, whatever comes first.
+    /// Nothing if a booking blocks its end already, e.g. since the buffers have grown.
+    async fn max_extension(
+        &self,
+        conn: &mut PgConnection,
+        rsvp: &Reservation,
+        resource: Option<&Resource>,
+    ) -> Result<chrono::Duration, Error> {
+        let start = convert_to_utc_time(rsvp.start.clone().unwrap());
+        let end = convert_to_utc_time(rsvp.end.clone().unwrap());
+        let next: Option<DateTime<Utc>> = sqlx::query_scalar(
+            "
+                SELECT min(lower(rsvp.blocked_span(resource_id, timespan, $4)))
+                FROM rsvp.reservation
+                WHERE tenant_id = $4
+                    AND resource_id IN (
+                        SELECT rsvp.resource_ancestors($1, $4)
+                        UNION SELECT rsvp.resource_descendants($1, $4)
+                    )
+                    AND id <> $2
+                    AND upper(rsvp.blocked_span(resource_id, timespan, $4)) > $3
+            ",
+        )
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.id)
+        .bind(end)
+        .bind(&self.tenant)
+        .fetch_one(conn)
+        .await?;
+
+        let mut max_end = next.unwrap_or(DateTime::<Utc>::MAX_UTC);
+        if let Some(resource) = resource {
+            max_end -= resource
+                .post_buffer()
+                .unwrap_or_else(chrono::Duration::zero);
+            if let Some(max) = resource.policy.as_ref().and_then(|p| p.max_duration()) {
+                max_end = max_end.min(start + max);
+            }
+            if let Some(business_hours) = resource.business_hours.as_ref() {
+                let horizon = max_end.min(end + chrono::Duration::days(EXTENSION_SCAN_DAYS));
+                max_end = match business_hours.open_spans(start, horizon)?.first() {
+                    Some((open, close)) if *open == start => *close,
+                    _ => end,
+                };
+            }
+        }
+        Ok((max_end - end).max(chrono::Duration::zero()))
+    }
+
+    /// opening hours and booking rules of the reservation's resource, if it has any.
+    async fn check_policy(&self, conn: &mut PgConnection, rsvp: &Reservation) -> Result<(), Error> {
+        let Some(resource) = self.find_resource(conn, &rsvp.resource_id).await? else {
+            return Ok(());
+        };
+        let mut tz = None;
+        if let Some(business_hours) = resource.business_hours.as_ref() {
+            business_hours.check(rsvp)?;
+            tz = Some(business_hours.get_timezone()?);
+        }
+        if let Some(policy) = resource.policy.as_ref() {
+            policy.check(rsvp, Utc::now(), tz)?;
+        }
+        Ok(())
+    }
+
+    /// quotas the reservation's user would go over with it, instead of the one it replaces
+    /// if any. Reservations of a user are serialized once there are quotas, so two of them
+    /// can't both take what's left.
+    async fn check_quotas(
+        &self,
+        conn: &mut PgConnection,
+        rsvp: &Reservation,
+        replacing: Option<&Reservation>,
+    ) -> Result<(), Error> {
+        let quotas = self.quotas(&mut *conn).await?;
+        if quotas.is_empty() {
+            return Ok(());
+        }
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('quota'), hashtext($1))")
+            .bind(&rsvp.user_id)
+            .execute(&mut *conn)
+            .await?;
+
+        let now = Utc::now();
+        for quota in quotas {
+            if let Some(sql) = quota.applies_sql() {
+                let applies: bool = sqlx::query_scalar(&sql)
+                    .bind(&rsvp.resource_id)
+                    .bind(&self.tenant)
+                    .fetch_one(&mut *conn)
+                    .await?;
+                if !applies {
+                    continue;
+                }
+            }
+            let usage: i64 = sqlx::query_scalar(&quota.usage_sql(rsvp, now))
+                .bind(&rsvp.user_id)
+                .bind(&self.tenant)
+                .fetch_one(&mut *conn)
+                .await?;
+            let usage = usage - replacing.map(|v| quota.requested(v)).unwrap_or(0);
+            quota.check(usage, rsvp)?;
+        }
+        Ok(())
+    }
+
+    /// another booking of the reservation's resource, or of a parent or child of it,
+    /// overlapping with it once the turnaround buffers of both are taken into account.
+    async fn family_conflict(
+        &self,
+        conn: &mut PgConnection,
+        rsvp: &Reservation,
+    ) -> Result<Option<ReservationConflictInfo>, sqlx::Error> {
+        let row: Option<(String, DateTime<Utc>, DateTime<Utc>)> = sqlx::query_as(
+            "
+                SELECT resource_id, lower(timespan), upper(timespan) FROM rsvp.reservation
+                WHERE tenant_id = $4
+                    AND resource_id IN (
+                        SELECT rsvp.resource_ancestors($1, $4)
+                        UNION SELECT rsvp.resource_descendants($1, $4)
+                    )
+                    AND rsvp.blocked_span(resource_id, timespan, $4) && rsvp.blocked_span($1, $2, $4)
+                    AND id <> $3
+                ORDER BY lower(timespan), id
+                LIMIT 1
+            ",
+        )
+        .bind(&rsvp.resource_id)
+        .bind(rsvp.get_timespan())
+        .bind(rsvp.id)
+        .bind(&self.tenant)
+        .fetch_optional(conn)
+        .await?;
+
+        Ok(row.map(|(rid, start, end)| {
+            let timespan = rsvp.get_timespan();
+            let bound = |b: Bound<DateTime<Utc>>| match b {
+                Bound::Included(v) | Bound::Excluded(v) => v,
+                Bound::Unbounded => unreachable!("reservations are always bounded"),
+            };
+            ReservationConflictInfo::Parsed(ReservationConflict {
+                new: ReservationWindow {
+                    rid: rsvp.resource_id.clone(),
+                    start: bound(timespan.start),
+                    end: bound(timespan.end),
+                },
+                old: ReservationWindow { rid, start, end },
+            })
+        }))
+    }
+
+    /// number of reservations matching the filter, and whether it's only an estimation.
+    /// Large results are estimated from the planner statistics, as counting them exactly
+    /// means scanning every matching row.
+    async fn count(&self, filter: &ReservationFilter) -> Result<Option<(i64, bool)>, sqlx::Error> {
+        if !filter.include_total {
+            return Ok(None);
+        }
+
+        let estimation: i64 = sqlx::query_scalar("SELECT rsvp.count_estimate($1)")
+            .bind(filter.to_rows_sql())
+            .fetch_one(&self.pool)
+            .await?;
+        if estimation > self.exact_count_threshold {
+            return Ok(Some((estimation, true)));
+        }
+
+        let total = sqlx::query_scalar(&filter.to_count_sql())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(Some((total, false)))
+    }
+}
+
+// fn str_to_option(s: &str) -> Option<&str> {
+//     if s.is_empty() {
+//         None
+//     } else {
+//         Some(s)
+//     }
+// }
+
+/// completes once the manager is closed.
+async fn closed(closing: &mut watch::Receiver<bool>) {
+    while !*closing.borrow_and_update() {
+        if closing.changed().await.is_err() {
+            // every manager is gone, nobody can close them any more
+            futures::future::pending::<()>().await;
+        }
+    }
+}
+
+fn string_to_option(s: &str) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DurationRound;
+    use luckychacha_reservation_abi::{
+        convert_to_pb_duration, ApprovalQueryBuilder, AttributeCondition, AttributeOp,
+        BookingPolicy, BusinessHours, OpeningHours, QuotaPeriod, Reservation,
+        ReservationConflictInfo, ReservationFilterBuilder, ReservationQueryBuilder,
+        ReservationSearchBuilder, ReservationSortKey, ReserveAnyRequest,
+    };
+    use prost_types::Timestamp;
+
+    use super::*;
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_should_work_for_valid_window() {
+        let (_manager, rsvp) = make_luckychacha_reservation(migrated_pool.clone()).await;
+        assert_ne!(rsvp.id, 0);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_conflict_reservation_should_reject() {
+        let (manager, _rsvp1) = make_luckychacha_reservation(migrated_pool.clone()).await;
+
+        let rsvp2 = Reservation::new_pending(
+            "luckychacha-id",
+            "ocean-view-room-666",
+            "2022-12-25T15:00:00+0800".parse().unwrap(),
+            "2022-12-28T11:00:00+0800".parse().unwrap(),
+            String::from(
+                "I'll arrive at 3pm. Please help to upgrade to execuitive room if possible.",
+            ),
+        );
+
+        // let _rsvp1 = manager.reserve(rsvp1).await.unwrap();
+
+        let err = manager.reserve(rsvp2).await.unwrap_err();
+        if let luckychacha_reservation_abi::Error::ConflictReservation(
+            ReservationConflictInfo::Parsed(info),
+        ) = err
+        {
+            assert_eq!(info.old.rid, "ocean-view-room-666");
+        }
+        // assert!(err, Error::Conflict);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reservation_change_status_should_work() {
+        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
+        println!("rsvp: {rsvp:?}");
+        assert!(rsvp.id > 0);
+
+        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+
+        assert_eq!(
+            rsvp.status,
+            luckychacha_reservation_abi::ReservationStatus::Confirmed as i32
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reservation_change_status_twice_should_do_nothing() {
+        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
+
+        assert!(rsvp.id > 0);
+
+        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+
+        let rsvp = manager.change_status(rsvp.id).await.unwrap_err();
+
+        assert_eq!(
+            rsvp,
+            luckychacha_reservation_abi::Error::ReservationNotFound,
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
     async fn update_note_should_work() {
         let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
         assert!(rsvp.id > 0);
 
-        let rsvp = manager
-            .update_note(rsvp.id, "Hello world".into())
+        let rsvp = manager
+            .update_note(rsvp.id, "Hello world".into())
+            .await
+            .unwrap();
+
+        // let rsvp = manager.change_status(rsvp.id).await.unwrap_err();
+
+        assert_eq!(rsvp.note, "Hello world",);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn get_reservation_should_work() {
+        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
+        assert!(rsvp.id > 0);
+
+        let rsvp1 = manager.get(rsvp.id).await.unwrap();
+
+        assert_eq!(rsvp, rsvp1);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn delete_reservation_should_work() {
+        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
+        assert!(rsvp.id > 0);
+
+        manager.delete(rsvp.id).await.unwrap();
+
+        let get_return_err = manager.get(rsvp.id).await.unwrap_err();
+
+        assert_eq!(
+            get_return_err,
+            luckychacha_reservation_abi::Error::ReservationNotFound
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_reservations_should_work() {
+        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
+        assert!(rsvp.id > 0);
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .start("2022-12-25T15:00:00+0800".parse::<Timestamp>().unwrap())
+            .end("2022-12-28T11:00:00+0800".parse::<Timestamp>().unwrap())
+            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(rx.recv().await, Some(Ok(rsvp.clone())));
+        assert_eq!(rx.recv().await, None);
+
+        // if window is not in range, should return empty
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .start("2022-12-29T15:00:00+0800".parse::<Timestamp>().unwrap())
+            .end("2022-12-30T11:00:00+0800".parse::<Timestamp>().unwrap())
+            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        let mut rsvps = manager.query(query).await;
+        assert_eq!(rsvps.recv().await, None);
+
+        // if status is not in correct, should return empty
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .start("2022-12-25T15:00:00+0800".parse::<Timestamp>().unwrap())
+            .end("2022-12-28T11:00:00+0800".parse::<Timestamp>().unwrap())
+            .status(luckychacha_reservation_abi::ReservationStatus::Confirmed as i32)
+            .build()
+            .unwrap();
+        let mut rsvps = manager.query(query).await;
+        assert_eq!(rsvps.recv().await, None);
+
+        // change state to confirmed
+        let rsvp = manager.change_status(rsvp.id).await.unwrap();
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .start("2022-12-25T15:00:00+0800".parse::<Timestamp>().unwrap())
+            .end("2022-12-28T11:00:00+0800".parse::<Timestamp>().unwrap())
+            .status(luckychacha_reservation_abi::ReservationStatus::Confirmed as i32)
+            .build()
+            .unwrap();
+        let mut rsvps = manager.query(query).await;
+        assert_eq!(rsvps.recv().await, Some(Ok(rsvp)));
+        assert_eq!(rsvps.recv().await, None);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_reservations_should_respect_limit() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        make_reservations(&manager, 5, "").await;
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
+            .limit(3)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        let mut count = 0;
+        while let Some(rsvp) = rx.recv().await {
+            rsvp.unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 3);
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .limit(-1)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(
+            rx.recv().await,
+            Some(Err(luckychacha_reservation_abi::Error::InvalidQueryLimit(
+                -1
+            )))
+        );
+        assert_eq!(rx.recv().await, None);
+
+        let metrics = manager.query_metrics();
+        assert_eq!(metrics.completed, 1);
+        assert_eq!(metrics.rows, 3);
+        assert_eq!(metrics.active, 0);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn query_stream_should_stop_when_receiver_dropped() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        // more rows than the stream buffers, so the task has to wait for us
+        make_reservations(&manager, QUERY_STREAM_BUFFER + 10, "").await;
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert!(rx.recv().await.unwrap().is_ok());
+        drop(rx);
+
+        for _ in 0..50 {
+            if manager.query_metrics().active == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let metrics = manager.query_metrics();
+        assert_eq!(metrics.active, 0);
+        assert_eq!(metrics.cancelled, 1);
+        assert!(metrics.rows < (QUERY_STREAM_BUFFER + 10) as u64);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn filter_reservations_should_work() {
+        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
+        assert!(rsvp.id > 0);
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("alice")
+            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        let (pager, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(pager.prev, None);
+        assert_eq!(pager.next, None);
+
+        assert_eq!(rsvps.len(), 1);
+        assert_eq!(rsvps[0], rsvp);
+        assert_eq!(pager.total, None);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn filter_reservations_should_sort_by_start_time() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        // later reservations start earlier, and every two of them start at the same time
+        for i in 0..24 {
+            let day = 28 - i / 2;
+            let rsvp = Reservation::new_pending(
+                "alice",
+                format!("ixia-test-{i}"),
+                format!("2022-12-{day}T15:00:00+0800").parse().unwrap(),
+                "2022-12-30T11:00:00+0800".parse().unwrap(),
+                "",
+            );
+            manager.reserve(rsvp).await.unwrap();
+        }
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("alice")
+            .order_by(ReservationSortKey::Start as i32)
+            .build()
+            .unwrap();
+
+        let mut filter = Some(filter);
+        let mut rsvps = vec![];
+        while let Some(f) = filter {
+            let (pager, page) = manager.filter(f.clone()).await.unwrap();
+            rsvps.extend(page);
+            filter = f.next_page(&pager);
+        }
+        assert_eq!(rsvps.len(), 24);
+        let keys: Vec<_> = rsvps
+            .iter()
+            .map(|r| (r.start.clone().unwrap().seconds, r.id))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+
+        // paging back from the last page gives the same rows
+        let filter = ReservationFilterBuilder::default()
+            .user_id("alice")
+            .order_by(ReservationSortKey::Start as i32)
+            .build()
+            .unwrap();
+        let (pager, first) = manager.filter(filter.clone()).await.unwrap();
+        let filter = filter.next_page(&pager).unwrap();
+        let (pager, _) = manager.filter(filter.clone()).await.unwrap();
+        let filter = filter.prev_page(&pager).unwrap();
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, first);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn filter_reservations_should_count_total() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        make_reservations(&manager, 25, "").await;
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("alice")
+            .include_total(true)
+            .build()
+            .unwrap();
+        let (pager, rsvps) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(rsvps.len(), 10);
+        assert_eq!(pager.total, Some(25));
+        assert!(!pager.total_estimated);
+
+        // the total doesn't depend on the page
+        let filter = filter.next_page(&pager).unwrap();
+        let (pager, _) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(pager.total, Some(25));
+
+        // above the threshold the planner estimation is used
+        let manager = ReservationManager::with_pager_config(
+            migrated_pool.clone(),
+            &PagerConfig {
+                exact_count_threshold: 0,
+                ..Default::default()
+            },
+        );
+        let filter = ReservationFilter {
+            cursor: None,
+            ..filter
+        };
+        let (pager, _) = manager.filter(filter).await.unwrap();
+        assert!(pager.total_estimated);
+        assert!(pager.total.unwrap() > 0);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn filter_reservations_should_page_back_and_forth() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        make_reservations(&manager, 25, "").await;
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("alice")
+            .desc(true)
+            .build()
+            .unwrap();
+        let (pager, first) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(first.len(), 10);
+        assert_eq!(pager.prev, None);
+
+        let filter = filter.next_page(&pager).unwrap();
+        let (pager, second) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(second.len(), 10);
+        assert!(second[0].id < first[9].id);
+
+        let filter = filter.next_page(&pager).unwrap();
+        let (pager, third) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(third.len(), 5);
+        assert_eq!(pager.next, None);
+
+        let filter = filter.prev_page(&pager).unwrap();
+        let (pager, rsvps) = manager.filter(filter.clone()).await.unwrap();
+        assert_eq!(rsvps, second);
+
+        let filter = filter.prev_page(&pager).unwrap();
+        let (pager, rsvps) = manager.filter(filter).await.unwrap();
+        assert_eq!(rsvps, first);
+        assert_eq!(pager.prev, None);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn search_reservations_should_rank_matches() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let notes = [
+            "projector broken, please fix the projector",
+            "the projector is broken",
+            "need a whiteboard",
+            "projector works fine",
+        ];
+        let mut rsvps = vec![];
+        for (i, note) in notes.iter().enumerate() {
+            let rsvp = Reservation::new_pending(
+                "alice",
+                format!("room-{i}"),
+                "2022-12-25T15:00:00+0800".parse().unwrap(),
+                "2022-12-28T11:00:00+0800".parse().unwrap(),
+                *note,
+            );
+            rsvps.push(manager.reserve(rsvp).await.unwrap());
+        }
+
+        let search = ReservationSearchBuilder::default()
+            .text("projector broken")
+            .build()
+            .unwrap();
+        let (pager, found) = manager.search(search).await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].id, rsvps[0].id);
+        assert_eq!(found[1].id, rsvps[1].id);
+        assert!(pager.next.is_none());
+
+        // combined with other filters
+        let search = ReservationSearchBuilder::default()
+            .text("projector")
+            .resource_id("room-3")
+            .build()
+            .unwrap();
+        let (_, found) = manager.search(search).await.unwrap();
+        assert_eq!(found, vec![rsvps[3].clone()]);
+
+        let search = ReservationSearchBuilder::default()
+            .text("projector")
+            .start("2022-12-29T15:00:00+0800".parse::<Timestamp>().unwrap())
+            .build()
+            .unwrap();
+        let (_, found) = manager.search(search).await.unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn search_reservations_should_page_back_and_forth() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        // every reservation ranks the same, pages fall back to the id order
+        make_reservations(&manager, 25, "projector broken").await;
+
+        let search = ReservationSearchBuilder::default()
+            .text("projector")
+            .build()
+            .unwrap();
+        let (pager, first) = manager.search(search.clone()).await.unwrap();
+        assert_eq!(first.len(), 10);
+
+        let search = search.next_page(&pager).unwrap();
+        let (pager, second) = manager.search(search.clone()).await.unwrap();
+        assert_eq!(second.len(), 10);
+        assert!(second[0].id < first[9].id);
+
+        let search = search.prev_page(&pager).unwrap();
+        let (pager, rsvps) = manager.search(search).await.unwrap();
+        assert_eq!(rsvps, first);
+        assert!(pager.prev.is_none());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reservations_should_be_selected_by_labels() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let labels = [
+            vec![("team", "infra"), ("env", "lab")],
+            vec![("team", "infra"), ("env", "prod"), ("temporary", "yes")],
+            vec![("team", "qa"), ("env", "lab")],
+            vec![],
+        ];
+        let mut rsvps = vec![];
+        for (i, labels) in labels.into_iter().enumerate() {
+            let rsvp = Reservation::new_pending(
+                "alice",
+                format!("ixia-test-{i}"),
+                "2022-12-25T15:00:00+0800".parse().unwrap(),
+                "2022-12-28T11:00:00+0800".parse().unwrap(),
+                "",
+            )
+            .with_labels(labels);
+            rsvps.push(manager.reserve(rsvp).await.unwrap());
+        }
+        assert_eq!(manager.get(rsvps[1].id).await.unwrap(), rsvps[1]);
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("alice")
+            .label_selector("team=infra,!temporary")
+            .build()
+            .unwrap();
+        let (_, found) = manager.filter(filter).await.unwrap();
+        assert_eq!(found, vec![rsvps[0].clone()]);
+
+        let filter = ReservationFilterBuilder::default()
+            .label_selector("env in (lab,prod),team!=qa")
+            .build()
+            .unwrap();
+        let (_, found) = manager.filter(filter).await.unwrap();
+        assert_eq!(found, rsvps[..2].to_vec());
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("alice")
+            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
+            .label_selector("env notin (prod)")
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        let mut found = vec![];
+        while let Some(rsvp) = rx.recv().await {
+            found.push(rsvp.unwrap());
+        }
+        found.sort_by_key(|rsvp| rsvp.id);
+        assert_eq!(
+            found,
+            vec![rsvps[0].clone(), rsvps[2].clone(), rsvps[3].clone()]
+        );
+
+        let query = ReservationQueryBuilder::default()
+            .label_selector("env in lab")
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query).await;
+        assert_eq!(
+            rx.recv().await,
+            Some(Err(
+                luckychacha_reservation_abi::Error::InvalidLabelSelector("env in lab".into())
+            ))
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_with_invalid_label_should_reject() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let rsvp = Reservation::new_pending(
+            "alice",
+            "ixia-test-1",
+            "2022-12-25T15:00:00+0800".parse().unwrap(),
+            "2022-12-28T11:00:00+0800".parse().unwrap(),
+            "",
+        )
+        .with_labels([("team", "in fra")]);
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        assert_eq!(
+            err,
+            luckychacha_reservation_abi::Error::InvalidLabel("team=in fra".into())
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn put_resource_should_store_attributes() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let resource = Resource::new("room-1")
+            .with_attribute("seats", 10)
+            .with_attribute("projector", true);
+        assert_eq!(
+            manager.put_resource(resource.clone()).await.unwrap(),
+            resource
+        );
+        assert_eq!(manager.get_resource("room-1").await.unwrap(), resource);
+
+        // put replaces all attributes
+        let resource = Resource::new("room-1").with_attribute("seats", 12);
+        manager.put_resource(resource.clone()).await.unwrap();
+        assert_eq!(manager.get_resource("room-1").await.unwrap(), resource);
+
+        assert_eq!(
+            manager.get_resource("room-2").await.unwrap_err(),
+            Error::ResourceNotFound("room-2".into())
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_any_should_pick_best_fitting_free_resource() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        for (id, seats, projector) in [
+            ("room-a", 20, true),
+            ("room-b", 10, true),
+            ("room-c", 12, true),
+            ("room-d", 30, false),
+            ("room-e", 4, true),
+        ] {
+            let resource = Resource::new(id)
+                .with_attribute("seats", seats)
+                .with_attribute("projector", projector);
+            manager.put_resource(resource).await.unwrap();
+        }
+
+        let request = |best_fit: &str| ReserveAnyRequest {
+            reservation: Some(Reservation::new_pending(
+                "alice",
+                "",
+                "2022-12-25T15:00:00+0800".parse().unwrap(),
+                "2022-12-25T17:00:00+0800".parse().unwrap(),
+                "",
+            )),
+            conditions: vec![
+                AttributeCondition::new("seats", AttributeOp::Ge, 10),
+                AttributeCondition::new("projector", AttributeOp::Eq, true),
+            ],
+            best_fit: best_fit.into(),
+        };
+
+        let rsvp = manager.reserve_any(request("seats")).await.unwrap();
+        assert_eq!(rsvp.resource_id, "room-b");
+        assert_eq!(manager.get(rsvp.id).await.unwrap(), rsvp);
+
+        let rsvp = manager.reserve_any(request("seats")).await.unwrap();
+        assert_eq!(rsvp.resource_id, "room-c");
+
+        // without best fit, the first free one by id
+        let rsvp = manager.reserve_any(request("")).await.unwrap();
+        assert_eq!(rsvp.resource_id, "room-a");
+
+        // every matching room is booked by now
+        let err = manager.reserve_any(request("seats")).await.unwrap_err();
+        assert!(matches!(err, Error::ConflictReservation(_)));
+
+        let mut request = request("");
+        request.conditions = vec![AttributeCondition::new("seats", AttributeOp::Gt, 100)];
+        assert_eq!(
+            manager.reserve_any(request).await.unwrap_err(),
+            Error::NoResourceMatched
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn booking_parent_and_child_in_same_window_should_conflict() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        manager.put_resource(Resource::new("hall")).await.unwrap();
+        for partition in ["hall-a", "hall-b"] {
+            let resource = Resource::new(partition).with_parent("hall");
+            manager.put_resource(resource).await.unwrap();
+        }
+        let booking = |rid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                "alice",
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+
+        // partitions can be booked independently
+        manager
+            .reserve(booking(
+                "hall-a",
+                "2022-12-25T09:00:00+0800",
+                "2022-12-25T12:00:00+0800",
+            ))
+            .await
+            .unwrap();
+        manager
+            .reserve(booking(
+                "hall-b",
+                "2022-12-25T10:00:00+0800",
+                "2022-12-25T12:00:00+0800",
+            ))
+            .await
+            .unwrap();
+
+        // but not together with the whole hall
+        let err = manager
+            .reserve(booking(
+                "hall",
+                "2022-12-25T11:00:00+0800",
+                "2022-12-25T14:00:00+0800",
+            ))
+            .await
+            .unwrap_err();
+        let Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) = err else {
+            panic!("expect conflict, got {err:?}");
+        };
+        assert_eq!(conflict.new.rid, "hall");
+        assert_eq!(conflict.old.rid, "hall-a");
+        assert_eq!(conflict.old.start.to_rfc3339(), "2022-12-25T01:00:00+00:00");
+
+        let hall = manager
+            .reserve(booking(
+                "hall",
+                "2022-12-25T12:00:00+0800",
+                "2022-12-25T14:00:00+0800",
+            ))
+            .await
+            .unwrap();
+
+        let err = manager
+            .reserve(booking(
+                "hall-b",
+                "2022-12-25T13:00:00+0800",
+                "2022-12-25T15:00:00+0800",
+            ))
+            .await
+            .unwrap_err();
+        let Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) = err else {
+            panic!("expect conflict, got {err:?}");
+        };
+        assert_eq!(conflict.new.rid, "hall-b");
+        assert_eq!(conflict.old.rid, "hall");
+
+        // once the hall is cancelled, the partition is free again
+        manager.delete(hall.id).await.unwrap();
+        manager
+            .reserve(booking(
+                "hall-b",
+                "2022-12-25T13:00:00+0800",
+                "2022-12-25T15:00:00+0800",
+            ))
+            .await
+            .unwrap();
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn resource_hierarchy_should_not_have_cycles() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        manager.put_resource(Resource::new("hall")).await.unwrap();
+        let partition = Resource::new("hall-a").with_parent("hall");
+        assert_eq!(
+            manager.put_resource(partition.clone()).await.unwrap(),
+            partition
+        );
+
+        let err = manager
+            .put_resource(Resource::new("hall").with_parent("hall-a"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::InvalidParentResource("hall-a".into()));
+
+        let err = manager
+            .put_resource(Resource::new("hall-b").with_parent("ballroom"))
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::ResourceNotFound("ballroom".into()));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_should_follow_booking_policy() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let hours = |h: i64| Some(convert_to_pb_duration(chrono::Duration::hours(h)));
+        let policy = BookingPolicy {
+            min_duration: hours(1),
+            max_duration: hours(4),
+            slot_minutes: 30,
+            min_lead_time: hours(2),
+            max_horizon: hours(24 * 90),
+        };
+        let resource = Resource::new("lab-rig-1").with_policy(policy);
+        assert_eq!(
+            manager.put_resource(resource.clone()).await.unwrap(),
+            resource
+        );
+        assert_eq!(manager.get_resource("lab-rig-1").await.unwrap(), resource);
+
+        let start = (Utc::now() + chrono::Duration::days(1))
+            .duration_trunc(chrono::Duration::hours(1))
+            .unwrap();
+        let booking = |start: DateTime<Utc>, minutes: i64| {
+            Reservation::new_pending(
+                "alice",
+                "lab-rig-1",
+                start.into(),
+                (start + chrono::Duration::minutes(minutes)).into(),
+                "",
+            )
+        };
+
+        let rule = |err| match err {
+            Error::PolicyViolation { rule, .. } => rule,
+            err => panic!("expect policy violation, got {err:?}"),
+        };
+        let err = manager.reserve(booking(start, 30)).await.unwrap_err();
+        assert_eq!(rule(err), "min_duration");
+        let err = manager
+            .reserve(booking(start + chrono::Duration::minutes(10), 60))
+            .await
+            .unwrap_err();
+        assert_eq!(rule(err), "slot");
+        let err = manager
+            .reserve(booking(start - chrono::Duration::days(1), 60))
+            .await
+            .unwrap_err();
+        assert_eq!(rule(err), "min_lead_time");
+        let err = manager
+            .reserve(booking(start + chrono::Duration::days(100), 60))
+            .await
+            .unwrap_err();
+        assert_eq!(rule(err), "max_horizon");
+
+        manager.reserve(booking(start, 90)).await.unwrap();
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn availability_should_exclude_closed_hours_and_bookings() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let business_hours = BusinessHours {
+            timezone: "Asia/Shanghai".into(),
+            hours: (1..=5)
+                .map(|weekday| OpeningHours {
+                    weekday,
+                    open_minute: 9 * 60,
+                    close_minute: 18 * 60,
+                })
+                .collect(),
+            holidays: vec!["2022-12-26".into()],
+        };
+        let hall = Resource::new("hall").with_business_hours(business_hours.clone());
+        assert_eq!(manager.put_resource(hall.clone()).await.unwrap(), hall);
+        assert_eq!(manager.get_resource("hall").await.unwrap(), hall);
+        let partition = Resource::new("hall-a")
+            .with_parent("hall")
+            .with_business_hours(business_hours);
+        manager.put_resource(partition).await.unwrap();
+
+        let booking = |rid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                "alice",
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        let err = manager
+            .reserve(booking(
+                "hall",
+                "2022-12-23T03:00:00+0800",
+                "2022-12-23T04:00:00+0800",
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PolicyViolation { rule, .. } if rule == "business_hours"));
+        let err = manager
+            .reserve(booking(
+                "hall",
+                "2022-12-26T10:00:00+0800",
+                "2022-12-26T11:00:00+0800",
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PolicyViolation { rule, .. } if rule == "holiday"));
+
+        manager
+            .reserve(booking(
+                "hall",
+                "2022-12-23T10:00:00+0800",
+                "2022-12-23T12:00:00+0800",
+            ))
+            .await
+            .unwrap();
+
+        // friday to tuesday, the weekend and monday are closed
+        let request = AvailabilityRequest::new(
+            "hall-a",
+            "2022-12-23T00:00:00+0800".parse().unwrap(),
+            "2022-12-28T00:00:00+0800".parse().unwrap(),
+        );
+        let slots = manager.availability(request).await.unwrap();
+        let slot = |start: &str, end: &str| TimeSlot {
+            start: Some(start.parse().unwrap()),
+            end: Some(end.parse().unwrap()),
+        };
+        assert_eq!(
+            slots,
+            vec![
+                slot("2022-12-23T09:00:00+08:00", "2022-12-23T10:00:00+08:00"),
+                slot("2022-12-23T12:00:00+08:00", "2022-12-23T18:00:00+08:00"),
+                slot("2022-12-27T09:00:00+08:00", "2022-12-27T18:00:00+08:00"),
+            ]
+        );
+
+        // resources without business hours are always open
+        let request = AvailabilityRequest::new(
+            "ocean-view-room-666",
+            "2022-12-23T00:00:00+0800".parse().unwrap(),
+            "2022-12-24T00:00:00+0800".parse().unwrap(),
+        );
+        assert_eq!(
+            manager.availability(request).await.unwrap(),
+            vec![slot(
+                "2022-12-23T00:00:00+08:00",
+                "2022-12-24T00:00:00+08:00"
+            )]
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn bookings_within_turnaround_buffers_should_conflict() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        // 30 minutes to prepare the room, 3 hours of cleaning after checkout
+        let room = Resource::new("room-1")
+            .with_buffers(chrono::Duration::minutes(30), chrono::Duration::hours(3));
+        assert_eq!(manager.put_resource(room.clone()).await.unwrap(), room);
+
+        let booking = |start: &str, end: &str| {
+            Reservation::new_pending(
+                "alice",
+                "room-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        let first = manager
+            .reserve(booking("2022-12-25T08:00:00Z", "2022-12-25T11:00:00Z"))
+            .await
+            .unwrap();
+        // buffers aren't part of the booked window
+        assert_eq!(first.start.unwrap().to_string(), "2022-12-25T08:00:00Z");
+        assert_eq!(first.end.unwrap().to_string(), "2022-12-25T11:00:00Z");
+
+        let err = manager
+            .reserve(booking("2022-12-25T14:00:00Z", "2022-12-25T15:00:00Z"))
+            .await
+            .unwrap_err();
+        let Error::ConflictReservation(ReservationConflictInfo::Parsed(conflict)) = err else {
+            panic!("expect conflict, got {err:?}");
+        };
+        assert_eq!(conflict.old.start.to_rfc3339(), "2022-12-25T08:00:00+00:00");
+        assert_eq!(conflict.old.end.to_rfc3339(), "2022-12-25T11:00:00+00:00");
+
+        // the room is free again once cleaned and prepared
+        manager
+            .reserve(booking("2022-12-25T14:30:00Z", "2022-12-25T15:00:00Z"))
+            .await
+            .unwrap();
+
+        let request = AvailabilityRequest::new(
+            "room-1",
+            "2022-12-25T00:00:00+0000".parse().unwrap(),
+            "2022-12-26T00:00:00+0000".parse().unwrap(),
+        );
+        let slots = manager.availability(request).await.unwrap();
+        let slot = |start: &str, end: &str| TimeSlot {
+            start: Some(start.parse().unwrap()),
+            end: Some(end.parse().unwrap()),
+        };
+        assert_eq!(
+            slots,
+            vec![
+                slot("2022-12-25T00:00:00Z", "2022-12-25T04:30:00Z"),
+                slot("2022-12-25T18:30:00Z", "2022-12-26T00:00:00Z"),
+            ]
+        );
+
+        let room = Resource::new("room-2")
+            .with_buffers(chrono::Duration::minutes(-5), chrono::Duration::zero());
+        assert!(matches!(
+            manager.put_resource(room).await,
+            Err(Error::InvalidBuffer(_))
+        ));
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reserve_over_quota_should_reject() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        for id in ["room-1", "room-2", "room-3"] {
+            let room = Resource::new(id).with_attribute("type", "room");
+            manager.put_resource(room).await.unwrap();
+        }
+        manager
+            .put_resource(Resource::new("desk-1").with_attribute("type", "desk"))
+            .await
+            .unwrap();
+        let rooms = Quota::active_reservations("rooms", 2).with_condition(AttributeCondition::new(
+            "type",
+            AttributeOp::Eq,
+            "room",
+        ));
+        assert_eq!(manager.put_quota(rooms.clone()).await.unwrap(), rooms);
+        let weekly = Quota::booked_minutes("weekly", 6 * 60, QuotaPeriod::Week);
+        manager.put_quota(weekly.clone()).await.unwrap();
+        assert_eq!(manager.list_quotas().await.unwrap(), vec![rooms, weekly]);
+
+        // active reservations haven't ended yet, book far enough into the future
+        let booking = |rid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                "alice",
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        for rid in ["room-1", "room-2"] {
+            manager
+                .reserve(booking(rid, "2030-01-07T09:00:00Z", "2030-01-07T10:00:00Z"))
+                .await
+                .unwrap();
+        }
+        let err = manager
+            .reserve(booking(
+                "room-3",
+                "2030-01-07T09:00:00Z",
+                "2030-01-07T10:00:00Z",
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Error::QuotaExceeded {
+                quota: "rooms".into(),
+                limit: 2,
+                usage: 2,
+            }
+        );
+
+        // desks don't count towards rooms, but do towards the booked time of the week
+        manager
+            .reserve(booking(
+                "desk-1",
+                "2030-01-08T09:00:00Z",
+                "2030-01-08T12:00:00Z",
+            ))
+            .await
+            .unwrap();
+        let err = manager
+            .reserve(booking(
+                "desk-1",
+                "2030-01-09T09:00:00Z",
+                "2030-01-09T11:00:00Z",
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Error::QuotaExceeded {
+                quota: "weekly".into(),
+                limit: 360,
+                usage: 300,
+            }
+        );
+        // next week starts over
+        manager
+            .reserve(booking(
+                "desk-1",
+                "2030-01-14T09:00:00Z",
+                "2030-01-14T11:00:00Z",
+            ))
+            .await
+            .unwrap();
+
+        assert!(manager.delete_quota("rooms").await.is_ok());
+        assert_eq!(
+            manager.delete_quota("rooms").await,
+            Err(Error::QuotaNotFound("rooms".into()))
+        );
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn concurrent_reservations_should_not_exceed_quota() {
+        let manager = Arc::new(ReservationManager::new(migrated_pool.clone()));
+        manager
+            .put_quota(Quota::active_reservations("active", 3))
+            .await
+            .unwrap();
+
+        let tasks: Vec<_> = (0..10)
+            .map(|i| {
+                let manager = manager.clone();
+                let rsvp = Reservation::new_pending(
+                    "alice",
+                    format!("room-{i}"),
+                    "2030-01-07T09:00:00Z".parse().unwrap(),
+                    "2030-01-07T10:00:00Z".parse().unwrap(),
+                    "",
+                );
+                tokio::spawn(async move { manager.reserve(rsvp).await })
+            })
+            .collect();
+        let mut reserved = 0;
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(_) => reserved += 1,
+                Err(e) => assert!(matches!(e, Error::QuotaExceeded { .. }), "{e:?}"),
+            }
+        }
+        assert_eq!(reserved, 3);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn cancel_should_promote_waitlist() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let booking = |uid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                uid,
+                "room-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        let bob = manager
+            .reserve(booking(
+                "bob",
+                "2030-01-07T10:00:00Z",
+                "2030-01-07T12:00:00Z",
+            ))
             .await
             .unwrap();
 
-        // let rsvp = manager.change_status(rsvp.id).await.unwrap_err();
+        let alice = manager
+            .join_waitlist(booking(
+                "alice",
+                "2030-01-07T11:00:00Z",
+                "2030-01-07T13:00:00Z",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(alice.get_reservation().id, 0);
+        let carol = manager
+            .join_waitlist(booking(
+                "carol",
+                "2030-01-07T11:30:00Z",
+                "2030-01-07T12:30:00Z",
+            ))
+            .await
+            .unwrap();
+        let entries = manager
+            .list_waitlist(ListWaitlistRequest::new("room-1", ""))
+            .await
+            .unwrap();
+        assert_eq!(entries, vec![alice.clone(), carol.clone()]);
 
-        assert_eq!(rsvp.note, "Hello world",);
+        // alice was first, carol's window overlaps with hers and keeps waiting
+        manager.delete(bob.id).await.unwrap();
+        let rsvps: Vec<Reservation> =
+            sqlx::query_as("SELECT * FROM rsvp.reservation WHERE resource_id = 'room-1'")
+                .fetch_all(&migrated_pool)
+                .await
+                .unwrap();
+        assert_eq!(rsvps.len(), 1);
+        assert_eq!(rsvps[0].user_id, "alice");
+        assert_eq!(
+            rsvps[0].status,
+            luckychacha_reservation_abi::ReservationStatus::Pending as i32
+        );
+        let entries = manager
+            .list_waitlist(ListWaitlistRequest::new("", ""))
+            .await
+            .unwrap();
+        assert_eq!(entries, vec![carol.clone()]);
+
+        // announced through the change feed
+        let op: String = sqlx::query_scalar(
+            "SELECT op::text FROM rsvp.reservation_change WHERE reservation_id = $1",
+        )
+        .bind(rsvps[0].id)
+        .fetch_one(&migrated_pool)
+        .await
+        .unwrap();
+        assert_eq!(op, "create");
+
+        assert_eq!(manager.get_waitlist_entry(carol.id).await.unwrap(), carol);
+        assert_eq!(manager.leave_waitlist(carol.id).await.unwrap(), carol);
+        assert_eq!(
+            manager.leave_waitlist(carol.id).await,
+            Err(Error::WaitlistEntryNotFound(carol.id))
+        );
+        assert_eq!(
+            manager.get_waitlist_entry(carol.id).await,
+            Err(Error::WaitlistEntryNotFound(carol.id))
+        );
+
+        // a free window is reserved right away
+        let entry = manager
+            .join_waitlist(booking(
+                "carol",
+                "2030-01-07T14:00:00Z",
+                "2030-01-07T15:00:00Z",
+            ))
+            .await
+            .unwrap();
+        assert_ne!(entry.get_reservation().id, 0);
+        assert!(manager
+            .list_waitlist(ListWaitlistRequest::new("", "carol"))
+            .await
+            .unwrap()
+            .is_empty());
     }
 
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn get_reservation_should_work() {
-        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
-        assert!(rsvp.id > 0);
+    async fn ended_waitlist_entries_should_be_dropped() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let booking = |uid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                uid,
+                "room-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        assert_eq!(
+            manager
+                .join_waitlist(booking(
+                    "alice",
+                    "2022-12-25T10:00:00Z",
+                    "2022-12-25T12:00:00Z",
+                ))
+                .await,
+            Err(Error::InvalidTime)
+        );
 
-        let rsvp1 = manager.get(rsvp.id).await.unwrap();
+        // an entry which has been waiting in vain until its window passed
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO rsvp.waitlist(user_id, resource_id, timespan) VALUES ('alice', 'room-1', '[2022-12-25 10:00+00, 2022-12-25 12:00+00)') RETURNING id",
+        )
+        .fetch_one(&migrated_pool)
+        .await
+        .unwrap();
+        let bob = manager
+            .reserve(booking(
+                "bob",
+                "2030-01-07T10:00:00Z",
+                "2030-01-07T12:00:00Z",
+            ))
+            .await
+            .unwrap();
+        manager.delete(bob.id).await.unwrap();
 
-        assert_eq!(rsvp, rsvp1);
+        assert_eq!(
+            manager.get_waitlist_entry(id).await,
+            Err(Error::WaitlistEntryNotFound(id))
+        );
+        let reserved: i64 =
+            sqlx::query_scalar("SELECT count(*) FROM rsvp.reservation WHERE user_id = 'alice'")
+                .fetch_one(&migrated_pool)
+                .await
+                .unwrap();
+        assert_eq!(reserved, 0);
     }
 
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn delete_reservation_should_work() {
-        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
-        assert!(rsvp.id > 0);
+    async fn reserve_preempting_should_cancel_lower_priority_reservations() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let booking = |uid: &str, start: &str, end: &str| {
+            Reservation::new_pending(
+                uid,
+                "room-1",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+        let alice = manager
+            .reserve(booking(
+                "alice",
+                "2030-01-07T10:00:00Z",
+                "2030-01-07T12:00:00Z",
+            ))
+            .await
+            .unwrap();
+        let bob = manager
+            .reserve(booking(
+                "bob",
+                "2030-01-07T13:00:00Z",
+                "2030-01-07T14:00:00Z",
+            ))
+            .await
+            .unwrap();
 
-        manager.delete(rsvp.id).await.unwrap();
+        // a higher priority alone doesn't bump anyone
+        let incident =
+            booking("ops", "2030-01-07T11:00:00Z", "2030-01-07T13:30:00Z").with_priority(10);
+        let err = manager.reserve(incident.clone()).await.unwrap_err();
+        assert!(matches!(err, Error::ConflictReservation(_)));
 
-        let get_return_err = manager.get(rsvp.id).await.unwrap_err();
+        let (rsvp, preempted) = manager
+            .reserve_preempting(incident, "server room on fire".into())
+            .await
+            .unwrap();
+        assert_eq!(rsvp.priority, 10);
+        assert_eq!(preempted, vec![alice.clone(), bob.clone()]);
+        assert_eq!(manager.get(alice.id).await, Err(Error::ReservationNotFound));
+
+        let preemptions = manager.list_preemptions("alice").await.unwrap();
+        assert_eq!(preemptions.len(), 1);
+        assert_eq!(preemptions[0].reservation, Some(alice.clone()));
+        assert_eq!(preemptions[0].preempted_by, rsvp.id);
+        assert_eq!(preemptions[0].reason, "server room on fire");
+
+        // cancellations go through the change feed
+        let op: String = sqlx::query_scalar(
+            "SELECT op::text FROM rsvp.reservation_change WHERE reservation_id = $1 ORDER BY id DESC LIMIT 1",
+        )
+        .bind(alice.id)
+        .fetch_one(&migrated_pool)
+        .await
+        .unwrap();
+        assert_eq!(op, "delete");
+
+        // the same priority can't be bumped, and nothing is cancelled then
+        let other =
+            booking("carol", "2030-01-07T09:00:00Z", "2030-01-07T11:30:00Z").with_priority(10);
+        let err = manager
+            .reserve_preempting(other, "".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ConflictReservation(_)));
+        assert_eq!(manager.get(rsvp.id).await.unwrap(), rsvp);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn reservations_requiring_approval_should_be_approved() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let lab = Resource::new("lab").with_approvers(["dora", "eve"]);
+        assert_eq!(manager.put_resource(lab.clone()).await.unwrap(), lab);
+
+        let mut rsvps = vec![];
+        for day in 1..=12 {
+            let rsvp = Reservation::new_pending(
+                "alice",
+                "lab",
+                format!("2030-01-{day:02}T09:00:00Z").parse().unwrap(),
+                format!("2030-01-{day:02}T10:00:00Z").parse().unwrap(),
+                "",
+            );
+            rsvps.push(manager.reserve(rsvp).await.unwrap());
+        }
+        // another resource doesn't need approval
+        let other = manager
+            .reserve(Reservation::new_pending(
+                "alice",
+                "room-1",
+                "2030-01-01T09:00:00Z".parse().unwrap(),
+                "2030-01-01T10:00:00Z".parse().unwrap(),
+                "",
+            ))
+            .await
+            .unwrap();
+        assert!(manager.change_status(other.id).await.is_ok());
 
+        let id = rsvps[0].id;
         assert_eq!(
-            get_return_err,
-            luckychacha_reservation_abi::Error::ReservationNotFound
+            manager.change_status(id).await,
+            Err(Error::ApprovalRequired(id))
+        );
+        let err = manager
+            .approve(id, "mallory".into(), "".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PermissionDenied(_)));
+
+        let rsvp = manager
+            .approve(id, "dora".into(), "looks fine".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            rsvp.status,
+            luckychacha_reservation_abi::ReservationStatus::Confirmed as i32
+        );
+        // only pending reservations can be decided on
+        assert_eq!(
+            manager.reject(id, "eve".into(), "".into()).await,
+            Err(Error::ReservationNotFound)
+        );
+
+        let rejected = manager
+            .reject(rsvps[1].id, "eve".into(), "lab is being moved".into())
+            .await
+            .unwrap();
+        assert_eq!(rejected.id, rsvps[1].id);
+        assert_eq!(
+            manager.get(rejected.id).await,
+            Err(Error::ReservationNotFound)
+        );
+        let decisions: Vec<(String, String)> =
+            sqlx::query_as("SELECT decision::text, reason FROM rsvp.approval ORDER BY id")
+                .fetch_all(&migrated_pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            decisions,
+            vec![
+                ("approved".to_string(), "looks fine".to_string()),
+                ("rejected".to_string(), "lab is being moved".to_string()),
+            ]
+        );
+
+        // 10 left, paged oldest first
+        let query = ApprovalQueryBuilder::default()
+            .approver("eve")
+            .page_size(6)
+            .build();
+        assert!(query.is_err());
+        let query = ApprovalQueryBuilder::default()
+            .approver("eve")
+            .build()
+            .unwrap();
+        let (pager, page) = manager.list_pending_approvals(query.clone()).await.unwrap();
+        assert_eq!(page, rsvps[2..].to_vec());
+        assert!(pager.next.is_none());
+
+        let lab = lab.with_approvers(["dora"]);
+        manager.put_resource(lab).await.unwrap();
+        let (_, page) = manager.list_pending_approvals(query).await.unwrap();
+        assert!(page.is_empty());
+
+        // admins aren't approvers, but may decide anyway
+        let rsvp = manager
+            .as_admin()
+            .approve(rsvps[2].id, "root".into(), "".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            rsvp.status,
+            luckychacha_reservation_abi::ReservationStatus::Confirmed as i32
         );
     }
 
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn query_reservations_should_work() {
-        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
-        assert!(rsvp.id > 0);
+    async fn no_shows_should_be_released() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        for id in ["desk-1", "desk-2", "desk-3"] {
+            let desk = Resource::new(id).with_check_in_window(chrono::Duration::minutes(10));
+            manager.put_resource(desk).await.unwrap();
+        }
+        let at = |minutes: i64| (Utc::now() + chrono::Duration::minutes(minutes)).into();
+        let book = |resource_id: &'static str, start: i64, end: i64| {
+            let manager = &manager;
+            async move {
+                let rsvp = Reservation::new_pending("alice", resource_id, at(start), at(end), "");
+                let rsvp = manager.reserve(rsvp).await.unwrap();
+                manager.change_status(rsvp.id).await.unwrap()
+            }
+        };
+        // nobody showed up within the window
+        let absent = book("desk-1", -30, 30).await;
+        // checked in
+        let present = book("desk-2", -30, 30).await;
+        // the window is still open
+        let late = book("desk-3", -5, 30).await;
 
-        let query = ReservationQueryBuilder::default()
-            .user_id("alice")
-            .start("2022-12-25T15:00:00+0800".parse::<Timestamp>().unwrap())
-            .end("2022-12-28T11:00:00+0800".parse::<Timestamp>().unwrap())
-            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
-            .build()
+        // the rest of desk-1 is waited for
+        let waiting = Reservation::new_pending("bob", "desk-1", at(10), at(20), "");
+        let entry = manager.join_waitlist(waiting).await.unwrap();
+        assert_eq!(entry.reservation.unwrap().id, 0);
+
+        let checked_in = manager.check_in(present.id).await.unwrap();
+        assert!(checked_in.checked_in_at.is_some());
+        // checking in again keeps the first check-in
+        assert_eq!(manager.check_in(present.id).await.unwrap(), checked_in);
+
+        let released = manager.release_no_shows().await.unwrap();
+        assert_eq!(released, vec![absent.clone()]);
+        assert_eq!(
+            manager.get(absent.id).await,
+            Err(Error::ReservationNotFound)
+        );
+        assert!(manager.get(late.id).await.is_ok());
+        assert_eq!(manager.count_no_shows("alice").await.unwrap(), 1);
+        assert_eq!(manager.count_no_shows("bob").await.unwrap(), 0);
+        let entries = manager
+            .list_waitlist(ListWaitlistRequest::new("desk-1", ""))
+            .await
             .unwrap();
-        let mut rx = manager.query(query).await;
-        assert_eq!(rx.recv().await, Some(Ok(rsvp.clone())));
-        assert_eq!(rx.recv().await, None);
+        assert!(entries.is_empty());
 
-        // if window is not in range, should return empty
-        let query = ReservationQueryBuilder::default()
-            .user_id("alice")
-            .start("2022-12-29T15:00:00+0800".parse::<Timestamp>().unwrap())
-            .end("2022-12-30T11:00:00+0800".parse::<Timestamp>().unwrap())
-            .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
-            .build()
+        // nothing left to release
+        assert!(manager.release_no_shows().await.unwrap().is_empty());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn check_in_should_only_be_open_around_confirmed_reservations() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let desk = Resource::new("desk").with_check_in_window(chrono::Duration::minutes(10));
+        manager.put_resource(desk).await.unwrap();
+        let at = |minutes: i64| (Utc::now() + chrono::Duration::minutes(minutes)).into();
+
+        let rsvp = manager
+            .reserve(Reservation::new_pending("alice", "desk", at(5), at(60), ""))
+            .await
             .unwrap();
-        let mut rsvps = manager.query(query).await;
-        assert_eq!(rsvps.recv().await, None);
+        let err = manager.check_in(rsvp.id).await.unwrap_err();
+        assert!(matches!(err, Error::CheckInNotAllowed(_)));
+        manager.change_status(rsvp.id).await.unwrap();
+        assert!(manager.check_in(rsvp.id).await.is_ok());
 
-        // if status is not in correct, should return empty
-        let query = ReservationQueryBuilder::default()
-            .user_id("alice")
-            .start("2022-12-25T15:00:00+0800".parse::<Timestamp>().unwrap())
-            .end("2022-12-28T11:00:00+0800".parse::<Timestamp>().unwrap())
-            .status(luckychacha_reservation_abi::ReservationStatus::Confirmed as i32)
-            .build()
+        let rsvp = manager
+            .reserve(Reservation::new_pending(
+                "alice",
+                "desk",
+                at(120),
+                at(180),
+                "",
+            ))
+            .await
             .unwrap();
-        let mut rsvps = manager.query(query).await;
-        assert_eq!(rsvps.recv().await, None);
+        manager.change_status(rsvp.id).await.unwrap();
+        let err = manager.check_in(rsvp.id).await.unwrap_err();
+        assert!(matches!(err, Error::CheckInNotAllowed(_)));
+    }
 
-        // change state to confirmed
-        let rsvp = manager.change_status(rsvp.id).await.unwrap();
-        let query = ReservationQueryBuilder::default()
-            .user_id("alice")
-            .start("2022-12-25T15:00:00+0800".parse::<Timestamp>().unwrap())
-            .end("2022-12-28T11:00:00+0800".parse::<Timestamp>().unwrap())
-            .status(luckychacha_reservation_abi::ReservationStatus::Confirmed as i32)
-            .build()
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn change_status_should_wait_for_resources_being_changed() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        manager.put_resource(Resource::new("lab")).await.unwrap();
+        let rsvp = manager
+            .reserve(Reservation::new_pending(
+                "alice",
+                "lab",
+                "2030-01-01T09:00:00Z".parse().unwrap(),
+                "2030-01-01T10:00:00Z".parse().unwrap(),
+                "",
+            ))
+            .await
             .unwrap();
-        let mut rsvps = manager.query(query).await;
-        assert_eq!(rsvps.recv().await, Some(Ok(rsvp)));
-        assert_eq!(rsvps.recv().await, None);
+
+        // the lab starts requiring approval while alice confirms
+        let mut tx = migrated_pool.begin().await.unwrap();
+        sqlx::query("UPDATE rsvp.resource SET requires_approval = TRUE WHERE id = 'lab'")
+            .execute(&mut tx)
+            .await
+            .unwrap();
+        let confirming = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.change_status(rsvp.id).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!confirming.is_finished());
+        tx.commit().await.unwrap();
+        assert_eq!(
+            confirming.await.unwrap(),
+            Err(Error::ApprovalRequired(rsvp.id))
+        );
     }
 
     #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
-    async fn filter_reservations_should_work() {
-        let (manager, rsvp) = make_alice_reservation(migrated_pool.clone()).await;
-        assert!(rsvp.id > 0);
+    async fn release_should_free_the_rest_of_the_window() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let at = |minutes: i64| Utc::now() + chrono::Duration::minutes(minutes);
+        let rsvp = manager
+            .reserve(Reservation::new_pending(
+                "alice",
+                "room-1",
+                at(-30).into(),
+                at(30).into(),
+                "",
+            ))
+            .await
+            .unwrap();
+        let waiting = Reservation::new_pending("bob", "room-1", at(10).into(), at(20).into(), "");
+        manager.join_waitlist(waiting).await.unwrap();
+
+        // the end has to be within the reservation
+        assert_eq!(
+            manager.release(rsvp.id, Some(at(-40))).await,
+            Err(Error::InvalidTime)
+        );
+        assert_eq!(
+            manager.release(rsvp.id, Some(at(40))).await,
+            Err(Error::InvalidTime)
+        );
+
+        let before = manager.get(rsvp.id).await.unwrap();
+        let released = manager.release(rsvp.id, None).await.unwrap();
+        assert_eq!(released.id, rsvp.id);
+        assert_eq!(released.start, before.start);
+        let end = convert_to_utc_time(released.end.unwrap());
+        assert!(end > at(-1) && end <= at(0));
+
+        // the new end goes through the change feed
+        let ops: Vec<String> = sqlx::query_scalar(
+            "SELECT op::text FROM rsvp.reservation_change WHERE reservation_id = $1 ORDER BY id",
+        )
+        .bind(rsvp.id)
+        .fetch_all(&migrated_pool)
+        .await
+        .unwrap();
+        assert_eq!(ops, vec!["create", "update"]);
+
+        let promoted = manager
+            .filter(
+                ReservationFilterBuilder::default()
+                    .user_id("bob")
+                    .build()
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .1;
+        assert_eq!(promoted.len(), 1);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn extend_should_report_no_extension_if_the_end_is_blocked_already() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let minutes = chrono::Duration::minutes;
+        let around_the_clock = BusinessHours {
+            timezone: "UTC".into(),
+            hours: (1..=7)
+                .map(|weekday| OpeningHours {
+                    weekday,
+                    open_minute: 0,
+                    close_minute: 24 * 60,
+                })
+                .collect(),
+            holidays: vec![],
+        };
+        let room = Resource::new("room").with_business_hours(around_the_clock);
+        manager.put_resource(room.clone()).await.unwrap();
+
+        let now = Utc::now().duration_trunc(minutes(1)).unwrap();
+        let book = |start: i64, end: i64| {
+            let manager = &manager;
+            async move {
+                let rsvp = Reservation::new_pending(
+                    "alice",
+                    "room",
+                    (now + minutes(start)).into(),
+                    (now + minutes(end)).into(),
+                    "",
+                );
+                manager.reserve(rsvp).await.unwrap()
+            }
+        };
+        book(-120, -40).await;
+        let running = book(-30, 30).await;
+        // cleaning takes longer now, after the earlier booking the room isn't free until 40
+        let room = room.with_buffers(chrono::Duration::zero(), minutes(80));
+        manager.put_resource(room).await.unwrap();
+
+        match manager.extend(running.id, minutes(10)).await.unwrap_err() {
+            Error::ExtensionConflict { max_extension, .. } => {
+                assert_eq!(max_extension, chrono::Duration::zero());
+            }
+            e => panic!("expect extension conflict, got {e:?}"),
+        }
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn extend_should_keep_the_start_and_report_the_longest_extension() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let minutes = chrono::Duration::minutes;
+        let policy = BookingPolicy {
+            max_duration: Some(convert_to_pb_duration(chrono::Duration::hours(3))),
+            ..Default::default()
+        };
+        let room = Resource::new("room")
+            .with_policy(policy)
+            .with_buffers(chrono::Duration::zero(), minutes(15));
+        manager.put_resource(room).await.unwrap();
+
+        let now = Utc::now().duration_trunc(minutes(1)).unwrap();
+        let book = |start: i64, end: i64| {
+            let manager = &manager;
+            async move {
+                let rsvp = Reservation::new_pending(
+                    "alice",
+                    "room",
+                    (now + minutes(start)).into(),
+                    (now + minutes(end)).into(),
+                    "",
+                );
+                manager.reserve(rsvp).await.unwrap()
+            }
+        };
+        let running = book(-30, 30).await;
+        let next = book(90, 120).await;
+        let over = book(-120, -60).await;
+        // extending doesn't make another active reservation
+        manager
+            .put_quota(Quota::active_reservations("active", 2))
+            .await
+            .unwrap();
+
+        let extended = manager.extend(running.id, minutes(30)).await.unwrap();
+        assert_eq!(extended.id, running.id);
+        assert_eq!(extended.start, running.start);
+        assert_eq!(
+            convert_to_utc_time(extended.end.unwrap()),
+            now + minutes(60)
+        );
+
+        // the next booking starts at 90, 15 minutes of cleaning have to fit in before it
+        let err = manager.extend(running.id, minutes(30)).await.unwrap_err();
+        match err {
+            Error::ExtensionConflict {
+                id,
+                conflict: ReservationConflictInfo::Parsed(conflict),
+                max_extension,
+            } => {
+                assert_eq!(id, running.id);
+                assert_eq!(conflict.old.start, now + minutes(90));
+                assert_eq!(max_extension, minutes(15));
+            }
+            e => panic!("expect extension conflict, got {e:?}"),
+        }
+        manager.extend(running.id, minutes(15)).await.unwrap();
+
+        // no longer than 3 hours
+        assert!(matches!(
+            manager.extend(running.id, minutes(120)).await,
+            Err(Error::PolicyViolation { rule, .. }) if rule == "max_duration"
+        ));
+
+        // only running reservations can be extended
+        assert_eq!(
+            manager.extend(next.id, minutes(10)).await,
+            Err(Error::InvalidTime)
+        );
+        assert_eq!(
+            manager.extend(over.id, minutes(10)).await,
+            Err(Error::InvalidTime)
+        );
+
+        // every new end goes through the change feed
+        let ops: Vec<String> = sqlx::query_scalar(
+            "SELECT op::text FROM rsvp.reservation_change WHERE reservation_id = $1 ORDER BY id",
+        )
+        .bind(running.id)
+        .fetch_all(&migrated_pool)
+        .await
+        .unwrap();
+        assert_eq!(ops, vec!["create", "update", "update"]);
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn tenants_should_only_see_their_own_data() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        let sales = manager.for_tenant("sales");
+        let support = manager.for_tenant("support");
+
+        // the same resource id is a different resource in every tenant
+        let room = Resource::new("room-1").with_attribute("seats", 10);
+        let mut stored = sales.put_resource(room.clone()).await.unwrap();
+        assert_eq!(stored.tenant_id, "sales");
+        stored.tenant_id.clear();
+        assert_eq!(stored, room);
+        support
+            .put_resource(Resource::new("room-1").with_attribute("seats", 4))
+            .await
+            .unwrap();
+        assert_eq!(
+            sales.get_resource("room-1").await.unwrap().attributes["seats"],
+            10.into()
+        );
+        assert_eq!(
+            manager.get_resource("room-1").await.unwrap_err(),
+            Error::ResourceNotFound("room-1".into())
+        );
+
+        // and can be booked in the same window
+        let rsvp = || {
+            Reservation::new_pending(
+                "alice",
+                "room-1",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-25T16:00:00-0700".parse().unwrap(),
+                "",
+            )
+        };
+        let booked = sales.reserve(rsvp()).await.unwrap();
+        assert_eq!(booked.tenant_id, "sales");
+        let other = support.reserve(rsvp()).await.unwrap();
+        assert!(matches!(
+            sales.reserve(rsvp()).await,
+            Err(Error::ConflictReservation(_))
+        ));
+
+        assert_eq!(sales.get(booked.id).await.unwrap(), booked);
+        assert_eq!(
+            sales.get(other.id).await.unwrap_err(),
+            Error::ReservationNotFound
+        );
+        assert_eq!(
+            sales.delete(other.id).await.unwrap_err(),
+            Error::ReservationNotFound
+        );
 
         let filter = ReservationFilterBuilder::default()
+            .user_id("alice")
+            .build()
+            .unwrap();
+        let (_, rsvps) = support.filter(filter.clone()).await.unwrap();
+        assert_eq!(rsvps, vec![other.clone()]);
+        let (_, rsvps) = manager.filter(filter).await.unwrap();
+        assert!(rsvps.is_empty());
+        let query = ReservationQueryBuilder::default()
             .user_id("alice")
             .status(luckychacha_reservation_abi::ReservationStatus::Pending as i32)
             .build()
             .unwrap();
-        let (pager, rsvps) = manager.filter(filter).await.unwrap();
-        assert_eq!(pager.prev, None);
-        assert_eq!(pager.next, None);
+        let mut rx = sales.query(query).await;
+        assert_eq!(rx.recv().await, Some(Ok(booked)));
+        assert_eq!(rx.recv().await, None);
 
-        assert_eq!(rsvps.len(), 1);
-        assert_eq!(rsvps[0], rsvp);
+        sales
+            .put_quota(Quota::active_reservations("one-at-a-time", 1))
+            .await
+            .unwrap();
+        assert!(support.list_quotas().await.unwrap().is_empty());
+    }
+
+    #[sqlx_database_tester::test(pool(variable = "migrated_pool", migrations = "../migrations"))]
+    async fn is_healthy_should_tell_whether_the_db_answers() {
+        let manager = ReservationManager::new(migrated_pool.clone());
+        assert!(manager.is_healthy(Duration::from_secs(1)).await);
+        manager.close().await;
+        assert!(!manager.is_healthy(Duration::from_secs(1)).await);
     }
 
     // luckychacha reservation template
@@ -439,4 +3277,18 @@ mod tests {
         let rsvp = manager.reserve(rsvp).await.unwrap();
         (manager, rsvp)
     }
+
+    /// reserve `ixia-test-0` to `ixia-test-{n - 1}` for alice at the same time.
+    async fn make_reservations(manager: &ReservationManager, n: usize, note: &str) {
+        for i in 0..n {
+            let rsvp = Reservation::new_pending(
+                "alice",
+                format!("ixia-test-{i}"),
+                "2022-12-25T15:00:00+0800".parse().unwrap(),
+                "2022-12-28T11:00:00+0800".parse().unwrap(),
+                note,
+            );
+            manager.reserve(rsvp).await.unwrap();
+        }
+    }
 }
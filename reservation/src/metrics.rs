@@ -0,0 +1,69 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// how a query stream ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryOutcome {
+    /// every matching row (up to the limit) was sent
+    Completed,
+    /// the client went away, or the manager was closed, before all rows were sent
+    Cancelled,
+    /// the database returned an error
+    Failed,
+}
+
+/// metrics of a single query stream, recorded when the stream ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryStreamStats {
+    pub rows: u64,
+    pub elapsed: Duration,
+    pub outcome: QueryOutcome,
+}
+
+/// counters over all query streams of a manager.
+#[derive(Debug, Default)]
+pub struct QueryMetrics {
+    active: AtomicU64,
+    completed: AtomicU64,
+    cancelled: AtomicU64,
+    failed: AtomicU64,
+    rows: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryMetricsSnapshot {
+    pub active: u64,
+    pub completed: u64,
+    pub cancelled: u64,
+    pub failed: u64,
+    pub rows: u64,
+}
+
+impl QueryMetrics {
+    pub fn stream_started(&self) {
+        self.active.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stream_finished(&self, stats: &QueryStreamStats) {
+        self.active.fetch_sub(1, Ordering::Relaxed);
+        self.rows.fetch_add(stats.rows, Ordering::Relaxed);
+        let counter = match stats.outcome {
+            QueryOutcome::Completed => &self.completed,
+            QueryOutcome::Cancelled => &self.cancelled,
+            QueryOutcome::Failed => &self.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> QueryMetricsSnapshot {
+        QueryMetricsSnapshot {
+            active: self.active.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            cancelled: self.cancelled.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            rows: self.rows.load(Ordering::Relaxed),
+        }
+    }
+}
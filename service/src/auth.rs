@@ -0,0 +1,395 @@
+// tonic interceptors have to fail with a Status, however large it is
+#![allow(clippy::result_large_err)]
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{anyhow, Context};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use luckychacha_reservation_abi::{AuthConfig, JwtAlgorithm};
+use serde::Deserialize;
+use simple_asn1::{from_der, oid, ASN1Block};
+use tonic::{Request, Status};
+
+/// metadata naming the tenant of a request, only trusted if authentication is off.
+pub const TENANT_HEADER: &str = "x-tenant-id";
+
+/// the authenticated caller, put into the extensions of every request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub user_id: String,
+    pub roles: Vec<String>,
+    /// the default tenant if the token doesn't name one.
+    pub tenant_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    tenant: String,
+}
+
+#[derive(Clone)]
+enum Keys {
+    Single(DecodingKey),
+    /// keys of a JWKS by their id.
+    Set(HashMap<String, DecodingKey>),
+}
+
+/// verifies the bearer tokens of callers, rejecting requests without a valid one.
+#[derive(Clone)]
+pub struct Authenticator {
+    keys: Arc<Keys>,
+    validation: Validation,
+}
+
+impl Authenticator {
+    pub fn from_config(config: &AuthConfig) -> Result<Self, anyhow::Error> {
+        let (algorithm, keys) = match config.algorithm {
+            JwtAlgorithm::HS256 => {
+                if config.secret.is_empty() {
+                    return Err(anyhow!("HS256 tokens need a secret"));
+                }
+                let key = DecodingKey::from_secret(config.secret.as_bytes());
+                (Algorithm::HS256, Keys::Single(key))
+            }
+            JwtAlgorithm::RS256 if !config.jwks.is_empty() => {
+                let f = std::fs::read_to_string(&config.jwks)
+                    .with_context(|| format!("failed to read jwks {}", config.jwks))?;
+                let set: JwkSet = serde_json::from_str(&f)?;
+                let mut keys = HashMap::new();
+                for jwk in set.keys.iter() {
+                    if let Some(kid) = jwk.common.key_id.as_ref() {
+                        keys.insert(kid.clone(), DecodingKey::from_jwk(jwk)?);
+                    }
+                }
+                (Algorithm::RS256, Keys::Set(keys))
+            }
+            JwtAlgorithm::RS256 => {
+                let pem = std::fs::read(&config.public_key)
+                    .with_context(|| format!("failed to read public key {}", config.public_key))?;
+                (
+                    Algorithm::RS256,
+                    Keys::Single(DecodingKey::from_rsa_pem(&pem)?),
+                )
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        if !config.issuer.is_empty() {
+            validation.set_issuer(&[&config.issuer]);
+        }
+        if !config.audience.is_empty() {
+            validation.set_audience(&[&config.audience]);
+        }
+        Ok(Self {
+            keys: Arc::new(keys),
+            validation,
+        })
+    }
+
+    pub fn authenticate(&self, token: &str) -> Result<Principal, Status> {
+        let invalid =
+            |e: jsonwebtoken::errors::Error| Status::unauthenticated(format!("invalid token: {e}"));
+        let key = match self.keys.as_ref() {
+            Keys::Single(key) => key,
+            Keys::Set(keys) => {
+                let kid = decode_header(token)
+                    .map_err(invalid)?
+                    .kid
+                    .unwrap_or_default();
+                keys.get(&kid)
+                    .ok_or_else(|| Status::unauthenticated(format!("unknown key id {kid:?}")))?
+            }
+        };
+        let claims = decode::<Claims>(token, key, &self.validation)
+            .map_err(invalid)?
+            .claims;
+        if claims.sub.is_empty() {
+            return Err(Status::unauthenticated("token has no subject"));
+        }
+        Ok(Principal {
+            user_id: claims.sub,
+            roles: claims.roles,
+            tenant_id: claims.tenant,
+        })
+    }
+
+    /// put the caller into the extensions of the request, see `tonic::service::Interceptor`.
+    pub fn intercept(&self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("missing bearer token"))?;
+        let principal = self.authenticate(token)?;
+        request.extensions_mut().insert(principal);
+        Ok(request)
+    }
+}
+
+/// identify the caller by their client certificate, or by their bearer token which wins if
+/// there are both. Requests without either are only let through if tokens aren't required.
+pub(crate) fn identify(
+    auth: Option<&Authenticator>,
+    mut request: Request<()>,
+) -> Result<Request<()>, Status> {
+    if let Some(caller) = client_principal(&request) {
+        request.extensions_mut().insert(caller);
+        if !request.metadata().contains_key("authorization") {
+            return Ok(request);
+        }
+    }
+    match auth {
+        Some(auth) => auth.intercept(request),
+        None => Ok(request),
+    }
+}
+
+/// the caller presenting a certificate signed by the client CA, see `TlsConfig::client_ca`.
+fn client_principal<T>(request: &Request<T>) -> Option<Principal> {
+    let certs = request.peer_certs()?;
+    let (user_id, tenant_id) = subject(certs.first()?.get_ref())?;
+    Some(Principal {
+        user_id,
+        roles: vec![],
+        tenant_id,
+    })
+}
+
+/// common name and organization of the subject of a DER encoded certificate.
+fn subject(der: &[u8]) -> Option<(String, String)> {
+    let Some(ASN1Block::Sequence(_, cert)) = from_der(der).ok()?.into_iter().next() else {
+        return None;
+    };
+    let Some(ASN1Block::Sequence(_, tbs)) = cert.into_iter().next() else {
+        return None;
+    };
+    // the version is optional, then come serial, signature, issuer, validity and subject
+    let skip = usize::from(matches!(tbs.first(), Some(ASN1Block::Explicit(..))));
+    let Some(ASN1Block::Sequence(_, names)) = tbs.into_iter().nth(skip + 4) else {
+        return None;
+    };
+
+    let (mut cn, mut org) = (String::new(), String::new());
+    for name in names {
+        let ASN1Block::Set(_, attrs) = name else {
+            continue;
+        };
+        for attr in attrs {
+            let ASN1Block::Sequence(_, pair) = attr else {
+                continue;
+            };
+            let value = match pair.get(1) {
+                Some(ASN1Block::UTF8String(_, v))
+                | Some(ASN1Block::PrintableString(_, v))
+                | Some(ASN1Block::IA5String(_, v)) => v.clone(),
+                _ => continue,
+            };
+            match pair.first() {
+                Some(ASN1Block::ObjectIdentifier(_, id)) if *id == oid!(2, 5, 4, 3) => cn = value,
+                Some(ASN1Block::ObjectIdentifier(_, id)) if *id == oid!(2, 5, 4, 10) => org = value,
+                _ => {}
+            }
+        }
+    }
+    (!cn.is_empty()).then_some((cn, org))
+}
+
+/// the authenticated caller, None if authentication is off and what the client claims has
+/// to be trusted.
+pub(crate) fn principal<T>(request: &Request<T>) -> Option<Principal> {
+    request.extensions().get::<Principal>().cloned()
+}
+
+/// the tenant the request is scoped to: the one of the caller's token, or the one of the
+/// `x-tenant-id` header if authentication is off. Callers can't pick another tenant than the
+/// one of their token.
+pub(crate) fn tenant<T>(request: &Request<T>) -> Result<String, Status> {
+    let header = match request.metadata().get(TENANT_HEADER) {
+        Some(v) => Some(
+            v.to_str()
+                .map_err(|_| Status::invalid_argument("invalid tenant id"))?,
+        ),
+        None => None,
+    };
+    let tenant = match (principal(request), header) {
+        (Some(caller), Some(tenant)) if tenant != caller.tenant_id => {
+            return Err(Status::permission_denied(format!(
+                "{} may not access tenant {tenant}",
+                caller.user_id
+            )))
+        }
+        (Some(caller), _) => caller.tenant_id,
+        (None, tenant) => tenant.unwrap_or_default().to_string(),
+    };
+    if tenant.len() > 64 {
+        return Err(Status::invalid_argument(format!(
+            "invalid tenant id {tenant}"
+        )));
+    }
+    Ok(tenant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct TestClaims<'a> {
+        sub: &'a str,
+        exp: u64,
+        roles: Vec<&'a str>,
+        tenant: &'a str,
+    }
+
+    fn claims(sub: &str) -> TestClaims<'_> {
+        TestClaims {
+            sub,
+            exp: jsonwebtoken::get_current_timestamp() + 3600,
+            roles: vec!["admin"],
+            tenant: "sales",
+        }
+    }
+
+    fn hs256_config(secret: &str) -> AuthConfig {
+        AuthConfig {
+            algorithm: JwtAlgorithm::HS256,
+            secret: secret.to_string(),
+            public_key: String::new(),
+            jwks: String::new(),
+            issuer: String::new(),
+            audience: String::new(),
+        }
+    }
+
+    fn rs256_token(sub: &str, kid: Option<&str>) -> String {
+        let pem = std::fs::read("fixtures/jwt_rs256.pem").unwrap();
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = kid.map(|v| v.to_string());
+        encode(
+            &header,
+            &claims(sub),
+            &EncodingKey::from_rsa_pem(&pem).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn bearer(token: &str) -> Request<()> {
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {token}").parse().unwrap());
+        request
+    }
+
+    #[test]
+    fn hs256_tokens_should_be_verified() {
+        let auth = Authenticator::from_config(&hs256_config("secret")).unwrap();
+        let token = encode(
+            &Header::default(),
+            &claims("alice"),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        let request = auth.intercept(bearer(&token)).unwrap();
+        assert_eq!(
+            request.extensions().get::<Principal>(),
+            Some(&Principal {
+                user_id: "alice".into(),
+                roles: vec!["admin".into()],
+                tenant_id: "sales".into(),
+            })
+        );
+        assert_eq!(principal(&request).unwrap().user_id, "alice");
+        assert_eq!(tenant(&request).unwrap(), "sales");
+
+        let forged = encode(
+            &Header::default(),
+            &claims("alice"),
+            &EncodingKey::from_secret(b"guessed"),
+        )
+        .unwrap();
+        let status = auth.intercept(bearer(&forged)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        let status = auth.intercept(Request::new(())).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    #[test]
+    fn rs256_tokens_should_be_verified_with_public_key_or_jwks() {
+        let config = AuthConfig {
+            algorithm: JwtAlgorithm::RS256,
+            public_key: "fixtures/jwt_rs256.pub.pem".into(),
+            ..hs256_config("")
+        };
+        let auth = Authenticator::from_config(&config).unwrap();
+        assert_eq!(
+            auth.authenticate(&rs256_token("bob", None))
+                .unwrap()
+                .user_id,
+            "bob"
+        );
+
+        let config = AuthConfig {
+            jwks: "fixtures/jwks.json".into(),
+            ..config
+        };
+        let auth = Authenticator::from_config(&config).unwrap();
+        assert_eq!(
+            auth.authenticate(&rs256_token("bob", Some("test-key")))
+                .unwrap()
+                .user_id,
+            "bob"
+        );
+        assert!(auth
+            .authenticate(&rs256_token("bob", Some("other-key")))
+            .is_err());
+    }
+
+    #[test]
+    fn tenant_should_be_taken_from_token_or_header() {
+        let with_header = |mut request: Request<()>, tenant: &str| {
+            request
+                .metadata_mut()
+                .insert(TENANT_HEADER, tenant.parse().unwrap());
+            request
+        };
+        assert_eq!(tenant(&Request::new(())).unwrap(), "");
+        assert_eq!(
+            tenant(&with_header(Request::new(()), "support")).unwrap(),
+            "support"
+        );
+
+        // the token decides once authenticated
+        let auth = Authenticator::from_config(&hs256_config("secret")).unwrap();
+        let token = encode(
+            &Header::default(),
+            &claims("alice"),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        let request = auth
+            .intercept(with_header(bearer(&token), "sales"))
+            .unwrap();
+        assert_eq!(tenant(&request).unwrap(), "sales");
+        let request = auth
+            .intercept(with_header(bearer(&token), "support"))
+            .unwrap();
+        assert_eq!(
+            tenant(&request).unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn hs256_without_secret_should_be_rejected() {
+        assert!(Authenticator::from_config(&hs256_config("")).is_err());
+    }
+}
@@ -1,19 +1,43 @@
+mod auth;
+mod limit;
+mod policy;
 mod service;
 #[cfg(test)]
 mod test_utils;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use futures::Stream;
 use luckychacha_reservation::ReservationManager;
 use luckychacha_reservation_abi::{
-    reservation_service_server::ReservationServiceServer, Config, Reservation,
+    reservation_service_server::ReservationServiceServer, Config, Reservation, TlsConfig,
+    FILE_DESCRIPTOR_SET,
 };
-use std::pin::Pin;
-use tokio::sync::mpsc;
-use tonic::{transport::Server, Status};
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::sync::{mpsc, oneshot};
+use tonic::{
+    service::interceptor,
+    transport::{Certificate, Identity, NamedService, Server, ServerTlsConfig},
+    Request, Status,
+};
+use tonic_health::{
+    server::{health_reporter, HealthReporter},
+    ServingStatus,
+};
+use tower::ServiceBuilder;
+
+pub use auth::{Authenticator, Principal, TENANT_HEADER};
+pub use limit::RateLimitLayer;
+pub use policy::{Policy, ADMIN};
+
+/// how often the database is pinged to tell load balancers whether we're serving.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// how long database connections still in use are waited for once the calls are cut off.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct RsvpService {
-    manager: ReservationManager,
+    manager: Arc<ReservationManager>,
+    policy: Policy,
 }
 
 type ReservationStream = Pin<Box<dyn Stream<Item = Result<Reservation, Status>> + Send>>;
@@ -22,13 +46,130 @@ pub struct TonicReceiverStream<T> {
     inner: mpsc::Receiver<Result<T, luckychacha_reservation_abi::Error>>,
 }
 
+/// serve until SIGTERM or SIGINT, see `start_server_with_shutdown`.
 pub async fn start_server(config: &Config) -> Result<(), anyhow::Error> {
+    start_server_with_shutdown(config, shutdown_signal()).await
+}
+
+/// serve until `signal` completes. No calls are accepted after that, and the ones in flight get
+/// `drain_period` to finish before they are cut off and the database connections are closed.
+/// Connections outliving the server, e.g. of streams nobody reads, end with `Unavailable`.
+#[allow(clippy::result_large_err)]
+pub async fn start_server_with_shutdown(
+    config: &Config,
+    signal: impl Future<Output = ()>,
+) -> Result<(), anyhow::Error> {
     let addr = format!("{}:{}", config.server.host, config.server.port).parse()?;
     let svc = RsvpService::from_config(config).await?;
-    let svc = ReservationServiceServer::new(svc);
+    let manager = svc.manager.clone();
+    let no_shows = manager
+        .clone()
+        .spawn_no_show_release(Duration::from_secs(config.no_show.interval));
+    let auth = config
+        .auth
+        .as_ref()
+        .map(Authenticator::from_config)
+        .transpose()?;
+    // callers are identified first, so that they can be rate limited. Health checks and
+    // reflection are open to everyone.
+    let rsvp = ServiceBuilder::new()
+        .layer(interceptor(move |request: Request<()>| {
+            auth::identify(auth.as_ref(), request)
+        }))
+        .layer(RateLimitLayer::from_config(&config.rate_limit)?)
+        .service(ReservationServiceServer::new(svc));
+
+    let (mut reporter, health) = health_reporter();
+    let health_checks = tokio::spawn(report_health(manager.clone(), reporter.clone()));
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(
+            tonic_health::proto::GRPC_HEALTH_V1_FILE_DESCRIPTOR_SET,
+        )
+        .build()?;
+
+    let mut server = Server::builder();
+    if let Some(tls) = config.server.tls.as_ref() {
+        server = server.tls_config(tls_config(tls)?)?;
+    }
 
     println!("Listening on: {addr}");
 
-    Server::builder().add_service(svc).serve(addr).await?;
+    let (draining, drain) = oneshot::channel();
+    let serve = server
+        .add_service(rsvp)
+        .add_service(health)
+        .add_service(reflection)
+        .serve_with_shutdown(addr, async {
+            signal.await;
+            println!("Shutting down, draining calls in flight");
+            // those watching the health learn about it while their streams drain
+            health_checks.abort();
+            for service in ["", ReservationServiceServer::<RsvpService>::NAME] {
+                reporter
+                    .set_service_status(service, ServingStatus::NotServing)
+                    .await;
+            }
+            let _ = draining.send(());
+        });
+    let drain_period = Duration::from_secs(config.server.drain_period);
+    tokio::select! {
+        ret = serve => ret?,
+        _ = async {
+            let _ = drain.await;
+            tokio::time::sleep(drain_period).await;
+        } => println!("Drain period is over, cutting off the remaining calls"),
+    }
+
+    no_shows.abort();
+    // calls are served by tasks of their connections, dropping the server doesn't stop them,
+    // but closing the manager stops their streams
+    if tokio::time::timeout(CLOSE_TIMEOUT, manager.close())
+        .await
+        .is_err()
+    {
+        println!("Database connections are still in use, leaving them behind");
+    }
     Ok(())
 }
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// tell whether we're serving by whether the database answers, for the service and the server.
+async fn report_health(manager: Arc<ReservationManager>, mut reporter: HealthReporter) {
+    let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let status = if manager.is_healthy(HEALTH_CHECK_INTERVAL).await {
+            ServingStatus::Serving
+        } else {
+            ServingStatus::NotServing
+        };
+        for service in ["", ReservationServiceServer::<RsvpService>::NAME] {
+            reporter.set_service_status(service, status).await;
+        }
+    }
+}
+
+fn tls_config(config: &TlsConfig) -> Result<ServerTlsConfig, anyhow::Error> {
+    let read = |path: &str| std::fs::read(path).with_context(|| format!("failed to read {path}"));
+    let mut tls = ServerTlsConfig::new()
+        .identity(Identity::from_pem(read(&config.cert)?, read(&config.key)?));
+    if !config.client_ca.is_empty() {
+        tls = tls.client_ca_root(Certificate::from_pem(read(&config.client_ca)?));
+    }
+    Ok(tls)
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use futures::future::{ready, Either, Ready};
+use luckychacha_reservation_abi::{RateLimit, RateLimitConfig};
+use tonic::{
+    body::BoxBody,
+    codegen::http,
+    transport::{
+        server::{TcpConnectInfo, TlsConnectInfo},
+        NamedService,
+    },
+    Status,
+};
+use tower::{Layer, Service};
+
+use crate::Principal;
+
+/// how often buckets of callers who are back to a full one are dropped.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// token buckets of every caller and RPC.
+struct Limiter {
+    default: Option<RateLimit>,
+    methods: BTreeMap<String, RateLimit>,
+    buckets: Mutex<Buckets>,
+}
+
+struct Buckets {
+    by_caller: HashMap<(String, String), Bucket>,
+    swept: Instant,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Limiter {
+    fn limit(&self, method: &str) -> Option<RateLimit> {
+        self.methods.get(method).or(self.default.as_ref()).copied()
+    }
+
+    /// take a token of the caller's bucket of the RPC, or tell how long until there is one.
+    fn acquire(&self, key: &str, method: &str, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = self.limit(method) else {
+            return Ok(());
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+        // full buckets are the same as none, sweeping them now and then keeps the map small
+        // without scanning it on every call
+        if now.saturating_duration_since(buckets.swept) >= SWEEP_INTERVAL {
+            buckets
+                .by_caller
+                .retain(|(_, method), bucket| match self.limit(method) {
+                    Some(limit) => bucket.refilled(limit, now) < burst(limit),
+                    None => false,
+                });
+            buckets.swept = now;
+        }
+        let bucket = buckets
+            .by_caller
+            .entry((key.to_string(), method.to_string()))
+            .or_insert(Bucket {
+                tokens: burst(limit),
+                updated: now,
+            });
+        bucket.tokens = bucket.refilled(limit, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / limit.rate as f64,
+            ))
+        }
+    }
+}
+
+impl Bucket {
+    fn refilled(&self, limit: RateLimit, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        (self.tokens + elapsed * limit.rate as f64).min(burst(limit))
+    }
+}
+
+fn burst(limit: RateLimit) -> f64 {
+    limit.burst.max(limit.rate) as f64
+}
+
+/// rejects calls of callers over their limit with `ResourceExhausted`, see `RateLimitConfig`.
+/// Callers are told apart by principal, or by address if they aren't authenticated, so this has
+/// to be layered below the authentication.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<Limiter>,
+}
+
+impl RateLimitLayer {
+    pub fn from_config(config: &RateLimitConfig) -> Result<Self, anyhow::Error> {
+        let mut limits = config.default.iter().chain(config.methods.values());
+        if limits.any(|limit| limit.rate == 0) {
+            return Err(anyhow!(
+                "rate limits have to allow at least one call per second"
+            ));
+        }
+        Ok(Self {
+            limiter: Arc::new(Limiter {
+                default: config.default,
+                methods: config.methods.clone(),
+                buckets: Mutex::new(Buckets {
+                    by_caller: HashMap::new(),
+                    swept: Instant::now(),
+                }),
+            }),
+        })
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimited<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimited {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimited<S> {
+    inner: S,
+    limiter: Arc<Limiter>,
+}
+
+// required to use `RateLimited` with `Router`
+impl<S: NamedService> NamedService for RateLimited<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+impl<S, B> Service<http::Request<B>> for RateLimited<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Self::Response, Self::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        // paths are `/<package>.<service>/<method>`
+        let method = request.uri().path().rsplit('/').next().unwrap_or_default();
+        match self
+            .limiter
+            .acquire(&caller(&request), method, Instant::now())
+        {
+            Ok(()) => Either::Right(self.inner.call(request)),
+            Err(wait) => {
+                let mut status = Status::resource_exhausted(format!("too many {method} calls"));
+                // whole seconds as in HTTP, rounded up so that retrying then succeeds
+                let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+                status
+                    .metadata_mut()
+                    .insert("retry-after", seconds.to_string().parse().unwrap());
+                Either::Left(ready(Ok(status.to_http())))
+            }
+        }
+    }
+}
+
+/// the principal of the call, or the address it comes from.
+fn caller<B>(request: &http::Request<B>) -> String {
+    let extensions = request.extensions();
+    if let Some(caller) = extensions.get::<Principal>() {
+        return format!("user:{}/{}", caller.tenant_id, caller.user_id);
+    }
+    let addr = extensions
+        .get::<TcpConnectInfo>()
+        .or_else(|| {
+            extensions
+                .get::<TlsConnectInfo<TcpConnectInfo>>()
+                .map(|info| info.get_ref())
+        })
+        .and_then(|info| info.remote_addr());
+    match addr {
+        Some(addr) => format!("peer:{}", addr.ip()),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(config: RateLimitConfig) -> Arc<Limiter> {
+        RateLimitLayer::from_config(&config).unwrap().limiter
+    }
+
+    #[test]
+    fn calls_over_the_limit_should_wait_for_the_bucket_to_refill() {
+        let limiter = limiter(RateLimitConfig {
+            default: None,
+            methods: BTreeMap::from([("reserve".into(), RateLimit { rate: 2, burst: 3 })]),
+        });
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.acquire("alice", "reserve", now).is_ok());
+        }
+        assert_eq!(
+            limiter.acquire("alice", "reserve", now),
+            Err(Duration::from_millis(500))
+        );
+        // others and other RPCs aren't affected
+        assert!(limiter.acquire("bob", "reserve", now).is_ok());
+        assert!(limiter.acquire("alice", "query", now).is_ok());
+
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.acquire("alice", "reserve", later).is_ok());
+        assert!(limiter.acquire("alice", "reserve", later).is_err());
+    }
+
+    #[test]
+    fn default_limit_should_apply_to_unlisted_rpcs() {
+        let limiter = limiter(RateLimitConfig {
+            default: Some(RateLimit { rate: 1, burst: 0 }),
+            methods: BTreeMap::from([("get".into(), RateLimit { rate: 5, burst: 0 })]),
+        });
+        let now = Instant::now();
+        assert!(limiter.acquire("alice", "query", now).is_ok());
+        assert_eq!(
+            limiter.acquire("alice", "query", now),
+            Err(Duration::from_secs(1))
+        );
+        for _ in 0..5 {
+            assert!(limiter.acquire("alice", "get", now).is_ok());
+        }
+        assert!(limiter.acquire("alice", "get", now).is_err());
+    }
+
+    #[test]
+    fn full_buckets_should_be_swept() {
+        let limiter = limiter(RateLimitConfig {
+            default: Some(RateLimit { rate: 1, burst: 1 }),
+            methods: BTreeMap::new(),
+        });
+        let now = Instant::now();
+        assert!(limiter.acquire("alice", "get", now).is_ok());
+        assert!(limiter.acquire("bob", "get", now).is_ok());
+        assert_eq!(limiter.buckets.lock().unwrap().by_caller.len(), 2);
+
+        // both are full again a minute later and swept, only the bucket bob takes from is left
+        let later = now + SWEEP_INTERVAL;
+        assert!(limiter.acquire("bob", "get", later).is_ok());
+        assert!(limiter.acquire("bob", "get", later).is_err());
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.by_caller.len(), 1);
+        assert!(buckets
+            .by_caller
+            .contains_key(&("bob".into(), "get".into())));
+    }
+
+    #[test]
+    fn zero_rate_should_be_rejected() {
+        let config = RateLimitConfig {
+            default: Some(RateLimit { rate: 0, burst: 1 }),
+            methods: BTreeMap::new(),
+        };
+        assert!(RateLimitLayer::from_config(&config).is_err());
+    }
+}
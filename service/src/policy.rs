@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use luckychacha_reservation_abi::{Error, RbacConfig};
+
+use crate::Principal;
+
+/// may do anything.
+pub const ADMIN: &str = "admin";
+
+/// who may do what. Users manage their own reservations, owners of a resource (its
+/// approvers) the reservations of the resource, and admins everything. Every check passes
+/// without a caller, i.e. if authentication is off.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    grants: HashMap<String, Vec<String>>,
+}
+
+impl Policy {
+    pub fn from_config(config: &RbacConfig) -> Self {
+        Self {
+            grants: config
+                .grants
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+
+    /// roles of the token and the ones granted by config.
+    pub fn has_role(&self, caller: &Principal, role: &str) -> bool {
+        let granted = self.grants.get(&caller.user_id).into_iter().flatten();
+        caller.roles.iter().chain(granted).any(|v| v == role)
+    }
+
+    /// only admins.
+    pub fn check_admin(&self, caller: Option<&Principal>, action: &str) -> Result<(), Error> {
+        self.check(caller, action, |_| false)
+    }
+
+    /// the user themselves and admins, e.g. for the preemptions of a user.
+    pub fn check_user(
+        &self,
+        caller: Option<&Principal>,
+        user_id: &str,
+        action: &str,
+    ) -> Result<(), Error> {
+        self.check(caller, action, |caller| caller.user_id == user_id)
+    }
+
+    /// owners of the resource and admins.
+    pub fn check_owner(
+        &self,
+        caller: Option<&Principal>,
+        owners: &[String],
+        action: &str,
+    ) -> Result<(), Error> {
+        self.check(caller, action, |caller| owners.contains(&caller.user_id))
+    }
+
+    /// the user of a reservation, owners of its resource and admins.
+    pub fn check_reservation(
+        &self,
+        caller: Option<&Principal>,
+        user_id: &str,
+        owners: &[String],
+        action: &str,
+    ) -> Result<(), Error> {
+        self.check(caller, action, |caller| {
+            caller.user_id == user_id || owners.contains(&caller.user_id)
+        })
+    }
+
+    /// reservations of a user, or of everyone if empty, on a resource, or on all of them if
+    /// empty. Users may list their own ones, owners the ones on their resource.
+    pub fn check_listing(
+        &self,
+        caller: Option<&Principal>,
+        user_id: &str,
+        resource_id: &str,
+        owners: &[String],
+    ) -> Result<(), Error> {
+        let action = match (user_id, resource_id) {
+            ("", "") => "list all reservations".to_string(),
+            ("", rid) => format!("list reservations of {rid}"),
+            (uid, _) => format!("list reservations of {uid}"),
+        };
+        self.check(caller, &action, |caller| {
+            caller.user_id == user_id
+                || (!resource_id.is_empty() && owners.contains(&caller.user_id))
+        })
+    }
+
+    fn check(
+        &self,
+        caller: Option<&Principal>,
+        action: &str,
+        allowed: impl FnOnce(&Principal) -> bool,
+    ) -> Result<(), Error> {
+        let Some(caller) = caller else {
+            return Ok(());
+        };
+        if self.has_role(caller, ADMIN) || allowed(caller) {
+            return Ok(());
+        }
+        Err(Error::PermissionDenied(format!(
+            "{} may not {action}",
+            caller.user_id
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn principal(user_id: &str, roles: &[&str]) -> Principal {
+        Principal {
+            user_id: user_id.into(),
+            roles: roles.iter().map(|v| v.to_string()).collect(),
+            tenant_id: String::new(),
+        }
+    }
+
+    fn policy() -> Policy {
+        Policy::from_config(&RbacConfig {
+            grants: BTreeMap::from([("root".to_string(), vec![ADMIN.to_string()])]),
+        })
+    }
+
+    #[test]
+    fn users_should_only_manage_their_own_reservations() {
+        let policy = policy();
+        let alice = principal("alice", &[]);
+        let owners = vec!["olivia".to_string()];
+
+        assert!(policy
+            .check_reservation(Some(&alice), "alice", &owners, "cancel reservation 1")
+            .is_ok());
+        assert_eq!(
+            policy.check_reservation(Some(&alice), "bob", &owners, "cancel reservation 2"),
+            Err(Error::PermissionDenied(
+                "alice may not cancel reservation 2".into()
+            ))
+        );
+        // owners manage the reservations of their resources
+        let olivia = principal("olivia", &[]);
+        assert!(policy
+            .check_reservation(Some(&olivia), "bob", &owners, "cancel reservation 2")
+            .is_ok());
+        assert!(policy.check_user(Some(&alice), "bob", "list").is_err());
+    }
+
+    #[test]
+    fn admins_should_do_anything() {
+        let policy = policy();
+        // by token or by config
+        for admin in [principal("alice", &[ADMIN]), principal("root", &[])] {
+            assert!(policy.check_admin(Some(&admin), "put quotas").is_ok());
+            assert!(policy
+                .check_reservation(Some(&admin), "bob", &[], "cancel")
+                .is_ok());
+            assert!(policy.check_listing(Some(&admin), "", "", &[]).is_ok());
+        }
+        assert!(policy
+            .check_admin(Some(&principal("bob", &["owner"])), "put quotas")
+            .is_err());
+        // everyone is trusted without authentication
+        assert!(policy.check_admin(None, "put quotas").is_ok());
+    }
+
+    #[test]
+    fn listing_should_be_limited_to_own_reservations_or_owned_resources() {
+        let policy = policy();
+        let alice = principal("alice", &[]);
+        let owners = vec!["alice".to_string()];
+        assert!(policy.check_listing(Some(&alice), "alice", "", &[]).is_ok());
+        assert!(policy.check_listing(Some(&alice), "", "", &[]).is_err());
+        assert!(policy.check_listing(Some(&alice), "bob", "", &[]).is_err());
+        assert!(policy
+            .check_listing(Some(&alice), "", "room", &owners)
+            .is_ok());
+        assert!(policy.check_listing(Some(&alice), "", "room", &[]).is_err());
+    }
+}
@@ -1,21 +1,146 @@
 use futures::Stream;
 use luckychacha_reservation::{ReservationManager, Rsvp};
 use luckychacha_reservation_abi::{
-    reservation_service_server::ReservationService, CancelRequest, CancelResponse, Config,
-    ConfirmRequest, ConfirmResponse, FilterRequest, FilterResponse, GetRequest, GetResponse,
-    ListenRequest, QueryRequest, ReserveRequest, ReserveResponse, UpdateRequest, UpdateResponse,
+    convert_to_duration, convert_to_utc_time, reservation_service_server::ReservationService,
+    ApproveRequest, ApproveResponse, AvailabilityRequest, AvailabilityResponse, CancelRequest,
+    CancelResponse, CheckInRequest, CheckInResponse, Config, ConfirmRequest, ConfirmResponse,
+    CountNoShowsRequest, CountNoShowsResponse, DeleteQuotaRequest, DeleteQuotaResponse, Error,
+    ExtendRequest, ExtendResponse, FilterRequest, FilterResponse, GetRequest, GetResourceRequest,
+    GetResourceResponse, GetResponse, LeaveWaitlistRequest, LeaveWaitlistResponse,
+    ListPendingApprovalsRequest, ListPendingApprovalsResponse, ListPreemptionsRequest,
+    ListPreemptionsResponse, ListQuotasRequest, ListQuotasResponse, ListWaitlistRequest,
+    ListWaitlistResponse, ListenRequest, PutQuotaRequest, PutQuotaResponse, PutResourceRequest,
+    PutResourceResponse, QueryRequest, RejectRequest, RejectResponse, ReleaseRequest,
+    ReleaseResponse, Reservation, ReserveAnyRequest, ReserveAnyResponse, ReserveRequest,
+    ReserveResponse, SearchRequest, SearchResponse, UpdateRequest, UpdateResponse,
 };
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tonic::{async_trait, Request, Response, Status};
 
-use crate::{ReservationStream, RsvpService, TonicReceiverStream};
+use crate::{
+    auth, policy::Policy, Principal, ReservationStream, RsvpService, TonicReceiverStream, ADMIN,
+};
 
 impl RsvpService {
     pub async fn from_config(config: &Config) -> Result<Self, anyhow::Error> {
         Ok(Self {
-            manager: ReservationManager::from_config(&config.db).await?,
+            manager: Arc::new(ReservationManager::from_config(config).await?),
+            policy: Policy::from_config(&config.rbac),
         })
     }
+
+    /// the manager scoped to the tenant of the request.
+    #[allow(clippy::result_large_err)]
+    fn scoped<T>(&self, request: &Request<T>) -> Result<ReservationManager, Status> {
+        Ok(self.manager.for_tenant(auth::tenant(request)?))
+    }
+
+    /// owners of a resource, who may manage its reservations. None for unknown resources.
+    async fn owners(
+        &self,
+        manager: &ReservationManager,
+        resource_id: &str,
+    ) -> Result<Vec<String>, Error> {
+        match manager.get_resource(resource_id).await {
+            Ok(resource) => Ok(resource.approvers),
+            Err(Error::ResourceNotFound(_)) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// make sure the caller may manage the reservation.
+    async fn authorize_reservation(
+        &self,
+        manager: &ReservationManager,
+        caller: Option<&Principal>,
+        id: i64,
+        action: &str,
+    ) -> Result<(), Error> {
+        if caller.is_none() {
+            return Ok(());
+        }
+        let rsvp = manager.get(id).await?;
+        let owners = self.owners(manager, &rsvp.resource_id).await?;
+        self.policy.check_reservation(
+            caller,
+            &rsvp.user_id,
+            &owners,
+            &format!("{action} reservation {id}"),
+        )
+    }
+
+    /// make sure the caller may list reservations of the user on the resource.
+    async fn authorize_listing(
+        &self,
+        manager: &ReservationManager,
+        caller: Option<&Principal>,
+        user_id: &str,
+        resource_id: &str,
+    ) -> Result<(), Error> {
+        if caller.is_none() {
+            return Ok(());
+        }
+        let owners = match resource_id {
+            "" => vec![],
+            rid => self.owners(manager, rid).await?,
+        };
+        self.policy
+            .check_listing(caller, user_id, resource_id, &owners)
+    }
+
+    /// priority decides whose reservations get bumped, only owners of the resource and admins
+    /// may hand it out. Anyone else's is dropped. Without a resource, only admins keep it.
+    async fn restrict_priority(
+        &self,
+        manager: &ReservationManager,
+        caller: Option<&Principal>,
+        resource_id: &str,
+        rsvp: &mut Reservation,
+    ) -> Result<(), Error> {
+        if caller.is_none() || rsvp.priority == 0 {
+            return Ok(());
+        }
+        let owners = match resource_id {
+            "" => vec![],
+            rid => self.owners(manager, rid).await?,
+        };
+        if self
+            .policy
+            .check_owner(
+                caller,
+                &owners,
+                &format!("prioritize reservations of {resource_id}"),
+            )
+            .is_err()
+        {
+            rsvp.priority = 0;
+        }
+        Ok(())
+    }
+
+    /// make sure the caller may decide on the pending reservation: approvers of its resource
+    /// and admins, who get a manager letting them decide on any. Without a caller, the manager
+    /// still makes sure the approver named in the request is one of the resource.
+    async fn authorize_approval(
+        &self,
+        manager: &ReservationManager,
+        caller: Option<&Principal>,
+        id: i64,
+        action: &str,
+    ) -> Result<ReservationManager, Error> {
+        let Some(caller) = caller else {
+            return Ok(manager.clone());
+        };
+        let rsvp = manager.get(id).await?;
+        let owners = self.owners(manager, &rsvp.resource_id).await?;
+        self.policy
+            .check_owner(Some(caller), &owners, &format!("{action} reservation {id}"))?;
+        if self.policy.has_role(caller, ADMIN) {
+            return Ok(manager.as_admin());
+        }
+        Ok(manager.clone())
+    }
 }
 
 #[async_trait]
@@ -24,13 +149,71 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<ReserveRequest>,
     ) -> Result<Response<ReserveResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
         let request = request.into_inner();
         if request.reservation.is_none() {
             return Err(Status::invalid_argument("missing reservation"));
         }
-        let reservation = self.manager.reserve(request.reservation.unwrap()).await?;
+        let mut rsvp = request.reservation.unwrap();
+        if let Some(caller) = caller.as_ref() {
+            rsvp.user_id = caller.user_id.clone();
+        }
+        // also the priority of the waitlist entry, and what it's promoted to
+        let resource_id = rsvp.resource_id.clone();
+        self.restrict_priority(&manager, caller.as_ref(), &resource_id, &mut rsvp)
+            .await?;
+        let ret = if request.preempt {
+            // bumps reservations of others
+            let owners = self.owners(&manager, &rsvp.resource_id).await?;
+            self.policy.check_owner(
+                caller.as_ref(),
+                &owners,
+                &format!("preempt reservations of {}", rsvp.resource_id),
+            )?;
+            manager
+                .reserve_preempting(rsvp.clone(), request.preempt_reason)
+                .await
+        } else {
+            manager.reserve(rsvp.clone()).await.map(|v| (v, vec![]))
+        };
+        match ret {
+            Ok((reservation, preempted)) => Ok(Response::new(ReserveResponse {
+                reservation: Some(reservation),
+                waitlist_entry: None,
+                preempted,
+            })),
+            Err(Error::ConflictReservation(_)) if request.waitlist => {
+                let entry = manager.join_waitlist(rsvp).await?;
+                Ok(Response::new(ReserveResponse {
+                    reservation: None,
+                    waitlist_entry: Some(entry),
+                    preempted: vec![],
+                }))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn reserve_any(
+        &self,
+        request: Request<ReserveAnyRequest>,
+    ) -> Result<Response<ReserveAnyResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let mut request = request.into_inner();
+        let Some(rsvp) = request.reservation.as_mut() else {
+            return Err(Status::invalid_argument("missing reservation"));
+        };
+        // the resource isn't picked yet
+        self.restrict_priority(&manager, caller.as_ref(), "", rsvp)
+            .await?;
+        if let Some(caller) = caller {
+            rsvp.user_id = caller.user_id;
+        }
+        let reservation = manager.reserve_any(request).await?;
 
-        Ok(Response::new(ReserveResponse {
+        Ok(Response::new(ReserveAnyResponse {
             reservation: Some(reservation),
         }))
     }
@@ -39,19 +222,122 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<ConfirmRequest>,
     ) -> Result<Response<ConfirmResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
         let request = request.into_inner();
-        let rsvp = self.manager.change_status(request.id).await?;
+        self.authorize_reservation(&manager, caller.as_ref(), request.id, "confirm")
+            .await?;
+        let rsvp = manager.change_status(request.id).await?;
         Ok(Response::new(ConfirmResponse {
             reservation: Some(rsvp),
         }))
     }
 
+    async fn check_in(
+        &self,
+        request: Request<CheckInRequest>,
+    ) -> Result<Response<CheckInResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        self.authorize_reservation(&manager, caller.as_ref(), request.id, "check in")
+            .await?;
+        let rsvp = manager.check_in(request.id).await?;
+        Ok(Response::new(CheckInResponse {
+            reservation: Some(rsvp),
+        }))
+    }
+
+    async fn count_no_shows(
+        &self,
+        request: Request<CountNoShowsRequest>,
+    ) -> Result<Response<CountNoShowsResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        self.policy.check_user(
+            caller.as_ref(),
+            &request.user_id,
+            &format!("count no-shows of {}", request.user_id),
+        )?;
+        let count = manager.count_no_shows(&request.user_id).await?;
+        Ok(Response::new(CountNoShowsResponse { count }))
+    }
+
+    async fn approve(
+        &self,
+        request: Request<ApproveRequest>,
+    ) -> Result<Response<ApproveResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let manager = self
+            .authorize_approval(&manager, caller.as_ref(), request.id, "approve")
+            .await?;
+        let approver = caller.map(|v| v.user_id).unwrap_or(request.approver);
+        let rsvp = manager
+            .approve(request.id, approver, request.reason)
+            .await?;
+        Ok(Response::new(ApproveResponse {
+            reservation: Some(rsvp),
+        }))
+    }
+
+    async fn reject(
+        &self,
+        request: Request<RejectRequest>,
+    ) -> Result<Response<RejectResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let manager = self
+            .authorize_approval(&manager, caller.as_ref(), request.id, "reject")
+            .await?;
+        let approver = caller.map(|v| v.user_id).unwrap_or(request.approver);
+        let rsvp = manager.reject(request.id, approver, request.reason).await?;
+        Ok(Response::new(RejectResponse {
+            reservation: Some(rsvp),
+        }))
+    }
+
+    async fn list_pending_approvals(
+        &self,
+        request: Request<ListPendingApprovalsRequest>,
+    ) -> Result<Response<ListPendingApprovalsResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let Some(mut query) = request.query else {
+            return Err(Status::invalid_argument("missing query"));
+        };
+        // approvers list their own ones, admins anyone's
+        if let Some(caller) = caller.as_ref() {
+            if query.approver.is_empty() {
+                query.approver = caller.user_id.clone();
+            }
+        }
+        self.policy.check_user(
+            caller.as_ref(),
+            &query.approver,
+            &format!("list pending approvals of {}", query.approver),
+        )?;
+        let (pager, rsvp) = manager.list_pending_approvals(query).await?;
+        Ok(Response::new(ListPendingApprovalsResponse {
+            reservations: rsvp,
+            pager: Some(pager),
+        }))
+    }
+
     async fn update(
         &self,
         request: Request<UpdateRequest>,
     ) -> Result<Response<UpdateResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
         let request = request.into_inner();
-        let rsvp = self.manager.update_note(request.id, request.note).await?;
+        self.authorize_reservation(&manager, caller.as_ref(), request.id, "update")
+            .await?;
+        let rsvp = manager.update_note(request.id, request.note).await?;
         Ok(Response::new(UpdateResponse {
             reservation: Some(rsvp),
         }))
@@ -61,16 +347,59 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<CancelRequest>,
     ) -> Result<Response<CancelResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
         let request = request.into_inner();
-        let rsvp = self.manager.delete(request.id).await?;
+        self.authorize_reservation(&manager, caller.as_ref(), request.id, "cancel")
+            .await?;
+        let rsvp = manager.delete(request.id).await?;
         Ok(Response::new(CancelResponse {
             reservation: Some(rsvp),
         }))
     }
 
+    async fn release(
+        &self,
+        request: Request<ReleaseRequest>,
+    ) -> Result<Response<ReleaseResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        self.authorize_reservation(&manager, caller.as_ref(), request.id, "release")
+            .await?;
+        let rsvp = manager
+            .release(request.id, request.end.map(convert_to_utc_time))
+            .await?;
+        Ok(Response::new(ReleaseResponse {
+            reservation: Some(rsvp),
+        }))
+    }
+
+    async fn extend(
+        &self,
+        request: Request<ExtendRequest>,
+    ) -> Result<Response<ExtendResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let Some(by) = request.by else {
+            return Err(Status::invalid_argument("missing duration"));
+        };
+        self.authorize_reservation(&manager, caller.as_ref(), request.id, "extend")
+            .await?;
+        let rsvp = manager.extend(request.id, convert_to_duration(&by)).await?;
+        Ok(Response::new(ExtendResponse {
+            reservation: Some(rsvp),
+        }))
+    }
+
     async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
         let request = request.into_inner();
-        let rsvp = self.manager.get(request.id).await?;
+        self.authorize_reservation(&manager, caller.as_ref(), request.id, "get")
+            .await?;
+        let rsvp = manager.get(request.id).await?;
         Ok(Response::new(GetResponse {
             reservation: Some(rsvp),
         }))
@@ -82,11 +411,20 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<QueryRequest>,
     ) -> Result<Response<Self::queryStream>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
         let request = request.into_inner();
-        if request.query.is_none() {
+        let Some(query) = request.query else {
             return Err(Status::invalid_argument("missing query"));
-        }
-        let rsvps = self.manager.query(request.query.unwrap()).await;
+        };
+        self.authorize_listing(
+            &manager,
+            caller.as_ref(),
+            &query.user_id,
+            &query.resource_id,
+        )
+        .await?;
+        let rsvps = manager.query(query).await;
         let stream = TonicReceiverStream::new(rsvps);
         Ok(Response::new(Box::pin(stream)))
     }
@@ -95,24 +433,210 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<FilterRequest>,
     ) -> Result<Response<FilterResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
         let request = request.into_inner();
-        if request.filter.is_none() {
+        let Some(filter) = request.filter else {
             return Err(Status::invalid_argument("missing filter"));
-        }
-        let (pager, rsvp) = self.manager.filter(request.filter.unwrap()).await?;
+        };
+        self.authorize_listing(
+            &manager,
+            caller.as_ref(),
+            &filter.user_id,
+            &filter.resource_id,
+        )
+        .await?;
+        let (pager, rsvp) = manager.filter(filter).await?;
         Ok(Response::new(FilterResponse {
             reservations: rsvp,
             pager: Some(pager),
         }))
     }
 
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let Some(search) = request.search else {
+            return Err(Status::invalid_argument("missing search"));
+        };
+        self.authorize_listing(
+            &manager,
+            caller.as_ref(),
+            &search.user_id,
+            &search.resource_id,
+        )
+        .await?;
+        let (pager, rsvp) = manager.search(search).await?;
+        Ok(Response::new(SearchResponse {
+            reservations: rsvp,
+            pager: Some(pager),
+        }))
+    }
+
+    async fn put_resource(
+        &self,
+        request: Request<PutResourceRequest>,
+    ) -> Result<Response<PutResourceResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let Some(resource) = request.resource else {
+            return Err(Status::invalid_argument("missing resource"));
+        };
+        // owners may change their resource, only admins add new ones
+        let owners = self.owners(&manager, &resource.id).await?;
+        self.policy.check_owner(
+            caller.as_ref(),
+            &owners,
+            &format!("put resource {}", resource.id),
+        )?;
+        let resource = manager.put_resource(resource).await?;
+        Ok(Response::new(PutResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    async fn get_resource(
+        &self,
+        request: Request<GetResourceRequest>,
+    ) -> Result<Response<GetResourceResponse>, Status> {
+        // anyone may look up resources
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let resource = manager.get_resource(&request.id).await?;
+        Ok(Response::new(GetResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    async fn availability(
+        &self,
+        request: Request<AvailabilityRequest>,
+    ) -> Result<Response<AvailabilityResponse>, Status> {
+        // anyone may look for free slots
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let slots = manager.availability(request).await?;
+        Ok(Response::new(AvailabilityResponse { slots }))
+    }
+
+    async fn put_quota(
+        &self,
+        request: Request<PutQuotaRequest>,
+    ) -> Result<Response<PutQuotaResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        let Some(quota) = request.quota else {
+            return Err(Status::invalid_argument("missing quota"));
+        };
+        self.policy
+            .check_admin(caller.as_ref(), &format!("put quota {}", quota.id))?;
+        let quota = manager.put_quota(quota).await?;
+        Ok(Response::new(PutQuotaResponse { quota: Some(quota) }))
+    }
+
+    async fn list_quotas(
+        &self,
+        request: Request<ListQuotasRequest>,
+    ) -> Result<Response<ListQuotasResponse>, Status> {
+        // anyone may know the limits they have to follow
+        let manager = self.scoped(&request)?;
+        let quotas = manager.list_quotas().await?;
+        Ok(Response::new(ListQuotasResponse { quotas }))
+    }
+
+    async fn delete_quota(
+        &self,
+        request: Request<DeleteQuotaRequest>,
+    ) -> Result<Response<DeleteQuotaResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        self.policy
+            .check_admin(caller.as_ref(), &format!("delete quota {}", request.id))?;
+        let quota = manager.delete_quota(&request.id).await?;
+        Ok(Response::new(DeleteQuotaResponse { quota: Some(quota) }))
+    }
+
+    async fn list_preemptions(
+        &self,
+        request: Request<ListPreemptionsRequest>,
+    ) -> Result<Response<ListPreemptionsResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        // the caller's own ones unless asked for someone else's
+        let user_id = match (request.user_id.as_str(), caller.as_ref()) {
+            ("", Some(caller)) => caller.user_id.clone(),
+            _ => request.user_id,
+        };
+        self.policy.check_user(
+            caller.as_ref(),
+            &user_id,
+            &format!("list preemptions of {user_id}"),
+        )?;
+        let preemptions = manager.list_preemptions(&user_id).await?;
+        Ok(Response::new(ListPreemptionsResponse { preemptions }))
+    }
+
+    async fn list_waitlist(
+        &self,
+        request: Request<ListWaitlistRequest>,
+    ) -> Result<Response<ListWaitlistResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        self.authorize_listing(
+            &manager,
+            caller.as_ref(),
+            &request.user_id,
+            &request.resource_id,
+        )
+        .await?;
+        let entries = manager.list_waitlist(request).await?;
+        Ok(Response::new(ListWaitlistResponse { entries }))
+    }
+
+    async fn leave_waitlist(
+        &self,
+        request: Request<LeaveWaitlistRequest>,
+    ) -> Result<Response<LeaveWaitlistResponse>, Status> {
+        let caller = auth::principal(&request);
+        let manager = self.scoped(&request)?;
+        let request = request.into_inner();
+        if caller.is_some() {
+            let rsvp = manager
+                .get_waitlist_entry(request.id)
+                .await?
+                .get_reservation();
+            let owners = self.owners(&manager, &rsvp.resource_id).await?;
+            self.policy.check_reservation(
+                caller.as_ref(),
+                &rsvp.user_id,
+                &owners,
+                &format!("remove waitlist entry {}", request.id),
+            )?;
+        }
+        let entry = manager.leave_waitlist(request.id).await?;
+        Ok(Response::new(LeaveWaitlistResponse { entry: Some(entry) }))
+    }
+
     type listenStream = ReservationStream;
 
     async fn listen(
         &self,
-        _request: Request<ListenRequest>,
+        request: Request<ListenRequest>,
     ) -> Result<Response<Self::listenStream>, Status> {
-        todo!()
+        // every change of every reservation of the tenant, the change queue keeps the tenant
+        self.policy
+            .check_admin(auth::principal(&request).as_ref(), "listen to all changes")?;
+        // the manager doesn't consume the change queue yet
+        Err(Status::unimplemented("listen is not supported yet"))
     }
 }
 
@@ -156,9 +680,7 @@ mod tests {
             "2023-01-05 14:00:00+0800".parse().unwrap(),
             "test a note",
         );
-        let request: Request<ReserveRequest> = Request::new(ReserveRequest {
-            reservation: Some(reservation),
-        });
+        let request: Request<ReserveRequest> = Request::new(ReserveRequest::new(reservation));
         let res = service.reserve(request).await.unwrap();
 
         assert!(res.into_inner().reservation.is_some());
@@ -3,13 +3,24 @@ mod test_utils;
 use crate::test_utils::TestConfig;
 
 use futures::StreamExt;
+use jsonwebtoken::{encode, get_current_timestamp, EncodingKey, Header};
 use luckychacha_reservation_abi::{
-    reservation_service_client::ReservationServiceClient, Config, FilterRequest, FilterResponse,
-    QueryRequest, Reservation, ReservationFilterBuilder, ReservationQueryBuilder, ReserveRequest,
+    reservation_service_client::ReservationServiceClient, ApproveRequest, AuthConfig,
+    CancelRequest, Config, FilterRequest, FilterResponse, GetRequest, JwtAlgorithm,
+    ListWaitlistRequest, ListenRequest, PutResourceRequest, QueryRequest, RateLimit,
+    RateLimitConfig, Reservation, ReservationFilterBuilder, ReservationQueryBuilder,
+    ReservationStatus, ReserveRequest, Resource, TlsConfig,
+};
+use luckychacha_reservation_service::{start_server, start_server_with_shutdown, TENANT_HEADER};
+use std::{collections::BTreeMap, time::Duration};
+use tokio::sync::oneshot;
+use tonic::{
+    transport::{Certificate, Channel, ClientTlsConfig, Identity},
+    Code,
+};
+use tonic_health::proto::{
+    health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
 };
-use luckychacha_reservation_service::start_server;
-use std::time::Duration;
-use tonic::transport::Channel;
 
 #[tokio::test]
 async fn grpc_server_should_work() {
@@ -20,8 +31,8 @@ async fn grpc_server_should_work() {
     let mut rsvp = Reservation::new_pending(
         "luckychacha-id",
         "ocean-view-room-666",
-        "2022-12-25T15:00:00+0800".parse().unwrap(),
-        "2022-12-28T11:00:00+0800".parse().unwrap(),
+        "2032-12-25T15:00:00+0800".parse().unwrap(),
+        "2032-12-28T11:00:00+0800".parse().unwrap(),
         String::from("I'll arrive at 3pm. Please help to upgrade to execuitive room if possible."),
     );
     let request = ReserveRequest::new(rsvp.clone());
@@ -40,14 +51,28 @@ async fn grpc_server_should_work() {
     let rsvp2 = Reservation::new_pending(
         "luckychacha-id",
         "ocean-view-room-666",
-        "2022-12-25T15:00:00+0800".parse().unwrap(),
-        "2022-12-28T11:00:00+0800".parse().unwrap(),
+        "2032-12-25T15:00:00+0800".parse().unwrap(),
+        "2032-12-28T11:00:00+0800".parse().unwrap(),
         String::from("I'll arrive at 3pm. Please help to upgrade to execuitive room if possible."),
     );
     let request = ReserveRequest::new(rsvp2.clone());
     let ret = client.reserve(request).await;
     assert!(ret.is_err());
 
+    // 3.join the waitlist instead when the window is taken
+    let request = ReserveRequest::new_or_wait(rsvp2.clone());
+    let ret = client.reserve(request).await.unwrap().into_inner();
+    assert!(ret.reservation.is_none());
+    let entry = ret.waitlist_entry.unwrap();
+    assert_eq!(entry.get_reservation().id, 0);
+    let entries = client
+        .list_waitlist(ListWaitlistRequest::new("ocean-view-room-666", ""))
+        .await
+        .unwrap()
+        .into_inner()
+        .entries;
+    assert_eq!(entries, vec![entry]);
+
     // 4.query grpc interface test.
 
     // 5.filter grpc interface test.
@@ -99,10 +124,11 @@ async fn grpc_filter_should_work() {
         .into_inner();
 
     let pager = pager.unwrap();
-    assert_eq!(pager.next, Some(filter.page_size));
+    assert!(pager.next.is_some());
     assert_eq!(pager.prev, None);
     assert_eq!(pager.total, None);
     assert_eq!(reservations.len(), filter.page_size as usize);
+    let first_page = reservations;
 
     let filter = filter.next_page(&pager).unwrap();
     // then we get next page
@@ -117,9 +143,428 @@ async fn grpc_filter_should_work() {
 
     let pager = pager.unwrap();
 
-    assert_eq!(pager.next, filter.cursor.map(|v| v + filter.page_size));
-    assert_eq!(pager.prev, filter.cursor.map(|v| v + 1));
+    assert!(pager.next.is_some());
+    assert!(pager.prev.is_some());
     assert_eq!(reservations.len(), filter.page_size as usize);
+    assert!(reservations[0].id > first_page.last().unwrap().id);
+
+    // and go back to exactly the first page
+    let filter = filter.prev_page(&pager).unwrap();
+    let FilterResponse {
+        pager,
+        reservations,
+    } = client
+        .filter(FilterRequest::new(filter.clone()))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let pager = pager.unwrap();
+    assert_eq!(pager.prev, None);
+    assert!(pager.next.is_some());
+    assert_eq!(reservations, first_page);
+
+    // a page token can't be used with another filter
+    let filter = ReservationFilterBuilder::default()
+        .user_id("another-id")
+        .cursor(pager.next.unwrap())
+        .build()
+        .unwrap();
+    let status = client.filter(FilterRequest::new(filter)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn grpc_server_should_authenticate_callers() {
+    let mut tconfig = TestConfig::with_server_port(50003);
+    tconfig.config.auth = Some(AuthConfig {
+        algorithm: JwtAlgorithm::HS256,
+        secret: "test-secret".into(),
+        public_key: String::new(),
+        jwks: String::new(),
+        issuer: String::new(),
+        audience: String::new(),
+    });
+    let mut client = get_test_client(&tconfig).await;
+
+    let rsvp = Reservation::new_pending(
+        "mallory",
+        "ocean-view-room-666",
+        "2022-12-25T15:00:00+0800".parse().unwrap(),
+        "2022-12-28T11:00:00+0800".parse().unwrap(),
+        "",
+    );
+    let status = client
+        .reserve(ReserveRequest::new(rsvp.clone()))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    // the user id is the token's subject, not what the client claims
+    let ret = client
+        .reserve(authorized(ReserveRequest::new(rsvp), "alice"))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    assert_eq!(ret.user_id, "alice");
+
+    // only alice may cancel her reservation
+    let status = client
+        .cancel(authorized(CancelRequest { id: ret.id }, "bob"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    let canceled = client
+        .cancel(authorized(CancelRequest { id: ret.id }, "alice"))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    assert_eq!(canceled.id, ret.id);
+
+    // pending reservations are decided on by approvers of the resource and admins
+    let lab = Resource::new("lab").with_approvers(["dora"]);
+    client
+        .put_resource(authorized(
+            PutResourceRequest {
+                resource: Some(lab),
+            },
+            "luckychacha-admin",
+        ))
+        .await
+        .unwrap();
+    let rsvp = Reservation::new_pending(
+        "alice",
+        "lab",
+        "2030-01-01T09:00:00Z".parse().unwrap(),
+        "2030-01-01T10:00:00Z".parse().unwrap(),
+        "",
+    );
+    let ret = client
+        .reserve(authorized(ReserveRequest::new(rsvp), "alice"))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    let approve = |user_id: &str| {
+        authorized(
+            ApproveRequest {
+                id: ret.id,
+                approver: String::new(),
+                reason: String::new(),
+            },
+            user_id,
+        )
+    };
+    let status = client.approve(approve("bob")).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    let approved = client
+        .approve(approve("luckychacha-admin"))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    assert_eq!(approved.status, ReservationStatus::Confirmed as i32);
+
+    // only owners of the resource and admins may prioritize reservations
+    let prioritized = |uid: &str, day: u32| Reservation {
+        priority: 5,
+        ..Reservation::new_pending(
+            uid,
+            "lab",
+            format!("2030-01-{day:02}T09:00:00Z").parse().unwrap(),
+            format!("2030-01-{day:02}T10:00:00Z").parse().unwrap(),
+            "",
+        )
+    };
+    let reserve = |rsvp: Reservation, user_id: &str| authorized(ReserveRequest::new(rsvp), user_id);
+    let ret = client
+        .reserve(reserve(prioritized("alice", 2), "alice"))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    assert_eq!(ret.priority, 0);
+    let ret = client
+        .reserve(reserve(prioritized("dora", 3), "dora"))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    assert_eq!(ret.priority, 5);
+
+    // only admins may listen to all changes, which isn't supported yet
+    let status = client
+        .listen(authorized(ListenRequest {}, "bob"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    let status = client
+        .listen(authorized(ListenRequest {}, "luckychacha-admin"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[tokio::test]
+async fn grpc_server_should_isolate_tenants() {
+    let tconfig = TestConfig::with_server_port(50004);
+    let mut client = get_test_client(&tconfig).await;
+
+    let rsvp = Reservation::new_pending(
+        "alice",
+        "ocean-view-room-666",
+        "2022-12-25T15:00:00+0800".parse().unwrap(),
+        "2022-12-28T11:00:00+0800".parse().unwrap(),
+        "",
+    );
+    // the same room in the same window, booked by two business units
+    let mut ids = vec![];
+    for tenant in ["sales", "support"] {
+        let ret = client
+            .reserve(with_tenant(ReserveRequest::new(rsvp.clone()), tenant))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+        assert_eq!(ret.tenant_id, tenant);
+        ids.push(ret.id);
+    }
+
+    let status = client
+        .get(with_tenant(GetRequest { id: ids[1] }, "sales"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::FailedPrecondition);
+    let ret = client
+        .get(with_tenant(GetRequest { id: ids[1] }, "support"))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    assert_eq!(ret.tenant_id, "support");
+}
+
+#[tokio::test]
+async fn grpc_server_should_identify_callers_by_client_certificate() {
+    let mut tconfig = TestConfig::with_server_port(50005);
+    tconfig.config.server.tls = Some(TlsConfig {
+        cert: "fixtures/tls_server.pem".into(),
+        key: "fixtures/tls_server.key".into(),
+        client_ca: "fixtures/tls_ca.pem".into(),
+    });
+    let config = tconfig.config.clone();
+    tokio::spawn(async move {
+        start_server(&config).await.unwrap();
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let ca = Certificate::from_pem(std::fs::read("fixtures/tls_ca.pem").unwrap());
+    let url = tconfig.server.server_url(true);
+    // clients without certificate are turned away, during or right after the handshake
+    let anonymous = Channel::from_shared(url.clone())
+        .unwrap()
+        .tls_config(
+            ClientTlsConfig::new()
+                .domain_name("localhost")
+                .ca_certificate(ca.clone()),
+        )
+        .unwrap()
+        .connect()
+        .await;
+    let rejected = match anonymous {
+        Ok(channel) => ReservationServiceClient::new(channel)
+            .get(GetRequest { id: 1 })
+            .await
+            .is_err(),
+        Err(_) => true,
+    };
+    assert!(rejected);
+
+    let identity = Identity::from_pem(
+        std::fs::read("fixtures/tls_client.pem").unwrap(),
+        std::fs::read("fixtures/tls_client.key").unwrap(),
+    );
+    let channel = Channel::from_shared(url)
+        .unwrap()
+        .tls_config(
+            ClientTlsConfig::new()
+                .domain_name("localhost")
+                .ca_certificate(ca)
+                .identity(identity),
+        )
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = ReservationServiceClient::new(channel);
+
+    // the certificate of alice at sales decides who books and where
+    let rsvp = Reservation::new_pending(
+        "bob",
+        "ocean-view-room-777",
+        "2022-12-25T15:00:00+0800".parse().unwrap(),
+        "2022-12-28T11:00:00+0800".parse().unwrap(),
+        "",
+    );
+    let ret = client
+        .reserve(ReserveRequest::new(rsvp))
+        .await
+        .unwrap()
+        .into_inner()
+        .reservation
+        .unwrap();
+    assert_eq!(ret.user_id, "alice");
+    assert_eq!(ret.tenant_id, "sales");
+}
+
+#[tokio::test]
+async fn grpc_server_should_rate_limit_callers() {
+    let mut tconfig = TestConfig::with_server_port(50006);
+    tconfig.config.rate_limit = RateLimitConfig {
+        default: None,
+        methods: BTreeMap::from([("get".into(), RateLimit { rate: 1, burst: 2 })]),
+    };
+    let mut client = get_test_client(&tconfig).await;
+
+    for _ in 0..2 {
+        let status = client.get(GetRequest { id: 1 }).await.unwrap_err();
+        assert_ne!(status.code(), Code::ResourceExhausted);
+    }
+    let status = client.get(GetRequest { id: 1 }).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert_eq!(status.metadata().get("retry-after").unwrap(), "1");
+
+    // other RPCs have no limit
+    make_reservations(&mut client, 3).await;
+}
+
+#[tokio::test]
+async fn grpc_server_should_report_health_without_authentication() {
+    let mut tconfig = TestConfig::with_server_port(50007);
+    tconfig.config.auth = Some(AuthConfig {
+        algorithm: JwtAlgorithm::HS256,
+        secret: "test-secret".into(),
+        public_key: String::new(),
+        jwks: String::new(),
+        issuer: String::new(),
+        audience: String::new(),
+    });
+    let mut client = get_test_client(&tconfig).await;
+    let status = client.get(GetRequest { id: 1 }).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let channel = Channel::from_shared(tconfig.server.server_url(false))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut health = HealthClient::new(channel);
+    for service in ["", "reservation.ReservationService"] {
+        let ret = health
+            .check(HealthCheckRequest {
+                service: service.into(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(ret.status(), ServingStatus::Serving);
+    }
+}
+
+#[tokio::test]
+async fn grpc_server_should_shut_down_gracefully() {
+    let mut tconfig = TestConfig::with_server_port(50008);
+    tconfig.config.server.drain_period = 1;
+    let config = tconfig.config.clone();
+    let (shutdown, signal) = oneshot::channel::<()>();
+    let server = tokio::spawn(async move {
+        start_server_with_shutdown(&config, async {
+            let _ = signal.await;
+        })
+        .await
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let url = tconfig.server.server_url(false);
+    let mut client = ReservationServiceClient::connect(url.clone())
+        .await
+        .unwrap();
+    // more than the server and the connection buffer, a stream nobody reads keeps the query
+    // waiting past the drain period
+    for i in 0..200 {
+        let rsvp = Reservation::new_pending(
+            "luckychacha-id",
+            format!("ocean-view-room-{i}"),
+            "2022-12-25T15:00:00+0800".parse().unwrap(),
+            "2022-12-28T11:00:00+0800".parse().unwrap(),
+            "x".repeat(50_000),
+        );
+        client.reserve(ReserveRequest::new(rsvp)).await.unwrap();
+    }
+    let query = ReservationQueryBuilder::default()
+        .user_id("luckychacha-id")
+        .status(ReservationStatus::Pending as i32)
+        .build()
+        .unwrap();
+    let mut stream = client
+        .query(QueryRequest::new(query))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(stream.next().await.unwrap().is_ok());
+
+    shutdown.send(()).unwrap();
+    let ret = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(ret.is_ok());
+    assert!(ReservationServiceClient::connect(url).await.is_err());
+
+    // the stream was cut off instead of pretending to be complete
+    let mut rows = 1;
+    let status = loop {
+        match stream.next().await {
+            Some(Ok(_)) => rows += 1,
+            Some(Err(status)) => break status,
+            None => panic!("expect the stream to fail, got {rows} rows"),
+        }
+    };
+    assert!(rows < 200);
+    assert_eq!(status.code(), Code::Unavailable);
+}
+
+fn with_tenant<T>(message: T, tenant: &str) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request
+        .metadata_mut()
+        .insert(TENANT_HEADER, tenant.parse().unwrap());
+    request
+}
+
+fn authorized<T>(message: T, user_id: &str) -> tonic::Request<T> {
+    let claims = serde_json::json!({ "sub": user_id, "exp": get_current_timestamp() + 60 });
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(b"test-secret"),
+    )
+    .unwrap();
+    let mut request = tonic::Request::new(message);
+    request
+        .metadata_mut()
+        .insert("authorization", format!("Bearer {token}").parse().unwrap());
+    request
 }
 
 async fn get_test_client(config: &Config) -> ReservationServiceClient<Channel> {